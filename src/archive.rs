@@ -0,0 +1,88 @@
+use crate::error::TodoError;
+use crate::store::{read_data, write_data};
+use crate::todo::Todo;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// A completed item that was moved out of the todo list.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ArchivedTodo {
+    #[serde(flatten)]
+    pub todo: Todo,
+    /// When the item was archived.
+    pub archived_at: DateTime<Utc>,
+}
+
+/// The file archived items of a todo list are kept in.
+/// Like the data file, each line is a single item in JSON format.
+pub struct Archive {
+    path: PathBuf,
+    encrypt: bool,
+}
+
+impl Archive {
+    /// Use the archive file at the given path.
+    pub fn new(path: impl Into<PathBuf>) -> Archive {
+        Archive {
+            path: path.into(),
+            encrypt: false,
+        }
+    }
+
+    /// Encrypt the archive file when writing it.
+    pub fn encrypted(mut self, encrypt: bool) -> Archive {
+        self.encrypt = encrypt;
+        self
+    }
+
+    /// The path of the archive file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Read the archived items, oldest first. A missing archive file has no items.
+    pub fn load(&self) -> Result<Vec<ArchivedTodo>, TodoError> {
+        let mut items: Vec<ArchivedTodo> = Vec::new();
+
+        if let Some(str) = read_data(&self.path)? {
+            for line in str.lines() {
+                let item = serde_json::from_str(line).map_err(|source| TodoError::ParseLine {
+                    line: line.to_string(),
+                    source,
+                })?;
+                items.push(item);
+            }
+        }
+
+        Ok(items)
+    }
+
+    /// Write the archived items to the archive file.
+    pub fn save(&self, items: &[ArchivedTodo]) -> Result<(), TodoError> {
+        let mut buf = String::new();
+        for item in items {
+            let item_serialized = serde_json::to_string(item).map_err(TodoError::Serialize)?;
+            buf.push_str(&item_serialized);
+            buf.push('\n');
+        }
+
+        write_data(
+            &self.path,
+            &buf,
+            self.encrypt,
+            "Could not write to the archive file",
+        )
+    }
+
+    /// Add items to the archive, marking them as archived now.
+    pub fn append(&self, items: Vec<Todo>) -> Result<(), TodoError> {
+        let mut archived = self.load()?;
+        let now = Utc::now();
+        archived.extend(items.into_iter().map(|todo| ArchivedTodo {
+            todo,
+            archived_at: now,
+        }));
+        self.save(&archived)
+    }
+}
@@ -0,0 +1,44 @@
+//! Opening the URLs attached to items with `todo link` in the browser.
+
+use crate::error::TodoError;
+use std::process::{Command, Stdio};
+
+/// Whether the text looks like a URL with a scheme, i.e., "https://example.com" or
+/// "mailto:me@example.com", so it can be handed to the browser.
+pub fn is_url(text: &str) -> bool {
+    text.split_once(':').is_some_and(|(scheme, rest)| {
+        scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+            && !rest.is_empty()
+    })
+}
+
+/// Open a URL in the default browser (or whichever app handles its scheme), without waiting for
+/// it to close.
+pub fn open(url: &str) -> Result<(), TodoError> {
+    let mut command = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(windows) {
+        // Unlike `start`, this doesn't split the URL at "&"
+        let mut command = Command::new("rundll32");
+        command.arg("url.dll,FileProtocolHandler");
+        command
+    } else {
+        Command::new("xdg-open")
+    };
+
+    let status = command
+        .arg(url)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map_err(|err| TodoError::io(format!("Could not open \"{url}\""), err))?;
+    if !status.success() {
+        return Err(TodoError::Usage(format!(
+            "Could not open \"{url}\": the opener exited with {status}."
+        )));
+    }
+    Ok(())
+}
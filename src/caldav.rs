@@ -0,0 +1,53 @@
+//! Uploading the items with a due date to a CalDAV calendar, so they show up in calendar apps.
+//!
+//! Every item is stored as its own calendar resource, named after its iCalendar UID, so uploading
+//! an item again replaces it on the server. The password is read from the TODO_CALDAV_PASSWORD
+//! environment variable, or asked for.
+
+use crate::error::TodoError;
+use crate::formats::ics;
+use crate::todo::Todo;
+use base64::prelude::*;
+use log::{debug, info};
+use std::env;
+
+/// Upload the items that have a due date to the calendar collection at `url`, returning how many
+/// were uploaded.
+pub fn sync(items: &[Todo], url: &str, user: Option<&str>) -> Result<usize, TodoError> {
+    let auth = match user {
+        Some(user) => Some(format!(
+            "Basic {}",
+            BASE64_STANDARD.encode(format!("{user}:{}", password()?))
+        )),
+        None => None,
+    };
+
+    let base = url.trim_end_matches('/');
+    let mut count = 0;
+    for item in items.iter().filter(|item| item.due.is_some()) {
+        let resource = format!("{base}/{}.ics", ics::uid(item));
+        debug!("Uploading \"{}\" to {resource}", item.label);
+        let mut request =
+            ureq::put(&resource).header("Content-Type", "text/calendar; charset=utf-8");
+        if let Some(auth) = &auth {
+            request = request.header("Authorization", auth);
+        }
+        request.send(ics::to_calendar(item)).map_err(|err| {
+            TodoError::Sync(format!(
+                "uploading \"{}\" to the CalDAV server failed: {err}",
+                item.label
+            ))
+        })?;
+        count += 1;
+    }
+    info!("Uploaded {count} item(s) to {base}");
+    Ok(count)
+}
+
+fn password() -> Result<String, TodoError> {
+    match env::var("TODO_CALDAV_PASSWORD") {
+        Ok(password) => Ok(password),
+        Err(_) => rpassword::prompt_password("CalDAV password: ")
+            .map_err(|err| TodoError::io("Could not read the password", err)),
+    }
+}
@@ -0,0 +1,744 @@
+use crate::dates;
+use crate::estimate::Estimate;
+use crate::list::{Filter, GroupBy, ItemRef, Selection, SortMode};
+use crate::recur::Recurrence;
+use crate::settings::SETTING_CHOICES;
+use crate::todo::{Priority, Status};
+use chrono::NaiveDate;
+use clap::builder::PossibleValuesParser;
+use clap::{Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
+use std::path::PathBuf;
+
+/// A Todo App that is simple, yet is actually useful.
+#[derive(Debug, Parser)]
+#[command(
+    name = "todo",
+    version,
+    about,
+    after_help = "Items can be given by their position as shown in `todo list`, or by their stable ID \
+(i.e., \"id:7\"), which doesn't change when other items are added, removed, or sorted."
+)]
+pub struct Cli {
+    /// Don't print the todo list after each mutation command (overrides the "silent" setting), or
+    /// the messages of --verbose
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
+
+    /// Print what is read, parsed, and written, i.e., the paths of the files used (-vv for more)
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+
+    /// The list to use instead of the one chosen with `todo use`
+    #[arg(short, long, global = true, value_name = "NAME")]
+    pub list: Option<String>,
+
+    /// The data file to use instead of a list (overrides TODO_DATA_FILE)
+    #[arg(long, global = true, value_name = "PATH", conflicts_with = "list")]
+    pub file: Option<PathBuf>,
+
+    /// Print without colors (overrides the "color" setting)
+    #[arg(long, global = true)]
+    pub no_color: bool,
+
+    /// Don't write anything, not even the settings file or the data directory (like the
+    /// "read-only" setting)
+    #[arg(long, global = true)]
+    pub read_only: bool,
+
+    /// Fail without changing anything when given items that don't exist, instead of skipping
+    /// them (overrides the "strict" setting)
+    #[arg(long, global = true)]
+    pub strict: bool,
+
+    /// Print what a command would change, and the todo list it would leave, without saving
+    /// anything
+    #[arg(long, global = true)]
+    pub dry_run: bool,
+
+    /// How errors are printed to stderr: as a message, or as a line of JSON with the error's code,
+    /// exit code, message, and context, for programs that run todo
+    #[arg(long, global = true, value_enum, default_value_t = ErrorFormat::Text)]
+    pub error_format: ErrorFormat,
+
+    /// Don't ask before removing every item or emptying the trash (overrides the "confirm" setting)
+    #[arg(short, long, global = true)]
+    pub yes: bool,
+
+    /// The action to run. Listing the todos is the default action.
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Add item(s) to the todo list. Without labels, asks for items one per line until an empty
+    /// line
+    Add {
+        /// The labels of the items to add
+        items: Vec<String>,
+    },
+    /// Print the items due today, after the overdue items
+    Today,
+    /// Print the items due this week by day (see the "week-start" setting), after the overdue
+    /// items
+    Week,
+    /// Print the items on a board, in a column per status: backlog, in progress, waiting, and done
+    Board,
+    /// Print the overdue items, the most overdue first, with how long ago they were due
+    Overdue,
+    /// Print the most urgent pending item, weighing its priority, due date, age, and whether it is
+    /// pinned (see the "*-weight" settings)
+    Next,
+    /// Plan the day: pick the most urgent pending items whose estimates fit in the "capacity"
+    /// setting, after the items due today, and mark them for today (see `todo list --planned`)
+    Plan {
+        /// How much work fits in today instead of the "capacity" setting, i.e., "4h" or "8pt"
+        #[arg(long, value_name = "ESTIMATE")]
+        capacity: Option<Estimate>,
+        /// Unmark the items planned for today
+        #[arg(long, conflicts_with = "capacity")]
+        clear: bool,
+    },
+    /// Go through the pending items one by one, choosing to keep, complete, edit, snooze, or
+    /// remove each (the changes are saved at the end)
+    Review,
+    /// Edit item(s) in the todo list, i.e., `todo edit 2 "new label"`, or `todo edit 1 2` to be
+    /// prompted for the new labels
+    Edit {
+        /// The item to edit followed by its new label, or only the items (or ranges) to edit
+        #[arg(required_unless_present_any = ["all", "interactive"], value_name = "ITEMS|LABEL")]
+        args: Vec<String>,
+        /// Edit the labels, and the notes below them, in $EDITOR instead of being prompted
+        /// (overrides the "edit-mode" setting)
+        #[arg(short, long)]
+        editor: bool,
+        /// Edit the whole todo list in $EDITOR, one item per line
+        #[arg(short, long, conflicts_with_all = ["args", "editor"])]
+        all: bool,
+        /// Pick the items to edit in a fuzzy finder: type to filter, Tab to select several, and
+        /// Enter to confirm
+        #[arg(short, long, conflicts_with_all = ["args", "all"])]
+        interactive: bool,
+    },
+    /// Print the todo list. Use the positions listed for commands that take items
+    List {
+        /// Only list items with all of these tags (i.e., "+work")
+        #[arg(value_parser = parse_tag_filter)]
+        tags: Vec<String>,
+        #[command(flatten)]
+        filter: Filter,
+        /// Print the items as a JSON array (overrides the "format" setting)
+        #[arg(long)]
+        json: bool,
+        /// Print the items as a table with a column for each field (overrides the "format" setting)
+        #[arg(long, conflicts_with = "json")]
+        table: bool,
+        /// Show when items were created and completed
+        #[arg(long)]
+        dates: bool,
+        /// List the items in sections, with a header and count for each
+        #[arg(short, long, value_enum)]
+        group_by: Option<GroupBy>,
+        /// Keep the list on screen, printing it again whenever the todo list changes
+        #[arg(short, long)]
+        watch: bool,
+        /// Only list the first items, i.e., `--limit 10`
+        #[arg(short = 'n', long, value_name = "COUNT")]
+        limit: Option<usize>,
+        /// Print the whole list even when it's longer than the terminal (overrides the "pager"
+        /// setting)
+        #[arg(long)]
+        no_pager: bool,
+    },
+    /// Print the items whose label matches a query, highlighting their positions
+    Search {
+        /// The text to search for (case-insensitive), or a pattern with --regex
+        query: String,
+        /// Treat the query as a regular expression
+        #[arg(short, long)]
+        regex: bool,
+    },
+    /// Replace text in the labels of all items, printing the labels it changes, i.e., `todo
+    /// replace colour color`
+    Replace {
+        /// The text to replace (matching case), or a pattern with --regex
+        from: String,
+        /// The text to put in its place. With --regex, "$1" (or "${name}") is a group of the match
+        to: String,
+        /// Treat the text to replace as a regular expression
+        #[arg(short, long)]
+        regex: bool,
+    },
+    /// Move item(s) from the todo list into the trash
+    Remove {
+        /// The items (or ranges, i.e., "1-4", or text in their labels) to remove, "all", or
+        /// "checked" (or "completed")
+        #[arg(required_unless_present = "interactive")]
+        items: Vec<Selection>,
+        /// Pick the items in a fuzzy finder instead: type to filter, Tab to select several, and
+        /// Enter to confirm
+        #[arg(short, long, conflicts_with = "items")]
+        interactive: bool,
+    },
+    /// Remove items with the same label as an earlier item, keeping the item created first and
+    /// adding the tags (and missing details) of its duplicates to it
+    Dedupe {
+        /// Also count labels that only differ in case as the same
+        #[arg(short, long)]
+        ignore_case: bool,
+    },
+    /// Clears all items from the todo list (equivalent to "remove all")
+    Clear,
+    /// Mark item(s) as completed
+    Check {
+        /// The items (or ranges, i.e., "1-4", or text in their labels) to check, or "all"
+        #[arg(required_unless_present = "interactive")]
+        items: Vec<Selection>,
+        /// Pick the pending items in a fuzzy finder instead: type to filter, Tab to select several,
+        /// and Enter to confirm
+        #[arg(short, long, conflicts_with = "items")]
+        interactive: bool,
+    },
+    /// Mark item(s) as incomplete
+    Uncheck {
+        /// The items (or ranges, i.e., "1-4", or text in their labels) to uncheck, or "all"
+        #[arg(required_unless_present = "interactive")]
+        items: Vec<Selection>,
+        /// Pick the completed items in a fuzzy finder instead: type to filter, Tab to select
+        /// several, and Enter to confirm
+        #[arg(short, long, conflicts_with = "items")]
+        interactive: bool,
+    },
+    /// Pin item(s), so they are always listed first
+    Pin {
+        /// The items (or ranges, i.e., "1-4", or text in their labels) to pin
+        #[arg(required = true)]
+        items: Vec<Selection>,
+    },
+    /// Stop listing item(s) first
+    Unpin {
+        /// The items (or ranges, i.e., "1-4", or text in their labels) to unpin, or "all"
+        #[arg(required = true)]
+        items: Vec<Selection>,
+    },
+    /// Focus on item(s), so `todo list` only shows the focused items until `todo unfocus`
+    Focus {
+        /// The items (or ranges, i.e., "1-4", or text in their labels) to focus on
+        #[arg(required = true)]
+        items: Vec<Selection>,
+    },
+    /// Stop focusing on item(s), or on every item if none are given, to list everything again
+    Unfocus {
+        /// The items (or ranges, i.e., "1-4", or text in their labels) to stop focusing on
+        items: Vec<Selection>,
+    },
+    /// Set the due date of an item, and how often it repeats, i.e., `todo due 3 friday --every
+    /// week`
+    Due {
+        /// The item to set the due date of
+        item: ItemRef,
+        /// The due date (i.e., "2025-03-01", "tomorrow", "next friday", "in 2 weeks", "jan 5"),
+        /// or "none" to remove it (and stop the item repeating)
+        #[arg(value_parser = parse_date_or_none, required_unless_present = "every")]
+        date: Option<OrNone<NaiveDate>>,
+        /// Bring the item back with the next due date when it is checked (i.e., "week", "2
+        /// days", "monday", "weekday", or an RRULE such as "FREQ=MONTHLY;BYDAY=1MO"), or "none"
+        /// to stop it repeating
+        #[arg(long, value_name = "RULE", value_parser = parse_recurrence_or_none)]
+        every: Option<OrNone<Recurrence>>,
+    },
+    /// Hide an item from `todo list` until a date, i.e., `todo snooze 4 3d`
+    Snooze {
+        /// The item to snooze
+        item: ItemRef,
+        /// When to list the item again (i.e., "3d", "2w", "monday", "jan 5"), or "none" to list
+        /// it again now
+        #[arg(value_parser = parse_date_or_none)]
+        until: OrNone<NaiveDate>,
+    },
+    /// Set the day an item can be started on, leaving it out of `todo list` until then, i.e.,
+    /// `todo start-date 5 2025-03-01`
+    StartDate {
+        /// The item to set the start date of
+        item: ItemRef,
+        /// The start date (i.e., "2025-03-01", "next monday", "in 2 weeks"), or "none" to remove
+        /// it
+        #[arg(value_parser = parse_date_or_none)]
+        date: OrNone<NaiveDate>,
+    },
+    /// Set how much work an item is expected to take (i.e., `todo estimate 3 2h` or `todo
+    /// estimate 3 5pt`), or print the estimated work left, in total and per tag
+    Estimate {
+        /// The item to estimate
+        #[arg(requires = "estimate")]
+        item: Option<ItemRef>,
+        /// A length of time (i.e., "45m", "2h", "1h30m") or story points (i.e., "3pt"), or "none"
+        /// to remove it
+        #[arg(value_parser = parse_estimate_or_none)]
+        estimate: Option<OrNone<Estimate>>,
+    },
+    /// Set the priority of an item
+    Priority {
+        /// The item to set the priority of
+        item: ItemRef,
+        /// "high", "medium", "low", or "none" to remove it
+        #[arg(value_parser = parse_priority_or_none)]
+        level: OrNone<Priority>,
+    },
+    /// Assign an item to someone in a shared list, i.e., `todo assign 3 alice`
+    Assign {
+        /// The item to assign
+        item: ItemRef,
+        /// Who is responsible for it, or "none" to unassign it
+        name: String,
+    },
+    /// Copy an item as a new, incomplete item, i.e., `todo copy 4 --to work`
+    Copy {
+        /// The item to copy
+        item: ItemRef,
+        /// The list to copy it into, instead of this one
+        #[arg(long, value_name = "LIST")]
+        to: Option<String>,
+    },
+    /// Replace an item with several items that keep its details, i.e.,
+    /// `todo split 3 "buy flour" "buy sugar"`
+    Split {
+        /// The item to split
+        item: ItemRef,
+        /// The labels of the new items
+        #[arg(required = true, num_args = 2..)]
+        labels: Vec<String>,
+    },
+    /// Combine two items into the first one, removing the second, i.e., `todo merge 3 7`
+    Merge {
+        /// The item to keep
+        item: ItemRef,
+        /// The item to merge into it
+        other: ItemRef,
+    },
+    /// Make an item wait for other items, i.e., `todo depends 5 on 2` (it is listed dimmed until
+    /// they are completed)
+    Depends {
+        /// The item that has to wait
+        item: ItemRef,
+        #[arg(
+            value_name = "on",
+            value_parser = PossibleValuesParser::new(["on"]),
+            hide_possible_values = true
+        )]
+        _on: String,
+        /// The items it waits for
+        #[arg(required = true)]
+        blockers: Vec<ItemRef>,
+        /// Stop waiting for these items instead
+        #[arg(short, long)]
+        remove: bool,
+    },
+    /// Add tag(s) to an item
+    Tag {
+        /// The item to tag
+        item: ItemRef,
+        /// The tags to add
+        #[arg(required = true)]
+        tags: Vec<String>,
+    },
+    /// Remove tag(s) from an item
+    Untag {
+        /// The item to untag
+        item: ItemRef,
+        /// The tags to remove, or "all"
+        #[arg(required = true)]
+        tags: Vec<String>,
+    },
+    /// Move an item to another position, shifting the items in between
+    Move {
+        /// The item to move
+        from: ItemRef,
+        /// Where to move it, as a position (past the end moves it last) or the item to take the
+        /// place of
+        to: ItemRef,
+    },
+    /// Swap the positions of two items
+    Swap { a: ItemRef, b: ItemRef },
+    /// Sort items, by default such that completed items appear last
+    Sort {
+        #[arg(value_enum, default_value_t = SortMode::Completed)]
+        mode: SortMode,
+        /// Sort in the opposite order (items without a priority or due date stay last)
+        #[arg(short, long)]
+        reverse: bool,
+    },
+    /// Print the todo list in another tool's format
+    Export { format: ExportFormat },
+    /// Add the items from another tool's file to the todo list
+    Import {
+        format: ImportFormat,
+        /// The file to import
+        #[arg(value_name = "FILE")]
+        path: PathBuf,
+        /// What the lists of Apple Reminders and Microsoft To Do become
+        #[arg(long, value_enum, default_value_t = ListsAs::Tags)]
+        lists_as: ListsAs,
+    },
+    /// Run the commands of a file one after the other, one per line (i.e., `add "buy milk"`),
+    /// reading the todo list once and writing it once at the end
+    Batch {
+        /// The file with the commands, or "-" to read them from stdin
+        #[arg(value_name = "FILE", default_value = "-")]
+        path: PathBuf,
+    },
+    /// Move completed items into the archive, or browse and restore archived items
+    Archive {
+        #[command(subcommand)]
+        action: Option<ArchiveAction>,
+    },
+    /// Browse or empty the trash, which holds removed items
+    Trash {
+        #[command(subcommand)]
+        action: TrashAction,
+    },
+    /// Move removed items from the trash back into the todo list
+    Restore {
+        /// The items to restore, by their position in `todo trash list` or their ID
+        #[arg(required = true)]
+        items: Vec<ItemRef>,
+    },
+    /// Revert the last command that changed the todo list
+    Undo,
+    /// Browse or restore the backups of the todo list, which are made before each change
+    Backup {
+        #[command(subcommand)]
+        action: BackupAction,
+    },
+    /// Print the commands that changed the todo list, and what each of them changed
+    History {
+        /// How many of the most recent commands to print
+        #[arg(short = 'n', long, default_value_t = 20)]
+        count: usize,
+    },
+    /// Print the items checked on a day, or since a date, from the done log, i.e., `todo did
+    /// yesterday`. Items are listed even if they were removed or unchecked since
+    Did {
+        /// The day to print the checked items of, i.e., "yesterday" or "2025-03-01" (Default =
+        /// today)
+        #[arg(value_parser = parse_date, conflicts_with = "since")]
+        day: Option<NaiveDate>,
+        /// Print the items checked on this date and after instead
+        #[arg(short, long, value_name = "DATE", value_parser = parse_date)]
+        since: Option<NaiveDate>,
+    },
+    /// Add or edit the note of an item in $EDITOR
+    Note {
+        /// The item to add a note to
+        item: ItemRef,
+        /// The text of the note, instead of opening $EDITOR ("" removes the note)
+        text: Option<String>,
+    },
+    /// Attach a web page to an item, i.e., `todo link 3 https://example.com`
+    Link {
+        /// The item to attach the link to
+        item: ItemRef,
+        /// The URL, or "none" to remove it
+        url: String,
+    },
+    /// Open the link of an item in the default browser
+    Open {
+        /// The item whose link to open
+        item: ItemRef,
+    },
+    /// Add a timestamped comment to an item, i.e., `todo comment 3 "waiting on vendor"`
+    Comment {
+        /// The item to comment on
+        item: ItemRef,
+        /// The text of the comment
+        text: String,
+    },
+    /// Print the full details of an item, including its note and comments
+    Show {
+        /// The item to show
+        item: ItemRef,
+    },
+    /// Start a timer on an item to track the time spent on it, stopping the running timer
+    Start {
+        /// The item to work on
+        item: ItemRef,
+    },
+    /// Stop the running timer
+    Stop,
+    /// Run a pomodoro timer on an item: work, then take a break, logging the finished work
+    Pomodoro {
+        /// The item to work on
+        item: ItemRef,
+        /// How many minutes to work
+        #[arg(long, default_value_t = 25, value_parser = clap::value_parser!(u64).range(1..))]
+        work: u64,
+        /// How many minutes the break lasts
+        #[arg(
+            long = "break",
+            default_value_t = 5,
+            value_parser = clap::value_parser!(u64).range(1..)
+        )]
+        rest: u64,
+        /// How many rounds of work and a break to run
+        #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+        rounds: u32,
+        /// Show a desktop notification when work or a break is over
+        #[arg(short, long)]
+        notify: bool,
+    },
+    /// Summarize the work on the todo list
+    Report {
+        #[command(subcommand)]
+        kind: ReportKind,
+    },
+    /// Show a desktop notification for items that are overdue or due soon (i.e., from cron)
+    Remind {
+        /// How many days ahead to warn about due items (overrides the "remind-days" setting)
+        #[arg(short, long)]
+        days: Option<u64>,
+        /// Only print the items instead of also showing a notification
+        #[arg(long)]
+        no_notify: bool,
+    },
+    /// Print how many items are pending and completed, and how many were completed recently
+    Stats {
+        /// Also chart how many items were open at the end of each day, from the history
+        #[arg(long)]
+        chart: bool,
+        /// Print the statistics, with a series of the last days, as a JSON object (overrides the
+        /// "format" setting)
+        #[arg(long, conflicts_with = "chart")]
+        json: bool,
+        /// How many days the chart or JSON series covers, including today
+        #[arg(long, default_value_t = 14)]
+        days: u64,
+    },
+    /// Print how many items are pending, done, and overdue on a single line, i.e., for a shell
+    /// prompt or tmux status line, or set the status of items (i.e., `todo status 3 waiting`)
+    Status {
+        /// The item (or range, i.e., "1-4", or text in its label) to set the status of
+        #[arg(requires = "status")]
+        item: Option<Selection>,
+        /// The new status: pending, in-progress, waiting, or done
+        status: Option<Status>,
+        /// Print a short, uncolored line that won't change between versions, i.e., "P:4 D:2 O:1"
+        #[arg(long, conflicts_with = "item")]
+        porcelain: bool,
+    },
+    /// Print how many items there are in total, pending, done, and overdue, one count per line
+    /// (i.e., "pending 4"), for scripts and status bars
+    Count {
+        /// Print a single line instead, i.e., "3/7 done, 1 overdue"
+        #[arg(short, long)]
+        summary: bool,
+        /// Print the counts as a JSON object (overrides the "format" setting)
+        #[arg(long, conflicts_with = "summary")]
+        json: bool,
+    },
+    /// Print the names of all lists
+    Lists,
+    /// Scope commands to a project, so new items are added to it and `todo list` only shows it,
+    /// or move items into it (i.e., `todo project website 3 5`)
+    Project {
+        /// The name of the project, or "none" to stop scoping commands to one (or to move items
+        /// out of their project)
+        name: Option<String>,
+        /// The items (or ranges, i.e., "1-4", or text in their labels) to move into the project
+        items: Vec<Selection>,
+    },
+    /// Print the projects, with how many of their items are completed
+    Projects,
+    /// Switch to a context, i.e., "@home", so new items are added to it and `todo list` only
+    /// shows it, or move items into it (i.e., `todo context @errands 3 5`)
+    Context {
+        /// The context, with or without the "@", or "none" to stop using one (or to move items
+        /// out of their context)
+        name: Option<String>,
+        /// The items (or ranges, i.e., "1-4", or text in their labels) to move into the context
+        items: Vec<Selection>,
+    },
+    /// Pull and push the todo lists with the git remote set with `todo set git-remote`, and upload
+    /// the items with a due date to the CalDAV calendar set with `todo set caldav-url`
+    Sync,
+    /// Encrypt the todo lists with a passphrase (read from TODO_PASSPHRASE, or asked for)
+    Encrypt,
+    /// Store the todo lists in plain text again
+    Decrypt,
+    /// Choose the list to use by default (it is created when items are first added to it)
+    Use {
+        /// The name of the list
+        name: String,
+    },
+    /// Change config setting to have value <VALUE> (run `todo set help` for the settings)
+    Set {
+        #[arg(value_parser = setting_names())]
+        setting: Option<String>,
+        value: Option<String>,
+    },
+    /// Define a shortcut for a command (i.e., `todo alias work list --tag work`), or list the
+    /// aliases
+    Alias {
+        /// The name of the alias
+        name: Option<String>,
+        /// The command it stands for, along with its arguments
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        command: Vec<String>,
+        /// Remove the alias instead
+        #[arg(short, long, requires = "name", conflicts_with = "command")]
+        remove: bool,
+    },
+    /// An alias from the settings, which is replaced by the command it stands for
+    #[command(external_subcommand)]
+    External(Vec<String>),
+    /// Keep the todo list in memory and serve it to other `todo` commands over a Unix socket,
+    /// until stopped
+    Daemon,
+    /// Start a todo list for the current directory (".todo"), which is used instead of the global
+    /// lists in this directory and its subdirectories
+    Init,
+    /// Print a shell completion script (i.e., `todo completions bash >
+    /// ~/.local/share/bash-completion/completions/todo`)
+    Completions { shell: Shell },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ArchiveAction {
+    /// Print the archived items
+    List,
+    /// Move archived items back into the todo list
+    Restore {
+        /// The items to restore, by their position in `todo archive list` or their ID
+        #[arg(required = true)]
+        items: Vec<ItemRef>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum BackupAction {
+    /// Print the backups, newest first
+    List,
+    /// Replace the todo list with a backup (the current todo list is backed up first)
+    Restore {
+        /// The backup to restore, by its number in `todo backup list` (1 is the newest)
+        #[arg(default_value_t = 1)]
+        n: usize,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ReportKind {
+    /// Print the time spent on each item and on each day
+    Time {
+        /// Only count the time since this date (i.e., "monday" or "2025-01-01")
+        #[arg(long, value_name = "DATE", value_parser = parse_date)]
+        since: Option<NaiveDate>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum TrashAction {
+    /// Print the removed items
+    List,
+    /// Permanently delete every removed item
+    Empty,
+}
+
+/// The formats the todo list can be exported to.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum ExportFormat {
+    /// todo.txt (https://github.com/todotxt/todo.txt)
+    Todotxt,
+    /// A JSON array, the same as `todo list --json`
+    Json,
+    /// iCalendar (https://www.rfc-editor.org/rfc/rfc5545) to-dos of the items with a due date
+    Ics,
+    /// A Markdown checklist ("- [ ] label"), with notes as indented lines
+    Md,
+    /// CSV with the columns label, status, due, tags, and priority, for spreadsheets
+    Csv,
+    /// Emacs Org-mode "* TODO" headings, with due dates as DEADLINE timestamps
+    Org,
+}
+
+/// The formats items can be imported from.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum ImportFormat {
+    /// todo.txt (https://github.com/todotxt/todo.txt)
+    Todotxt,
+    /// The task list items ("- [ ] label") of a Markdown file
+    Md,
+    /// CSV with a header row naming the columns (label, status, due, tags, priority)
+    Csv,
+    /// Apple Reminders, exported as CSV with the columns Title, Notes, Due Date, Priority, and List
+    Reminders,
+    /// Microsoft To Do, exported as the JSON of the Microsoft Graph API
+    Mstodo,
+}
+
+/// What the lists of an imported file become.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum ListsAs {
+    /// A tag on each item, named after its list
+    Tags,
+    /// Todo lists of their own, named after the lists
+    Lists,
+}
+
+/// How errors are printed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ErrorFormat {
+    /// "ERROR: " followed by the message
+    Text,
+    /// A line of JSON with the code, exit code, message, and context of the error
+    Json,
+}
+
+/// A value that can be removed by passing "none".
+#[derive(Clone, Copy, Debug)]
+pub struct OrNone<T>(pub Option<T>);
+
+/// The names accepted by `todo set`, so that they can be completed by the shell.
+fn setting_names() -> PossibleValuesParser {
+    let names = SETTING_CHOICES.iter().map(|(name, _, _)| *name);
+    PossibleValuesParser::new(names.chain(["help"]))
+}
+
+fn parse_tag_filter(s: &str) -> Result<String, String> {
+    match s.strip_prefix('+') {
+        Some(tag) if !tag.is_empty() => Ok(tag.to_string()),
+        _ => Err(format!("expected a tag such as \"+work\", found \"{s}\"")),
+    }
+}
+
+fn parse_date(s: &str) -> Result<NaiveDate, String> {
+    dates::parse_date(s, dates::today())
+}
+
+fn parse_estimate_or_none(s: &str) -> Result<OrNone<Estimate>, String> {
+    if s == "none" {
+        return Ok(OrNone(None));
+    }
+    s.parse().map(|estimate| OrNone(Some(estimate)))
+}
+
+fn parse_date_or_none(s: &str) -> Result<OrNone<NaiveDate>, String> {
+    if s == "none" {
+        return Ok(OrNone(None));
+    }
+    dates::parse_date(s, dates::today()).map(|date| OrNone(Some(date)))
+}
+
+fn parse_recurrence_or_none(s: &str) -> Result<OrNone<Recurrence>, String> {
+    if s == "none" {
+        return Ok(OrNone(None));
+    }
+    s.parse().map(|recurrence| OrNone(Some(recurrence)))
+}
+
+fn parse_priority_or_none(s: &str) -> Result<OrNone<Priority>, String> {
+    if s == "none" {
+        return Ok(OrNone(None));
+    }
+    s.parse().map(|priority| OrNone(Some(priority)))
+}
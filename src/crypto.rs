@@ -0,0 +1,146 @@
+//! Encryption of the data files with a passphrase.
+//!
+//! An encrypted file starts with a header line, followed by a line with the hex-encoded salt,
+//! nonce, and ChaCha20-Poly1305 ciphertext of the original contents. The key is derived from the
+//! passphrase with Argon2. The passphrase is read from the TODO_PASSPHRASE environment variable,
+//! or asked for once per run.
+
+use crate::error::TodoError;
+use argon2::Argon2;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use std::env;
+use std::sync::{Mutex, OnceLock};
+
+const HEADER: &str = "todo-encrypted v1";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+type Salt = [u8; SALT_LEN];
+
+static PASSPHRASE: OnceLock<String> = OnceLock::new();
+/// Keys that were already derived during this run, since deriving one is deliberately slow.
+static KEYS: Mutex<Vec<(Salt, [u8; 32])>> = Mutex::new(Vec::new());
+
+/// Whether the contents of a file are encrypted.
+pub fn is_encrypted(contents: &str) -> bool {
+    contents.starts_with(HEADER)
+}
+
+/// Encrypt the contents of a file.
+pub fn encrypt(contents: &str) -> Result<String, TodoError> {
+    // Reuse the salt of a key from this run, so the key doesn't have to be derived again
+    let salt = match KEYS.lock().unwrap().first() {
+        Some((salt, _)) => *salt,
+        None => random()?,
+    };
+    let nonce: [u8; NONCE_LEN] = random()?;
+
+    let cipher = ChaCha20Poly1305::new(&Key::from(key(&salt)?));
+    let ciphertext = cipher
+        .encrypt(&Nonce::from(nonce), contents.as_bytes())
+        .map_err(|err| TodoError::Encrypt(err.to_string()))?;
+
+    Ok(format!(
+        "{HEADER}\n{}{}{}\n",
+        to_hex(&salt),
+        to_hex(&nonce),
+        to_hex(&ciphertext)
+    ))
+}
+
+/// Decrypt the contents of an encrypted file, returning None if the passphrase is wrong or the
+/// contents are damaged.
+pub fn decrypt(contents: &str) -> Result<Option<String>, TodoError> {
+    let Some(bytes) = contents
+        .strip_prefix(HEADER)
+        .and_then(|hex| from_hex(hex.trim()))
+        .filter(|bytes| bytes.len() >= SALT_LEN + NONCE_LEN)
+    else {
+        return Ok(None);
+    };
+    let (salt, rest) = bytes.split_at(SALT_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+
+    let salt: &Salt = salt.try_into().unwrap();
+    let nonce = Nonce::from(<[u8; NONCE_LEN]>::try_from(nonce).unwrap());
+
+    let cipher = ChaCha20Poly1305::new(&Key::from(key(salt)?));
+    let Ok(plain) = cipher.decrypt(&nonce, ciphertext) else {
+        return Ok(None);
+    };
+    Ok(String::from_utf8(plain).ok())
+}
+
+/// Ask for a new passphrase, twice to catch typos, unless TODO_PASSPHRASE is set.
+pub fn new_passphrase() -> Result<(), TodoError> {
+    if PASSPHRASE.get().is_some() || env::var_os("TODO_PASSPHRASE").is_some() {
+        return Ok(());
+    }
+
+    let first = read_passphrase("New passphrase: ")?;
+    let second = read_passphrase("Repeat the passphrase: ")?;
+    if first != second {
+        return Err(TodoError::Usage("The passphrases don't match.".to_string()));
+    }
+    if first.is_empty() {
+        return Err(TodoError::Usage(
+            "The passphrase can't be empty.".to_string(),
+        ));
+    }
+
+    let _ = PASSPHRASE.set(first);
+    Ok(())
+}
+
+/// The passphrase, asking for it if this is the first time it is needed.
+fn passphrase() -> Result<&'static str, TodoError> {
+    if let Some(passphrase) = PASSPHRASE.get() {
+        return Ok(passphrase);
+    }
+
+    let passphrase = match env::var("TODO_PASSPHRASE") {
+        Ok(passphrase) => passphrase,
+        Err(_) => read_passphrase("Passphrase: ")?,
+    };
+    Ok(PASSPHRASE.get_or_init(|| passphrase))
+}
+
+fn read_passphrase(prompt: &str) -> Result<String, TodoError> {
+    rpassword::prompt_password(prompt)
+        .map_err(|err| TodoError::io("Could not read the passphrase", err))
+}
+
+/// Derive the key for a salt from the passphrase.
+fn key(salt: &Salt) -> Result<[u8; 32], TodoError> {
+    if let Some((_, key)) = KEYS.lock().unwrap().iter().find(|(s, _)| s == salt) {
+        return Ok(*key);
+    }
+
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase()?.as_bytes(), salt, &mut key)
+        .map_err(|err| TodoError::Encrypt(err.to_string()))?;
+    KEYS.lock().unwrap().push((*salt, key));
+    Ok(key)
+}
+
+fn random<const N: usize>() -> Result<[u8; N], TodoError> {
+    let mut bytes = [0u8; N];
+    getrandom::fill(&mut bytes).map_err(|err| TodoError::Encrypt(err.to_string()))?;
+    Ok(bytes)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
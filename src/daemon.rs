@@ -0,0 +1,196 @@
+//! A daemon that keeps a todo list in memory and serves it over a Unix socket, so commands don't
+//! have to read the data file each time.
+//!
+//! The protocol is one JSON object per line. A client sends a request, i.e., `{"op":"load"}` or
+//! `{"op":"save","items":[...],"next_id":7}`, and the daemon answers with
+//! `{"items":[...],"next_id":7}`, `"ok"`, or `{"error":"..."}`. The next ID is the ID the next new
+//! item gets, and may be left out of a save. After `{"op":"watch"}`, the connection stays open and the daemon sends
+//! the items again every time they are saved.
+
+use crate::error::TodoError;
+use crate::list::TodoList;
+use crate::store::{TodoStore, check_writable};
+use crate::todo::Todo;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+/// How long either side waits for the other before giving up.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub(crate) enum Request {
+    Load,
+    Save {
+        items: Vec<Todo>,
+        #[serde(default)]
+        next_id: u64,
+    },
+    Watch,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Response {
+    Ok,
+    Error(String),
+    #[serde(untagged)]
+    Items {
+        items: Vec<Todo>,
+        #[serde(default)]
+        next_id: u64,
+    },
+}
+
+/// Send a request to the daemon serving the given socket, returning None if no daemon is running.
+pub(crate) fn request(
+    socket_path: &Path,
+    request: &Request,
+) -> Result<Option<Response>, TodoError> {
+    let Ok(stream) = UnixStream::connect(socket_path) else {
+        return Ok(None);
+    };
+    let io_err = |err| TodoError::io("Could not talk to the daemon", err);
+    stream.set_read_timeout(Some(TIMEOUT)).map_err(io_err)?;
+
+    send(&stream, request).map_err(io_err)?;
+    let mut line = String::new();
+    BufReader::new(&stream)
+        .read_line(&mut line)
+        .map_err(io_err)?;
+
+    let response = serde_json::from_str(&line).map_err(|source| TodoError::ParseLine {
+        line: line.trim_end().to_string(),
+        source,
+    })?;
+    match response {
+        Response::Error(err) => Err(TodoError::Daemon(err)),
+        response => Ok(Some(response)),
+    }
+}
+
+/// Serve the todo list of a store until the process is stopped.
+pub fn serve(store: TodoStore) -> Result<(), TodoError> {
+    check_writable()?;
+    let socket_path = store.socket_path();
+    if UnixStream::connect(&socket_path).is_ok() {
+        return Err(TodoError::Usage(
+            "A daemon is already serving this list.".to_string(),
+        ));
+    }
+
+    // A socket file left behind by a daemon that was stopped
+    let _ = fs::remove_file(&socket_path);
+    let io_err = |err| TodoError::io("Could not open the daemon's socket", err);
+    let listener = UnixListener::bind(&socket_path).map_err(io_err)?;
+    fs::set_permissions(&socket_path, fs::Permissions::from_mode(0o600)).map_err(io_err)?;
+
+    println!(
+        "Serving {} on {}. Press Ctrl-C to stop.",
+        store.path().display(),
+        socket_path.display()
+    );
+
+    let mut daemon = Daemon {
+        store,
+        cache: None,
+        watchers: Vec::new(),
+    };
+    for stream in listener.incoming().flatten() {
+        if let Err(err) = daemon.handle(stream) {
+            eprintln!("ERROR: {err}");
+        }
+    }
+    Ok(())
+}
+
+type FileVersion = (u64, SystemTime);
+
+struct Daemon {
+    store: TodoStore,
+    /// The todo list, and the version of the data file it was read from.
+    cache: Option<(Option<FileVersion>, TodoList)>,
+    /// Connections that are sent the items whenever they change.
+    watchers: Vec<UnixStream>,
+}
+
+impl Daemon {
+    /// Answer every request of a connection until the client closes it.
+    fn handle(&mut self, stream: UnixStream) -> Result<(), TodoError> {
+        let io_err = |err| TodoError::io("Could not talk to a client", err);
+        stream.set_read_timeout(Some(TIMEOUT)).map_err(io_err)?;
+
+        let reader = BufReader::new(stream.try_clone().map_err(io_err)?);
+        for line in reader.lines() {
+            let line = line.map_err(io_err)?;
+            let result = match serde_json::from_str(&line) {
+                Ok(Request::Load) => self.load().map(items_response),
+                Ok(Request::Save { items, next_id }) => {
+                    self.save(items, next_id).map(|_| Response::Ok)
+                }
+                Ok(Request::Watch) => {
+                    let response = items_response(self.load()?);
+                    send(&stream, &response).map_err(io_err)?;
+                    self.watchers.push(stream);
+                    return Ok(());
+                }
+                Err(err) => Ok(Response::Error(format!(
+                    "invalid request \"{line}\": {err}"
+                ))),
+            };
+
+            let response = result.unwrap_or_else(|err| Response::Error(err.to_string()));
+            send(&stream, &response).map_err(io_err)?;
+        }
+        Ok(())
+    }
+
+    /// The todo list, which is read again if the data file was changed by something else.
+    fn load(&mut self) -> Result<&TodoList, TodoError> {
+        let version = self.version();
+        if self
+            .cache
+            .as_ref()
+            .is_none_or(|(cached, _)| *cached != version)
+        {
+            self.cache = Some((version, self.store.load_file()?));
+        }
+        Ok(&self.cache.as_ref().unwrap().1)
+    }
+
+    fn save(&mut self, items: Vec<Todo>, next_id: u64) -> Result<(), TodoError> {
+        let list = TodoList::from_items(items).with_next_id(next_id);
+        self.store.save_file(&list)?;
+
+        let response = items_response(&list);
+        self.watchers
+            .retain(|watcher| send(watcher, &response).is_ok());
+        self.cache = Some((self.version(), list));
+        Ok(())
+    }
+
+    /// Identify the current contents of the data file. Every write replaces the file with a new
+    /// one, so the inode changes even when the modification time (which is coarse) doesn't.
+    fn version(&self) -> Option<FileVersion> {
+        let meta = fs::metadata(self.store.path()).ok()?;
+        Some((meta.ino(), meta.modified().ok()?))
+    }
+}
+
+/// The response with the items of a todo list, and the ID its next new item gets.
+fn items_response(list: &TodoList) -> Response {
+    Response::Items {
+        items: list.items().to_vec(),
+        next_id: list.next_id(),
+    }
+}
+
+fn send<T: Serialize>(mut stream: &UnixStream, message: &T) -> std::io::Result<()> {
+    let line = serde_json::to_string(message)?;
+    writeln!(stream, "{line}")
+}
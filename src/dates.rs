@@ -0,0 +1,410 @@
+//! Parsing of dates given on the command line, and printing dates.
+//!
+//! Dates are printed in the order of the "date-format" setting, and weeks start on the day of the
+//! "week-start" setting. Timestamps are stored in UTC, and shown in the timezone of the "timezone"
+//! setting or else the system's timezone, which also decides what day today is.
+//!
+//! Besides ISO dates (YYYY-MM-DD), and dates written with slashes in the order of the
+//! "date-format" setting (i.e., "31/12/2025" or "31/12" for DD/MM), these phrases are understood
+//! (case-insensitively):
+//! - "today", "tomorrow", "yesterday"
+//! - weekdays, i.e., "friday" (today or the coming friday) and "next friday" (after today)
+//! - "in 3 days", "in a week", "in 2 weeks", "in 1 month", "in 2 years" (the "in" is optional)
+//! - "3d", "2w", "1m", "1y" (days, weeks, months, or years from today)
+//! - "next week", "next month", "next year"
+//! - month and day, i.e., "jan 5", "5 january", or "jan 5 2027". Without a year, the next time
+//!   that day comes around is used.
+
+use crate::settings::Settings;
+use chrono::{DateTime, Datelike, Days, FixedOffset, Local, Months, NaiveDate, Utc, Weekday};
+use chrono_tz::Tz;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+/// The order dates are written in.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum DateFormat {
+    /// YYYY-MM-DD
+    #[default]
+    Iso,
+    /// DD/MM/YYYY
+    Dmy,
+    /// MM/DD/YYYY
+    Mdy,
+}
+
+/// The settings for dates, read from the settings when first needed. Dates are parsed along with
+/// the command line, before the settings are otherwise loaded, so the settings file is only read
+/// here and not created (which read-only mode may not allow).
+static LOCALE: OnceLock<Locale> = OnceLock::new();
+
+struct Locale {
+    format: DateFormat,
+    week_start: Weekday,
+    /// The timezone of the "timezone" setting, or `None` for the system's timezone.
+    timezone: Option<Tz>,
+}
+
+fn locale() -> &'static Locale {
+    LOCALE.get_or_init(|| match Settings::read() {
+        Ok(settings) => Locale {
+            format: settings.display.date_format,
+            week_start: settings.display.week_start,
+            timezone: settings
+                .display
+                .timezone
+                .and_then(|timezone| timezone.parse().ok()),
+        },
+        Err(_) => Locale {
+            format: DateFormat::Iso,
+            week_start: Weekday::Mon,
+            timezone: None,
+        },
+    })
+}
+
+impl DateFormat {
+    /// The strftime format of a date in this order.
+    pub fn pattern(self) -> &'static str {
+        match self {
+            DateFormat::Iso => "%Y-%m-%d",
+            DateFormat::Dmy => "%d/%m/%Y",
+            DateFormat::Mdy => "%m/%d/%Y",
+        }
+    }
+}
+
+/// Format a date as set with the "date-format" setting.
+pub fn format_date(date: NaiveDate) -> String {
+    date.format(locale().format.pattern()).to_string()
+}
+
+/// The strftime format of dates, as set with the "date-format" setting.
+pub fn date_pattern() -> &'static str {
+    locale().format.pattern()
+}
+
+/// The first day of the week that a date is in, as set with the "week-start" setting.
+pub fn start_of_week(date: NaiveDate) -> NaiveDate {
+    date.week(locale().week_start).first_day()
+}
+
+/// A timestamp in the local timezone, which is the "timezone" setting or the system's timezone.
+pub fn local(timestamp: DateTime<Utc>) -> DateTime<FixedOffset> {
+    match locale().timezone {
+        Some(timezone) => timestamp.with_timezone(&timezone).fixed_offset(),
+        None => timestamp.with_timezone(&Local).fixed_offset(),
+    }
+}
+
+/// Today's date in the local timezone.
+pub fn today() -> NaiveDate {
+    local(Utc::now()).date_naive()
+}
+
+/// Midnight at the start of a day in the local timezone, or `None` if the clocks skip it.
+pub fn start_of_day(date: NaiveDate) -> Option<DateTime<Utc>> {
+    let midnight = date.and_hms_opt(0, 0, 0)?;
+    let start = match locale().timezone {
+        Some(timezone) => midnight.and_local_timezone(timezone).earliest()?.to_utc(),
+        None => midnight.and_local_timezone(Local).earliest()?.to_utc(),
+    };
+    Some(start)
+}
+
+/// Parse a date relative to today.
+pub fn parse_date(input: &str, today: NaiveDate) -> Result<NaiveDate, String> {
+    let input = input.trim().to_lowercase();
+    let words: Vec<&str> = input.split_whitespace().collect();
+
+    let date = match words.as_slice() {
+        [] => None,
+        [date] if NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok() => {
+            NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+        }
+        [date] if date.contains('/') => parse_numeric(date, today, locale().format),
+        [short] if parse_short_duration(short).is_some() => {
+            let (amount, unit) = parse_short_duration(short).unwrap();
+            add_duration(today, amount, unit)
+        }
+        ["today"] => Some(today),
+        ["tomorrow"] => today.checked_add_days(Days::new(1)),
+        ["yesterday"] => today.checked_sub_days(Days::new(1)),
+        ["next", "week"] => today.checked_add_days(Days::new(7)),
+        ["next", "month"] => today.checked_add_months(Months::new(1)),
+        ["next", "year"] => today.checked_add_months(Months::new(12)),
+        ["next", day] if parse_weekday(day).is_some() => {
+            Some(next_weekday(today, parse_weekday(day).unwrap(), false))
+        }
+        [day] if parse_weekday(day).is_some() => {
+            Some(next_weekday(today, parse_weekday(day).unwrap(), true))
+        }
+        // "5 jan" also looks like an amount and a unit
+        ["in", amount, unit] | [amount, unit] if parse_amount(amount).is_some() => {
+            add_duration(today, parse_amount(amount).unwrap(), unit)
+                .or_else(|| parse_month_day(&words, today))
+        }
+        _ => parse_month_day(&words, today),
+    };
+
+    date.ok_or_else(|| {
+        format!(
+            "cannot understand the date \"{}\" (try {}, \"tomorrow\", \"next friday\", \"in 2 weeks\", or \"jan 5\")",
+            input,
+            match locale().format {
+                DateFormat::Iso => "YYYY-MM-DD",
+                DateFormat::Dmy => "DD/MM/YYYY",
+                DateFormat::Mdy => "MM/DD/YYYY",
+            }
+        )
+    })
+}
+
+/// Describe a date relative to today, i.e., "tomorrow", "in 3 days", or "2 weeks ago".
+pub fn describe(date: NaiveDate, today: NaiveDate) -> String {
+    let days = (date - today).num_days();
+    let span = match days.abs() {
+        0 => return "today".to_string(),
+        1 if days > 0 => return "tomorrow".to_string(),
+        1 => return "yesterday".to_string(),
+        n @ 2..=13 => format!("{n} days"),
+        n @ 14..=59 => format!("{} weeks", n / 7),
+        n @ 60..=729 => format!("{} months", n / 30),
+        n => format!("{} years", n / 365),
+    };
+    if days > 0 {
+        format!("in {span}")
+    } else {
+        format!("{span} ago")
+    }
+}
+
+/// The first date on the given weekday after today (or including today).
+fn next_weekday(today: NaiveDate, weekday: Weekday, include_today: bool) -> NaiveDate {
+    let mut days =
+        (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+    if days == 0 && !include_today {
+        days = 7;
+    }
+    today + Days::new(days as u64)
+}
+
+fn add_duration(today: NaiveDate, amount: u32, unit: &str) -> Option<NaiveDate> {
+    match unit.trim_end_matches('s') {
+        "day" => today.checked_add_days(Days::new(amount as u64)),
+        "week" => today.checked_add_days(Days::new(amount as u64 * 7)),
+        "month" => today.checked_add_months(Months::new(amount)),
+        "year" => today.checked_add_months(Months::new(amount.checked_mul(12)?)),
+        _ => None,
+    }
+}
+
+/// Parse dates such as "jan 5", "5 jan", "january 5 2027", or "5 jan 2027".
+fn parse_month_day(words: &[&str], today: NaiveDate) -> Option<NaiveDate> {
+    let (month, day, year) = match words {
+        [a, b] => {
+            let (month, day) = month_and_day(a, b)?;
+            (month, day, None)
+        }
+        [a, b, year] => {
+            let (month, day) = month_and_day(a, b)?;
+            (month, day, Some(year.parse::<i32>().ok()?))
+        }
+        _ => return None,
+    };
+
+    on_month_day(month, day, year, today)
+}
+
+/// Parse dates written with slashes in the order of a date format, i.e., "31/12/2025" or "31/12"
+/// for DD/MM. ISO dates are only written with dashes.
+fn parse_numeric(text: &str, today: NaiveDate, format: DateFormat) -> Option<NaiveDate> {
+    let numbers: Vec<u32> = text
+        .split('/')
+        .map(|part| part.parse().ok())
+        .collect::<Option<_>>()?;
+    let (month, day, year) = match (format, numbers.as_slice()) {
+        (DateFormat::Dmy, &[day, month]) | (DateFormat::Mdy, &[month, day]) => (month, day, None),
+        (DateFormat::Dmy, &[day, month, year]) | (DateFormat::Mdy, &[month, day, year]) => {
+            (month, day, Some(i32::try_from(year).ok()?))
+        }
+        _ => return None,
+    };
+    on_month_day(month, day, year, today)
+}
+
+/// The date with the given month and day, in the given year or, without one, the next time that
+/// day comes around.
+fn on_month_day(month: u32, day: u32, year: Option<i32>, today: NaiveDate) -> Option<NaiveDate> {
+    match year {
+        Some(year) => NaiveDate::from_ymd_opt(year, month, day),
+        // February 29th can take up to 8 years to come around
+        None => (today.year()..=today.year() + 8)
+            .filter_map(|year| NaiveDate::from_ymd_opt(year, month, day))
+            .find(|&date| date >= today),
+    }
+}
+
+/// Parse a month and a day given in either order, i.e., "jan" and "5th".
+fn month_and_day(a: &str, b: &str) -> Option<(u32, u32)> {
+    if let Some(month) = parse_month(a) {
+        return Some((month, parse_day(b)?));
+    }
+    Some((parse_month(b)?, parse_day(a)?))
+}
+
+fn parse_day(s: &str) -> Option<u32> {
+    let s = s.trim_end_matches(',');
+    let s = s
+        .strip_suffix("st")
+        .or_else(|| s.strip_suffix("nd"))
+        .or_else(|| s.strip_suffix("rd"))
+        .or_else(|| s.strip_suffix("th"))
+        .unwrap_or(s);
+    s.parse().ok().filter(|day| (1..=31).contains(day))
+}
+
+/// Parse a duration such as "3d" into its amount and unit.
+pub(crate) fn parse_short_duration(s: &str) -> Option<(u32, &'static str)> {
+    let unit = match s.chars().last()? {
+        'd' => "day",
+        'w' => "week",
+        'm' => "month",
+        'y' => "year",
+        _ => return None,
+    };
+    let amount = s[..s.len() - 1].parse().ok()?;
+    Some((amount, unit))
+}
+
+pub(crate) fn parse_amount(s: &str) -> Option<u32> {
+    match s {
+        "a" | "an" | "one" => Some(1),
+        _ => s.parse().ok(),
+    }
+}
+
+pub(crate) fn parse_weekday(s: &str) -> Option<Weekday> {
+    match s {
+        "mon" | "monday" => Some(Weekday::Mon),
+        "tue" | "tues" | "tuesday" => Some(Weekday::Tue),
+        "wed" | "wednesday" => Some(Weekday::Wed),
+        "thu" | "thur" | "thurs" | "thursday" => Some(Weekday::Thu),
+        "fri" | "friday" => Some(Weekday::Fri),
+        "sat" | "saturday" => Some(Weekday::Sat),
+        "sun" | "sunday" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+fn parse_month(s: &str) -> Option<u32> {
+    let month = match s.trim_end_matches('.') {
+        "jan" | "january" => 1,
+        "feb" | "february" => 2,
+        "mar" | "march" => 3,
+        "apr" | "april" => 4,
+        "may" => 5,
+        "jun" | "june" => 6,
+        "jul" | "july" => 7,
+        "aug" | "august" => 8,
+        "sep" | "sept" | "september" => 9,
+        "oct" | "october" => 10,
+        "nov" | "november" => 11,
+        "dec" | "december" => 12,
+        _ => return None,
+    };
+    Some(month)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    /// A Friday.
+    fn friday() -> NaiveDate {
+        date(2026, 10, 16)
+    }
+
+    fn parse(input: &str, today: NaiveDate) -> NaiveDate {
+        parse_date(input, today).unwrap()
+    }
+
+    #[test]
+    fn month_and_day_is_the_next_time_it_comes_around() {
+        assert_eq!(parse("jan 5", date(2026, 1, 4)), date(2026, 1, 5));
+        assert_eq!(parse("jan 5", date(2026, 1, 5)), date(2026, 1, 5));
+        assert_eq!(parse("jan 5", date(2026, 1, 6)), date(2027, 1, 5));
+        assert_eq!(parse("5th january", date(2026, 1, 6)), date(2027, 1, 5));
+        assert_eq!(parse("jan 5 2030", date(2026, 1, 6)), date(2030, 1, 5));
+        // February 29th is only in leap years
+        assert_eq!(parse("feb 29", date(2026, 3, 1)), date(2028, 2, 29));
+        assert!(parse_date("feb 30", date(2026, 3, 1)).is_err());
+    }
+
+    #[test]
+    fn weekdays() {
+        assert_eq!(parse("friday", friday()), friday());
+        assert_eq!(parse("next friday", friday()), date(2026, 10, 23));
+        assert_eq!(parse("mon", friday()), date(2026, 10, 19));
+        assert_eq!(parse("next monday", friday()), date(2026, 10, 19));
+        assert_eq!(parse("thursday", friday()), date(2026, 10, 22));
+    }
+
+    #[test]
+    fn relative_dates() {
+        assert_eq!(parse("today", friday()), friday());
+        assert_eq!(parse("Tomorrow", friday()), date(2026, 10, 17));
+        assert_eq!(parse("yesterday", friday()), date(2026, 10, 15));
+        assert_eq!(parse("in 3 days", friday()), date(2026, 10, 19));
+        assert_eq!(parse("in a week", friday()), date(2026, 10, 23));
+        assert_eq!(parse("2 weeks", friday()), date(2026, 10, 30));
+        assert_eq!(parse("3d", friday()), date(2026, 10, 19));
+        assert_eq!(parse("next year", friday()), date(2027, 10, 16));
+        // Months that are too short end on their last day
+        assert_eq!(parse("1m", date(2026, 1, 31)), date(2026, 2, 28));
+        assert_eq!(parse("in 1 year", date(2028, 2, 29)), date(2029, 2, 28));
+        assert_eq!(parse("2026-12-31", friday()), date(2026, 12, 31));
+    }
+
+    #[test]
+    fn slash_dates_follow_the_date_format() {
+        let today = friday();
+        assert_eq!(
+            parse_numeric("31/12/2025", today, DateFormat::Dmy),
+            Some(date(2025, 12, 31))
+        );
+        assert_eq!(parse_numeric("31/12/2025", today, DateFormat::Mdy), None);
+        assert_eq!(
+            parse_numeric("12/31/2025", today, DateFormat::Mdy),
+            Some(date(2025, 12, 31))
+        );
+        assert_eq!(
+            parse_numeric("1/2", today, DateFormat::Dmy),
+            Some(date(2027, 2, 1))
+        );
+        assert_eq!(
+            parse_numeric("1/2", today, DateFormat::Mdy),
+            Some(date(2027, 1, 2))
+        );
+        assert_eq!(parse_numeric("16/10", today, DateFormat::Dmy), Some(today));
+        assert_eq!(parse_numeric("2025/12/31", today, DateFormat::Iso), None);
+        assert_eq!(parse_numeric("31/x", today, DateFormat::Dmy), None);
+    }
+
+    #[test]
+    fn describing_dates() {
+        let today = friday();
+        assert_eq!(describe(today, today), "today");
+        assert_eq!(describe(date(2026, 10, 17), today), "tomorrow");
+        assert_eq!(describe(date(2026, 10, 15), today), "yesterday");
+        assert_eq!(describe(date(2026, 10, 19), today), "in 3 days");
+        assert_eq!(describe(date(2026, 10, 2), today), "2 weeks ago");
+        assert_eq!(describe(date(2027, 1, 16), today), "in 3 months");
+    }
+}
@@ -0,0 +1,1535 @@
+use crate::archive::ArchivedTodo;
+use crate::dates;
+use crate::error::TodoError;
+use crate::estimate::{Effort, Estimate, Plan};
+use crate::formats;
+use crate::history::HistoryEntry;
+use crate::journal::DoneEntry;
+use crate::list::{Filter, GroupBy, SortMode, TodoList};
+use crate::migrate;
+use crate::settings::{LongLabels, Numbering};
+use crate::stats::{Counts, DayStats, Stats};
+use crate::store::read_data;
+use crate::template::Template;
+use crate::todo::{Priority, Status, Todo};
+use crate::trash::TrashedTodo;
+use chrono::{DateTime, Days, FixedOffset, NaiveDate, TimeDelta, Utc};
+use colored::{Color, Colorize};
+use regex::Regex;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+
+/// How many characters wide the progress bars of `todo projects` are.
+const PROGRESS_BAR_WIDTH: usize = 20;
+
+/// The narrowest the labels in a table are cut off to, however narrow the terminal is.
+const MIN_LABEL_WIDTH: usize = 10;
+
+/// The columns of `todo board`, and the status of the items in each.
+const BOARD_COLUMNS: [(&str, Status); 4] = [
+    ("Backlog", Status::Pending),
+    ("In Progress", Status::InProgress),
+    ("Waiting", Status::Waiting),
+    ("Done", Status::Done),
+];
+
+/// How many spaces are between the columns of `todo board`.
+const BOARD_GAP: usize = 2;
+
+/// How the todo list is printed.
+#[derive(Default)]
+pub struct ListOptions {
+    /// Only list the items that match.
+    pub filter: Filter,
+    /// Print the items as a JSON array.
+    pub json: bool,
+    /// Show when items were created and completed.
+    pub dates: bool,
+    /// Show due dates as dates, instead of relative to today.
+    pub exact_dates: bool,
+    /// What items are numbered with.
+    pub numbering: Numbering,
+    /// How labels too long for the terminal are printed.
+    pub long_labels: LongLabels,
+    /// How many columns wide the terminal is, if stdout is one.
+    pub width: Option<usize>,
+    /// The order to list the items in, and whether to reverse it, instead of their positions.
+    pub sort: Option<(SortMode, bool)>,
+    /// List the items in sections instead of all together.
+    pub group_by: Option<GroupBy>,
+    /// Print the items as a table with a column for each field.
+    pub table: bool,
+    /// Print each item with this format string instead.
+    pub template: Option<Template>,
+    /// The colors to print the items in.
+    pub theme: Theme,
+    /// The glyphs to mark completed and incomplete items with.
+    pub checkbox: Checkbox,
+    /// List at most this many items.
+    pub limit: Option<usize>,
+    /// Print how many of the listed items are done after them.
+    pub progress: bool,
+    /// Only list the focused items, if any item is focused on (i.e., for a plain `todo list`).
+    pub focus: bool,
+}
+
+/// The glyphs that mark whether an item is completed, i.e., "☑" and "☐".
+#[derive(Clone)]
+pub struct Checkbox {
+    pub done: String,
+    pub pending: String,
+}
+
+impl Default for Checkbox {
+    fn default() -> Checkbox {
+        Checkbox {
+            done: "☑".to_string(),
+            pending: "☐".to_string(),
+        }
+    }
+}
+
+impl Checkbox {
+    /// Parse a style of checkboxes: "unicode" (☑/☐), "ascii" ([x]/[ ]), "emoji" (✅/⬜), or a
+    /// custom pair of glyphs for completed and incomplete items separated by a slash, i.e., "✔/✘".
+    pub fn parse(style: &str) -> Option<Checkbox> {
+        let (done, pending) = match style {
+            "unicode" => ("☑", "☐"),
+            "ascii" => ("[x]", "[ ]"),
+            "emoji" => ("✅", "⬜"),
+            _ => style
+                .split_once('/')
+                .filter(|(done, pending)| !done.is_empty() && !pending.is_empty())?,
+        };
+        Some(Checkbox {
+            done: done.to_string(),
+            pending: pending.to_string(),
+        })
+    }
+
+    /// The glyph for an item that is or isn't completed.
+    pub fn glyph(&self, complete: bool) -> &str {
+        if complete { &self.done } else { &self.pending }
+    }
+}
+
+/// The colors items are printed in, depending on their state.
+#[derive(Clone, Copy)]
+pub struct Theme {
+    pub completed: Color,
+    pub pending: Color,
+    pub in_progress: Color,
+    pub waiting: Color,
+    pub overdue: Color,
+    /// The color of the marker of pinned items.
+    pub pinned: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme {
+            completed: Color::Green,
+            pending: Color::White,
+            in_progress: Color::Cyan,
+            waiting: Color::Magenta,
+            overdue: Color::Red,
+            pinned: Color::Yellow,
+        }
+    }
+}
+
+impl Theme {
+    /// The color of an incomplete item: overdue, or else the color of its status.
+    fn incomplete(&self, item: &Todo, today: NaiveDate) -> Color {
+        if item.is_overdue(today) {
+            return self.overdue;
+        }
+        match item.state() {
+            Status::InProgress => self.in_progress,
+            Status::Waiting => self.waiting,
+            _ => self.pending,
+        }
+    }
+}
+
+/// Parse a color name (i.e., "red" or "bright blue") or a hex code for truecolor terminals
+/// (i.e., "#ff8800").
+pub fn parse_color(color: &str) -> Option<Color> {
+    if let Some(hex) = color.strip_prefix('#') {
+        if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        return Some(Color::TrueColor {
+            r: channel(0)?,
+            g: channel(2)?,
+            b: channel(4)?,
+        });
+    }
+    color.replace(['-', '_'], " ").parse().ok()
+}
+
+/// Print the todo list, optionally filtered, as text or as JSON.
+pub fn list_items(list: &TodoList, options: &ListOptions) -> Result<(), TodoError> {
+    let filter = &options.filter;
+    let today = dates::today();
+    let blocked = list.blocked();
+    let focused = options.focus && list.is_focused();
+    let mut matches = list.find(|item| {
+        filter.matches(item, today)
+            && !(filter.unblocked && blocked.contains(&item.id))
+            && (!focused || item.focused)
+    });
+    if let Some((mode, reverse)) = options.sort {
+        matches.sort_by(|(_, a), (_, b)| mode.compare(a, b, reverse));
+    }
+    // Pinned items come first in any order, which the stable sort keeps otherwise
+    matches.sort_by_key(|(_, item)| !item.pinned);
+    // The items left out by the limit still count towards the progress
+    let done = matches.iter().filter(|(_, item)| item.complete).count();
+    let total = matches.len();
+    let hidden = options
+        .limit
+        .map_or(0, |limit| matches.len().saturating_sub(limit));
+    matches.truncate(matches.len() - hidden);
+
+    if options.json {
+        println!("{}", formats::json::export(&matches)?);
+        return Ok(());
+    }
+
+    if list.is_empty() {
+        print_list(list, options);
+        return Ok(());
+    }
+
+    if matches.is_empty() && filter.is_empty() {
+        println!(
+            "Nothing to do until later! Snoozed items and items that can't be started yet are listed with `todo list --all`."
+        );
+        return Ok(());
+    }
+    if matches.is_empty() {
+        println!("No items match the filters.");
+        return Ok(());
+    }
+
+    match options.group_by {
+        Some(group_by) => {
+            for (n, (header, items)) in group_by.groups(&matches).into_iter().enumerate() {
+                if n > 0 {
+                    println!();
+                }
+                println!("{}", format!("{header} ({})", items.len()).bold());
+                print_rows(&items, &blocked, options);
+            }
+        }
+        None => print_rows(&matches, &blocked, options),
+    }
+    if hidden > 0 {
+        println!("{}", format!("... and {hidden} more").dimmed());
+    }
+    if focused {
+        println!(
+            "{}",
+            "Only focused items are listed. `todo unfocus` lists everything again.".dimmed()
+        );
+    }
+    if options.progress {
+        println!();
+        println!(
+            "{}  {done}/{total} done ({}%)",
+            progress_bar(done, total),
+            done * 100 / total
+        );
+    }
+    Ok(())
+}
+
+/// Print the incomplete items due within a number of days from today (1 for only today), under
+/// a header for every day, after the overdue items.
+pub fn print_agenda(
+    list: &TodoList,
+    today: NaiveDate,
+    days: u64,
+    options: &ListOptions,
+) -> Result<(), TodoError> {
+    let last = today + Days::new(days.saturating_sub(1));
+    let mut matches = list.find(|item| {
+        !item.complete
+            && item.due.is_some_and(|due| due <= last)
+            && options.filter.matches(item, today)
+    });
+    matches.sort_by_key(|(_, item)| item.due);
+
+    if options.json {
+        println!("{}", formats::json::export(&matches)?);
+        return Ok(());
+    }
+    if matches.is_empty() {
+        println!("Nothing is due!");
+        return Ok(());
+    }
+
+    let blocked = list.blocked();
+    let mut sections: Vec<(String, Vec<(usize, &Todo)>)> = Vec::new();
+    for (i, item) in matches {
+        let due = item.due.unwrap_or(today);
+        let header = if due < today {
+            "Overdue".to_string()
+        } else if due == today {
+            "Today".to_string()
+        } else if due == today + Days::new(1) {
+            "Tomorrow".to_string()
+        } else {
+            format!("{} {}", due.format("%A"), dates::format_date(due))
+        };
+        match sections.last_mut() {
+            Some((last, items)) if *last == header => items.push((i, item)),
+            _ => sections.push((header, vec![(i, item)])),
+        }
+    }
+
+    for (n, (header, items)) in sections.iter().enumerate() {
+        if n > 0 {
+            println!();
+        }
+        let header = format!("{header} ({})", items.len());
+        if n == 0 && items[0].1.is_overdue(today) {
+            println!("{}", header.color(options.theme.overdue).bold());
+        } else {
+            println!("{}", header.bold());
+        }
+        print_rows(items, &blocked, options);
+    }
+    Ok(())
+}
+
+/// How many days late an item can be before `todo overdue` colors it as badly overdue.
+const LATE_DAYS: i64 = 7;
+
+/// Print the overdue items, the most overdue first, with how long ago they were due. Items that
+/// are more than a week late are colored in the overdue color, and the others in yellow.
+pub fn print_overdue(
+    list: &TodoList,
+    today: NaiveDate,
+    options: &ListOptions,
+) -> Result<(), TodoError> {
+    let mut matches =
+        list.find(|item| item.is_overdue(today) && options.filter.matches(item, today));
+    matches.sort_by_key(|(_, item)| item.due);
+
+    if options.json {
+        println!("{}", formats::json::export(&matches)?);
+        return Ok(());
+    }
+    if matches.is_empty() {
+        println!("Nothing is overdue!");
+        return Ok(());
+    }
+
+    let rows: Vec<(String, String, i64)> = matches
+        .iter()
+        .filter_map(|&(i, item)| {
+            let due = item.due?;
+            let line = format!(
+                "{}: {} (due {}) id:{}",
+                i + 1,
+                item.label,
+                dates::format_date(due),
+                item.id
+            );
+            Some((dates::describe(due, today), line, (today - due).num_days()))
+        })
+        .collect();
+    let width = rows
+        .iter()
+        .map(|(late, _, _)| late.len())
+        .max()
+        .unwrap_or(0);
+    for (late, line, days) in rows {
+        let line = format!("{late:<width$}  {line}");
+        if days > LATE_DAYS {
+            println!("{}", line.color(options.theme.overdue).bold());
+        } else {
+            println!("{}", line.yellow());
+        }
+    }
+    Ok(())
+}
+
+/// Print the items in a column per status, side by side, splitting the width of the terminal (or
+/// 80 columns if stdout isn't one) between the columns. Labels too wide for their column are
+/// wrapped, or cut off with the "long-labels" setting set to "truncate".
+pub fn print_board(list: &TodoList, options: &ListOptions) -> Result<(), TodoError> {
+    let today = dates::today();
+    let matches = list.find(|item| options.filter.matches(item, today));
+    if options.json {
+        println!("{}", formats::json::export(&matches)?);
+        return Ok(());
+    }
+
+    let width = options.width.unwrap_or(80);
+    let column_width = (width.saturating_sub(BOARD_GAP * (BOARD_COLUMNS.len() - 1))
+        / BOARD_COLUMNS.len())
+    .max(MIN_LABEL_WIDTH);
+    let theme = &options.theme;
+    let columns: Vec<Vec<(String, Color)>> = BOARD_COLUMNS
+        .iter()
+        .map(|&(_, status)| {
+            let mut lines = Vec::new();
+            for &(i, item) in matches.iter().filter(|(_, item)| item.state() == status) {
+                let color = if item.complete {
+                    theme.completed
+                } else {
+                    theme.incomplete(item, today)
+                };
+                let number = format!("{}: ", i + 1);
+                let indent = number.chars().count();
+                let room = column_width.saturating_sub(indent).max(1);
+                let label = match options.long_labels {
+                    LongLabels::Truncate => vec![truncate_label(&item.label, room)],
+                    _ => wrap_text(&item.label, room),
+                };
+                for (n, line) in label.into_iter().enumerate() {
+                    let prefix = if n == 0 {
+                        number.clone()
+                    } else {
+                        " ".repeat(indent)
+                    };
+                    lines.push((format!("{prefix}{line}"), color));
+                }
+            }
+            lines
+        })
+        .collect();
+
+    let headers = BOARD_COLUMNS.iter().map(|&(name, status)| {
+        let count = matches
+            .iter()
+            .filter(|(_, item)| item.state() == status)
+            .count();
+        truncate_label(&format!("{name} ({count})"), column_width)
+    });
+    let header = board_row(headers.map(|text| (text, None)), column_width);
+    println!("{}", header.bold());
+    let rule = "─".repeat(column_width);
+    let rules = BOARD_COLUMNS.iter().map(|_| (rule.clone(), None));
+    println!("{}", board_row(rules, column_width).dimmed());
+
+    let rows = columns.iter().map(Vec::len).max().unwrap_or(0);
+    for row in 0..rows {
+        let cells = columns.iter().map(|lines| match lines.get(row) {
+            Some((line, color)) => (line.clone(), Some(*color)),
+            None => (String::new(), None),
+        });
+        println!("{}", board_row(cells, column_width));
+    }
+    Ok(())
+}
+
+/// A row of `todo board`: the cells padded to the width of the columns, and colored if given a
+/// color. The last cell isn't padded, so the line doesn't end in spaces.
+fn board_row(cells: impl Iterator<Item = (String, Option<Color>)>, width: usize) -> String {
+    let cells: Vec<(String, Option<Color>)> = cells.collect();
+    let last = cells.iter().rposition(|(text, _)| !text.is_empty());
+    let mut row = String::new();
+    for (n, (text, color)) in cells.into_iter().enumerate() {
+        if last.is_none_or(|last| n > last) {
+            break;
+        }
+        if n > 0 {
+            row.push_str(&" ".repeat(BOARD_GAP));
+        }
+        let padding = if Some(n) == last {
+            String::new()
+        } else {
+            " ".repeat(width.saturating_sub(text.chars().count()))
+        };
+        match color {
+            Some(color) => row.push_str(&text.color(color).to_string()),
+            None => row.push_str(&text),
+        }
+        row.push_str(&padding);
+    }
+    row
+}
+
+/// Print the item chosen by `todo next`, in the same format as `todo list`.
+pub fn print_next(next: Option<(usize, &Todo)>, options: &ListOptions) -> Result<(), TodoError> {
+    let Some(item) = next else {
+        println!("Nothing to do!");
+        return Ok(());
+    };
+    if options.json {
+        println!("{}", formats::json::export(&[item])?);
+    } else {
+        print_rows(&[item], &HashSet::new(), options);
+    }
+    Ok(())
+}
+
+/// Print the items one per line, or as a table. Blocked items (by ID) are dimmed.
+fn print_rows(items: &[(usize, &Todo)], blocked: &HashSet<u64>, options: &ListOptions) {
+    if let Some(template) = &options.template {
+        let today = dates::today();
+        for &(i, item) in items {
+            println!("{}", template.render(i, item, today));
+        }
+    } else if options.table {
+        print_table(items, blocked, options);
+    } else {
+        print_items(items, blocked, options);
+    }
+}
+
+/// Print the items as a table with aligned columns. In a terminal, the labels are cut off to fit
+/// its width.
+fn print_table(items: &[(usize, &Todo)], blocked: &HashSet<u64>, options: &ListOptions) {
+    let (dates, theme) = (options.dates, &options.theme);
+    let today = dates::today();
+    let mut header = vec!["#", "ID", "STATUS", "PRIORITY", "DUE", "TAGS"];
+    if dates {
+        header.extend(["CREATED", "COMPLETED"]);
+    }
+
+    let rows: Vec<Vec<String>> = items
+        .iter()
+        .map(|&(i, item)| {
+            let tags: Vec<String> = item.tags.iter().map(|tag| format!("+{tag}")).collect();
+            let mut row = vec![
+                (i + 1).to_string(),
+                item.id.to_string(),
+                item.status(today).to_string(),
+                item.priority.map_or(String::new(), |p| p.to_string()),
+                item.due.map_or(String::new(), |date| {
+                    format_due(date, today, options.exact_dates)
+                }),
+                tags.join(" "),
+            ];
+            if dates {
+                row.push(format_timestamp(item.created_at));
+                row.push(item.completed_at.map_or(String::new(), format_timestamp));
+            }
+            row
+        })
+        .collect();
+
+    let widths: Vec<usize> = (0..header.len())
+        .map(|column| {
+            rows.iter()
+                .map(|row| row[column].chars().count())
+                .chain([header[column].len()])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    // The label is the last column, so it gets whatever width is left
+    let used: usize = widths.iter().map(|width| width + 2).sum();
+    let label_width = options
+        .width
+        .map(|width| width.saturating_sub(used).max(MIN_LABEL_WIDTH));
+
+    let header_line: Vec<String> = header
+        .iter()
+        .zip(&widths)
+        .map(|(title, &width)| format!("{title:<width$}"))
+        .collect();
+    println!("{}  LABEL", header_line.join("  ").bold());
+
+    for (row, &(_, item)) in rows.iter().zip(items) {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, &width)| format!("{cell:<width$}"))
+            .collect();
+        let pin = if item.pinned { "★ " } else { "" };
+        let mut label = format!("{pin}{}", item.label);
+        if let Some(width) = label_width {
+            label = truncate_label(&label, width);
+        }
+
+        let line = format!("{}  {label}", cells.join("  "));
+        if blocked.contains(&item.id) {
+            println!("{}", line.dimmed());
+        } else if item.complete {
+            println!("{}", line.color(theme.completed));
+        } else {
+            println!("{}", line.color(theme.incomplete(item, today)));
+        }
+    }
+}
+
+/// Print the items whose label matches a search query, with the matching text highlighted.
+/// Without a regex, the query matches case-insensitively anywhere in the label.
+pub fn search_items(
+    list: &TodoList,
+    query: &str,
+    regex: bool,
+    checkbox: &Checkbox,
+) -> Result<(), TodoError> {
+    let pattern = if regex {
+        query.to_string()
+    } else {
+        format!("(?i){}", regex::escape(query))
+    };
+    let re = Regex::new(&pattern).map_err(TodoError::InvalidRegex)?;
+
+    let matches = list.find(|item| re.is_match(&item.label));
+    if matches.is_empty() {
+        println!("No items match \"{query}\".");
+        return Ok(());
+    }
+
+    for (i, item) in matches {
+        let mut label = String::new();
+        let mut last = 0;
+        for m in re.find_iter(&item.label) {
+            label.push_str(&item.label[last..m.start()]);
+            label.push_str(&m.as_str().black().on_yellow().to_string());
+            last = m.end();
+        }
+        label.push_str(&item.label[last..]);
+
+        println!(
+            "{} {} {}",
+            checkbox.glyph(item.complete),
+            format!("{}:", i + 1).bold().cyan(),
+            label
+        );
+    }
+
+    Ok(())
+}
+
+/// Print the labels changed by `todo replace` as a diff, given the (zero-based) positions and old
+/// labels of the changed items.
+pub fn print_replacements(list: &TodoList, changed: &[(usize, String)]) {
+    for (i, before) in changed {
+        let after = &list.items()[*i].label;
+        println!("{}", format!("{}:", i + 1).bold().cyan());
+        println!("  {}", format!("- {before}").red());
+        println!("  {}", format!("+ {after}").green());
+    }
+}
+
+/// Print every detail of an item, given its (zero-based) position in the todo list.
+pub fn show_item(i: usize, item: &Todo) {
+    println!("{}", format!("{}: {}", i + 1, item.label).bold());
+    println!("  ID:        id:{}", item.id);
+    println!("  Status:    {}", item.state());
+    println!("  Created:   {}", format_timestamp(item.created_at));
+    if let Some(completed_at) = item.completed_at {
+        println!("  Completed: {}", format_timestamp(completed_at));
+    }
+    if let Some(priority) = item.priority {
+        println!("  Priority:  {priority}");
+    }
+    if let Some(estimate) = item.estimate {
+        println!("  Estimate:  {estimate}");
+    }
+    if item.is_planned(dates::today()) {
+        println!("  Planned:   today");
+    }
+    if item.focused {
+        println!("  Focused:   yes");
+    }
+    if let Some(start) = item.start {
+        let start = format!(
+            "{} ({})",
+            dates::format_date(start),
+            dates::describe(start, dates::today())
+        );
+        println!("  Starts:    {start}");
+    }
+    if let Some(due) = item.due {
+        let today = dates::today();
+        let due = format!(
+            "{} ({})",
+            dates::format_date(due),
+            dates::describe(due, today)
+        );
+        if item.is_overdue(today) {
+            println!("  Due:       {}", due.red());
+        } else {
+            println!("  Due:       {due}");
+        }
+    }
+    if let Some(recurrence) = &item.recurrence {
+        println!("  Repeats:   {}", recurrence.describe());
+    }
+    if !item.tags.is_empty() {
+        let tags: Vec<String> = item.tags.iter().map(|tag| format!("+{tag}")).collect();
+        println!("  Tags:      {}", tags.join(" "));
+    }
+    if let Some(project) = &item.project {
+        println!("  Project:   {project}");
+    }
+    if let Some(context) = &item.context {
+        println!("  Context:   @{context}");
+    }
+    if let Some(assignee) = &item.assignee {
+        println!("  Assignee:  {assignee}");
+    }
+    if !item.depends_on.is_empty() {
+        let ids: Vec<String> = item
+            .depends_on
+            .iter()
+            .map(|id| format!("id:{id}"))
+            .collect();
+        println!("  Blockers:  {}", ids.join(", "));
+    }
+    if !item.time_log.is_empty() {
+        let running = if item.is_timing() {
+            " (timer running)"
+        } else {
+            ""
+        };
+        println!(
+            "  Time:      {}{running}",
+            format_duration(item.time_spent(Utc::now()))
+        );
+    }
+    if item.pomodoros > 0 {
+        println!("  Pomodoros: {}", item.pomodoros);
+    }
+    if let Some(note) = &item.note {
+        println!("  Note:");
+        for line in note.lines() {
+            println!("    {line}");
+        }
+    }
+    if let Some(url) = &item.url {
+        println!("  Link:      {url}");
+    }
+    if !item.comments.is_empty() {
+        println!("  Comments:");
+        for comment in &item.comments {
+            let author = comment
+                .author
+                .as_ref()
+                .map_or(String::new(), |author| format!(" {author}"));
+            println!(
+                "    {}",
+                format!("{}{author}:", format_timestamp(comment.at)).dimmed()
+            );
+            for line in comment.text.lines() {
+                println!("      {line}");
+            }
+        }
+    }
+}
+
+/// Print the archived items, oldest first, with the time they were archived.
+pub fn print_archive(items: &[ArchivedTodo], checkbox: &Checkbox) {
+    if items.is_empty() {
+        println!("The archive is empty.");
+        return;
+    }
+
+    for (i, item) in items.iter().enumerate() {
+        println!(
+            "{}{}",
+            format!(
+                "{} {}: {} (archived {})",
+                checkbox.done,
+                i + 1,
+                item.todo.label,
+                format_timestamp(item.archived_at)
+            )
+            .green(),
+            format!(" id:{}", item.todo.id).dimmed()
+        );
+    }
+}
+
+/// Print the backups of the todo list, newest first, with when they were made and how many items
+/// they hold.
+pub fn print_backups(paths: &[PathBuf]) -> Result<(), TodoError> {
+    if paths.is_empty() {
+        println!("There are no backups yet.");
+        return Ok(());
+    }
+
+    for (i, path) in paths.iter().enumerate() {
+        let modified = fs::metadata(path)
+            .and_then(|meta| meta.modified())
+            .map_err(|err| TodoError::io("Could not read a backup", err))?;
+        let contents = read_data(path)?.unwrap_or_default();
+        let (_, lines) = migrate::split_header(path, &contents)?;
+        let count = lines.iter().filter(|line| !line.trim().is_empty()).count();
+        println!(
+            "{}: {} ({count} item(s))",
+            i + 1,
+            format_timestamp(modified.into())
+        );
+    }
+    Ok(())
+}
+
+/// Print the commands that changed the todo list, oldest first, and what each of them changed.
+pub fn print_history(entries: &[HistoryEntry]) {
+    if entries.is_empty() {
+        println!("No commands have changed the todo list yet.");
+        return;
+    }
+
+    for entry in entries {
+        println!(
+            "{} {}",
+            format_timestamp(entry.time).dimmed(),
+            entry.command.bold()
+        );
+        print_changes(entry);
+    }
+}
+
+/// Print what a command run with --dry-run would change, without saving it.
+pub fn print_dry_run(entry: Option<&HistoryEntry>) {
+    match entry {
+        Some(entry) => {
+            println!("{}", "Would change (nothing was saved):".bold());
+            print_changes(entry);
+        }
+        None => println!("Nothing would change."),
+    }
+}
+
+/// Print the items a command added (+), removed (-), and changed (~).
+fn print_changes(entry: &HistoryEntry) {
+    for change in &entry.changes {
+        match (&change.before, &change.after) {
+            (None, Some(after)) => println!("  {}", format!("+ {}", after.label).green()),
+            (Some(before), None) => println!("  {}", format!("- {}", before.label).red()),
+            (Some(before), Some(after)) => println!(
+                "  ~ {}: {}",
+                before.label,
+                describe_change(before, after).join(", ")
+            ),
+            (None, None) => {}
+        }
+    }
+    if entry.reordered {
+        println!("  {}", "items were reordered".dimmed());
+    }
+}
+
+/// Print the entries of the done log from the days between two dates, under a header for every
+/// day, with the time each item was checked.
+pub fn print_done(entries: &[DoneEntry], first: NaiveDate, last: NaiveDate, today: NaiveDate) {
+    let entries: Vec<(DateTime<FixedOffset>, &str)> = entries
+        .iter()
+        .map(|entry| (dates::local(entry.at), entry.label.as_str()))
+        .filter(|(at, _)| (first..=last).contains(&at.date_naive()))
+        .collect();
+    if entries.is_empty() {
+        let day = describe_day(first, today);
+        if first != last {
+            println!("Nothing was checked since {}.", dates::format_date(first));
+        } else if day == "Today" || day == "Yesterday" {
+            println!("Nothing was checked {}.", day.to_lowercase());
+        } else {
+            println!("Nothing was checked on {day}.");
+        }
+        return;
+    }
+
+    let mut day = None;
+    for (at, label) in entries {
+        if day != Some(at.date_naive()) {
+            if day.is_some() {
+                println!();
+            }
+            day = Some(at.date_naive());
+            println!("{}", describe_day(at.date_naive(), today).bold());
+        }
+        println!("  {} {label}", at.format("%H:%M").to_string().dimmed());
+    }
+}
+
+/// "Today", "Yesterday", or the weekday and date, i.e., "Monday 2025-03-03".
+fn describe_day(date: NaiveDate, today: NaiveDate) -> String {
+    if date == today {
+        "Today".to_string()
+    } else if today.pred_opt() == Some(date) {
+        "Yesterday".to_string()
+    } else {
+        format!("{} {}", date.format("%A"), dates::format_date(date))
+    }
+}
+
+/// The fields that differ between two versions of an item.
+fn describe_change(before: &Todo, after: &Todo) -> Vec<String> {
+    let mut changes = Vec::new();
+    if before.label != after.label {
+        changes.push(format!("renamed to \"{}\"", after.label));
+    }
+    if before.complete != after.complete {
+        let state = if after.complete {
+            "checked"
+        } else {
+            "unchecked"
+        };
+        changes.push(state.to_string());
+    }
+    if before.progress != after.progress && !after.complete {
+        changes.push(format!("status {}", after.state()));
+    }
+    if before.due != after.due {
+        changes.push(match after.due {
+            Some(due) => format!("due {}", dates::format_date(due)),
+            None => "due date removed".to_string(),
+        });
+    }
+    if before.planned != after.planned {
+        changes.push(match after.planned {
+            Some(day) => format!("planned for {}", dates::format_date(day)),
+            None => "unplanned".to_string(),
+        });
+    }
+    if before.estimate != after.estimate {
+        changes.push(match after.estimate {
+            Some(estimate) => format!("estimated at {estimate}"),
+            None => "estimate removed".to_string(),
+        });
+    }
+    if before.priority != after.priority {
+        changes.push(match after.priority {
+            Some(priority) => format!("priority {priority}"),
+            None => "priority removed".to_string(),
+        });
+    }
+    // A checked item hands its recurrence to the next occurrence
+    if before.recurrence != after.recurrence && before.complete == after.complete {
+        changes.push(match &after.recurrence {
+            Some(recurrence) => format!("repeats {}", recurrence.describe()),
+            None => "stopped repeating".to_string(),
+        });
+    }
+    if before.tags != after.tags {
+        changes.push("tags changed".to_string());
+    }
+    if before.pomodoros != after.pomodoros {
+        changes.push("pomodoro finished".to_string());
+    } else if before.time_log != after.time_log {
+        changes.push(match (before.is_timing(), after.is_timing()) {
+            (false, true) => "timer started".to_string(),
+            (true, false) => "timer stopped".to_string(),
+            _ => "time log changed".to_string(),
+        });
+    }
+    if before.depends_on != after.depends_on {
+        changes.push("dependencies changed".to_string());
+    }
+    if before.project != after.project {
+        changes.push(match &after.project {
+            Some(project) => format!("moved to project \"{project}\""),
+            None => "project removed".to_string(),
+        });
+    }
+    if before.context != after.context {
+        changes.push(match &after.context {
+            Some(context) => format!("moved to context @{context}"),
+            None => "context removed".to_string(),
+        });
+    }
+    if before.assignee != after.assignee {
+        changes.push(match &after.assignee {
+            Some(assignee) => format!("assigned to {assignee}"),
+            None => "unassigned".to_string(),
+        });
+    }
+    if before.note != after.note {
+        changes.push("note changed".to_string());
+    }
+    if before.url != after.url {
+        changes.push(match &after.url {
+            Some(url) => format!("linked to {url}"),
+            None => "link removed".to_string(),
+        });
+    }
+    if before.comments.len() < after.comments.len() {
+        changes.push("comment added".to_string());
+    } else if before.comments != after.comments {
+        changes.push("comments changed".to_string());
+    }
+    if before.pinned != after.pinned {
+        let state = if after.pinned { "pinned" } else { "unpinned" };
+        changes.push(state.to_string());
+    }
+    if before.focused != after.focused {
+        let state = if after.focused {
+            "focused"
+        } else {
+            "unfocused"
+        };
+        changes.push(state.to_string());
+    }
+    if before.start != after.start {
+        changes.push(match after.start {
+            Some(start) => format!("starts {}", dates::format_date(start)),
+            None => "start date removed".to_string(),
+        });
+    }
+    if before.hidden_until != after.hidden_until {
+        changes.push(match after.hidden_until {
+            Some(date) => format!("snoozed until {}", dates::format_date(date)),
+            None => "unsnoozed".to_string(),
+        });
+    }
+    if changes.is_empty() {
+        changes.push("changed".to_string());
+    }
+    changes
+}
+
+/// Print the projects of the todo list with a bar showing how many of their items are completed,
+/// marking the one commands are scoped to.
+pub fn print_projects(list: &TodoList, current: Option<&str>) {
+    let projects = list.projects();
+    if projects.is_empty() {
+        println!("There are no projects. Add items to one with `todo project <NAME> <ITEMS>...`.");
+        return;
+    }
+
+    let width = projects
+        .iter()
+        .map(|(name, _, _)| name.chars().count())
+        .max()
+        .unwrap_or(0);
+    for (name, done, total) in projects {
+        let bar = progress_bar(done, total);
+        let line = format!("{name:<width$}  {bar}  {done}/{total} done");
+
+        if Some(name) == current {
+            println!("* {}", line.bold());
+        } else {
+            println!("  {line}");
+        }
+    }
+}
+
+/// A bar filled in proportion to how many of the items are done.
+fn progress_bar(done: usize, total: usize) -> String {
+    let filled = done * PROGRESS_BAR_WIDTH / total.max(1);
+    format!(
+        "{}{}",
+        "█".repeat(filled).green(),
+        "░".repeat(PROGRESS_BAR_WIDTH - filled).dimmed()
+    )
+}
+
+/// Print the estimated work of the pending items, in total and for each tag, and the work
+/// scheduled for today next to the daily capacity.
+pub fn print_effort(pending: &[(usize, &Todo)], scheduled: Effort, capacity: Option<Estimate>) {
+    let estimated = pending
+        .iter()
+        .filter(|(_, item)| item.estimate.is_some())
+        .count();
+    let total = Effort::total(pending.iter().map(|&(_, item)| item));
+    println!(
+        "Pending work: {total} ({estimated} of {} item(s) estimated)",
+        pending.len()
+    );
+
+    let mut tags: BTreeMap<&str, Vec<&Todo>> = BTreeMap::new();
+    for &(_, item) in pending {
+        for tag in &item.tags {
+            tags.entry(tag).or_default().push(item);
+        }
+    }
+    let tags: Vec<(String, Effort)> = tags
+        .into_iter()
+        .map(|(tag, items)| (format!("+{tag}"), Effort::total(items)))
+        .filter(|(_, effort)| *effort != Effort::default())
+        .collect();
+    if !tags.is_empty() {
+        let width = tags
+            .iter()
+            .map(|(tag, _)| tag.chars().count())
+            .max()
+            .unwrap_or(0);
+        println!();
+        for (tag, effort) in tags {
+            println!("  {tag:<width$}  {effort}");
+        }
+    }
+
+    println!();
+    match capacity {
+        Some(capacity) => {
+            let line = format!(
+                "Scheduled for today: {} of {capacity} a day",
+                scheduled.in_unit_of(capacity)
+            );
+            if scheduled.exceeds(capacity) {
+                println!("{}", line.red());
+            } else {
+                println!("{line}");
+            }
+        }
+        None => println!("Scheduled for today: {scheduled}"),
+    }
+}
+
+/// Print the items proposed by `todo plan`, and how much of the capacity they take.
+pub fn print_plan(plan: &Plan, capacity: Estimate, options: &ListOptions) {
+    let effort = plan.effort.in_unit_of(capacity);
+    println!(
+        "{}",
+        format!("Plan for today: {effort} of {capacity}").bold()
+    );
+    print_items(&plan.items, &HashSet::new(), options);
+    if plan.effort.exceeds(capacity) {
+        println!(
+            "{}",
+            "The items due today alone take more than the capacity.".red()
+        );
+    }
+    if plan.unestimated > 0 {
+        let line = format!(
+            "{} item(s) without an estimate were left out. Add one with `todo estimate`.",
+            plan.unestimated
+        );
+        println!("{}", line.dimmed());
+    }
+}
+
+/// Print the removed items, oldest first, with the time they were removed.
+pub fn print_trash(items: &[TrashedTodo], checkbox: &Checkbox) {
+    if items.is_empty() {
+        println!("The trash is empty.");
+        return;
+    }
+
+    for (i, item) in items.iter().enumerate() {
+        println!(
+            "{}{}",
+            format!(
+                "{} {}: {} (removed {})",
+                checkbox.glyph(item.todo.complete),
+                i + 1,
+                item.todo.label,
+                format_timestamp(item.deleted_at)
+            )
+            .dimmed(),
+            format!(" id:{}", item.todo.id).dimmed()
+        );
+    }
+}
+
+/// Print the counts of a todo list, one per line (i.e., "pending 4"), as a single summary line, or
+/// as a JSON object.
+pub fn print_counts(counts: &Counts, summary: bool, json: bool) -> Result<(), TodoError> {
+    if summary {
+        println!("{}", counts.summary());
+    } else if json {
+        let json = serde_json::to_string_pretty(counts).map_err(TodoError::Serialize)?;
+        println!("{json}");
+    } else {
+        println!("total {}", counts.total);
+        println!("pending {}", counts.pending);
+        println!("done {}", counts.done);
+        println!("overdue {}", counts.overdue);
+    }
+    Ok(())
+}
+
+/// Print how many items are pending, done, and overdue on a single line. The porcelain format,
+/// "P:4 D:2 O:1", is uncolored and stays the same, so scripts can rely on it.
+pub fn print_status(counts: &Counts, porcelain: bool) {
+    if porcelain {
+        println!(
+            "P:{} D:{} O:{}",
+            counts.pending, counts.done, counts.overdue
+        );
+        return;
+    }
+
+    let overdue = format!("{} overdue", counts.overdue);
+    let overdue = if counts.overdue > 0 {
+        overdue.red().to_string()
+    } else {
+        overdue
+    };
+    println!(
+        "{} pending, {} done, {overdue}",
+        counts.pending, counts.done
+    );
+}
+
+/// Print a summary of the todo list and how many items were completed recently, optionally with a
+/// chart of the last days, or all of it as a JSON object.
+pub fn print_stats(stats: &Stats, chart: bool, json: bool) -> Result<(), TodoError> {
+    if json {
+        let json = serde_json::to_string_pretty(stats).map_err(TodoError::Serialize)?;
+        println!("{json}");
+        return Ok(());
+    }
+
+    println!(
+        "{} pending, {} completed, {} archived ({:.0}% done)",
+        stats.pending,
+        stats.completed,
+        stats.archived,
+        stats.completion_rate * 100.0
+    );
+    println!();
+    println!("Completed today:        {}", stats.completed_today);
+    println!(
+        "Completed this week:    {} ({:.1} per day)",
+        stats.completed_week,
+        stats.completed_week as f64 / 7.0
+    );
+    println!(
+        "Completed in 4 weeks:   {} ({:.1} per week)",
+        stats.completed_month,
+        stats.completed_month as f64 / 4.0
+    );
+
+    if let Some(oldest) = &stats.oldest_open {
+        let days = (Utc::now() - oldest.created_at).num_days();
+        println!(
+            "Oldest open item:       {}: {} (added {}, {} day(s) ago)",
+            oldest.position,
+            oldest.label,
+            format_timestamp(oldest.created_at),
+            days
+        );
+    }
+
+    if chart {
+        println!();
+        print_chart(&stats.days);
+    }
+    Ok(())
+}
+
+/// How many characters wide the longest bar of a chart is.
+const CHART_WIDTH: usize = 40;
+
+/// Print a bar for every day of how many items were open at the end of it, and how much that
+/// changed over all the days.
+fn print_chart(days: &[DayStats]) {
+    let max = days.iter().map(|day| day.open).max().unwrap_or(0);
+    println!("{}", "Open items per day".bold());
+    for &DayStats { date, open, .. } in days {
+        let width = (open * CHART_WIDTH).checked_div(max).unwrap_or(0);
+        // Days with open items always get some bar
+        let bar = "█".repeat(width.max((open > 0) as usize));
+        let date = date.format("%a %m-%d").to_string();
+        println!("  {}  {bar} {open}", date.dimmed());
+    }
+
+    if let (Some(first), Some(last)) = (days.first(), days.last()) {
+        let (first, last, count) = (first.open, last.open, days.len());
+        let trend = match last.cmp(&first) {
+            Ordering::Less => format!("down {} in {count} day(s)", first - last).green(),
+            Ordering::Greater => format!("up {} in {count} day(s)", last - first).red(),
+            Ordering::Equal => format!("unchanged in {count} day(s)").normal(),
+        };
+        println!("  {trend}");
+    }
+}
+
+/// Print the todo list
+pub fn print_list(list: &TodoList, options: &ListOptions) {
+    if list.is_empty() {
+        println!("Nothing to do!\n\nRun `todo help` for help.");
+        return;
+    }
+
+    let items: Vec<(usize, &Todo)> = list.items().iter().enumerate().collect();
+    print_items(&items, &list.blocked(), options);
+}
+
+/// How many columns wide the terminal is, or None if stdout isn't a terminal.
+pub fn terminal_width() -> Option<usize> {
+    terminal_size::terminal_size().map(|(width, _)| usize::from(width.0))
+}
+
+/// Print the given items alongside their (zero-based) positions in the todo list,
+/// and optionally when they were created and completed. Blocked items (by ID) are dimmed.
+pub fn print_items(items: &[(usize, &Todo)], blocked: &HashSet<u64>, options: &ListOptions) {
+    let theme = &options.theme;
+    let today = dates::today();
+    // Padded and aligned numbers are as wide as the widest one
+    let number_width = items
+        .iter()
+        .map(|&(i, item)| match options.numbering {
+            Numbering::Ids => format!("id:{}", item.id).len(),
+            _ => (i + 1).to_string().len(),
+        })
+        .max()
+        .unwrap_or(0);
+
+    for &(i, item) in items {
+        let number = match options.numbering {
+            Numbering::Plain => format!("{}: ", i + 1),
+            Numbering::Padded => format!("{:0number_width$}: ", i + 1),
+            Numbering::Aligned => format!("{:>number_width$}: ", i + 1),
+            Numbering::Hidden => String::new(),
+            Numbering::Ids => format!("{:<number_width$} ", format!("id:{}", item.id)),
+        };
+        let due = match item.due {
+            Some(date) => format!(" (due {})", format_due(date, today, options.exact_dates)),
+            None => String::new(),
+        };
+        let marker = item.priority.map_or("", |p| p.marker());
+        let pin = if item.pinned { "★ " } else { "" };
+        let mut tags: String = item.tags.iter().map(|tag| format!(" +{tag}")).collect();
+        if options.numbering != Numbering::Ids {
+            tags.push_str(&format!(" id:{}", item.id));
+        }
+        if let Some(assignee) = &item.assignee {
+            tags.push_str(&format!(" @{assignee}"));
+        }
+        if let Some(estimate) = item.estimate {
+            tags.push_str(&format!(" ~{estimate}"));
+        }
+        if item.url.is_some() {
+            tags.push_str(" 🔗");
+        }
+        if item.recurrence.is_some() && !item.complete {
+            tags.push_str(" ↻");
+        }
+        if let Some(date) = item.hidden_until.filter(|_| item.is_snoozed(today)) {
+            tags.push_str(&format!(" snoozed until {}", dates::format_date(date)));
+        }
+        if let Some(date) = item.start.filter(|_| item.is_deferred(today)) {
+            tags.push_str(&format!(" starts {}", dates::format_date(date)));
+        }
+        if item.is_timing() {
+            tags.push_str(" ⏱ timer running");
+        }
+        if item.is_planned(today) && !item.complete {
+            tags.push_str(" [today]");
+        }
+        if matches!(item.state(), Status::InProgress | Status::Waiting) {
+            tags.push_str(&format!(" [{}]", item.state()));
+        }
+        let is_blocked = blocked.contains(&item.id);
+        if is_blocked {
+            let ids: Vec<String> = item
+                .depends_on
+                .iter()
+                .map(|id| format!("id:{id}"))
+                .collect();
+            tags.push_str(&format!(" blocked by {}", ids.join(", ")));
+        }
+        if options.dates {
+            tags.push_str(&format!(" created {}", format_timestamp(item.created_at)));
+            if let Some(completed_at) = item.completed_at {
+                tags.push_str(&format!(" completed {}", format_timestamp(completed_at)));
+            }
+        }
+
+        let checkbox = if item.complete {
+            &options.checkbox.done
+        } else {
+            &options.checkbox.pending
+        };
+        let prefix = format!("{checkbox} {number}{pin}{marker}");
+        let prefix_width = prefix.chars().count();
+        let lines = fit_label(
+            &item.label,
+            &due,
+            tags.chars().count(),
+            prefix_width,
+            options.width,
+            options.long_labels,
+        );
+
+        let color = theme.incomplete(item, today);
+        let paint = |text: &str| {
+            if item.complete {
+                text.color(theme.completed)
+            } else if is_blocked {
+                text.dimmed()
+            } else {
+                text.color(color)
+            }
+        };
+        let prefix = if item.complete || is_blocked {
+            paint(&prefix).to_string()
+        } else {
+            let marker = match item.priority {
+                Some(Priority::High) => marker.red().bold(),
+                Some(Priority::Medium) => marker.yellow(),
+                Some(Priority::Low) => marker.blue(),
+                None => marker.normal(),
+            };
+            format!(
+                "{}{}{}",
+                format!("{checkbox} {number}").color(color),
+                pin.color(theme.pinned).bold(),
+                marker
+            )
+        };
+
+        // Wrapped lines line up under the start of the label, and the tags follow the last one
+        let indent = " ".repeat(prefix_width);
+        for (n, line) in lines.iter().enumerate() {
+            let start = if n == 0 { &prefix } else { &indent };
+            let tags = if n + 1 == lines.len() { &tags } else { "" };
+            println!("{start}{}{}", paint(line), tags.dimmed());
+        }
+    }
+}
+
+/// The label and due date of an item, fitted into the width left in the terminal after its
+/// checkbox (`indent` wide) and before its tags. Without a known width, or with long labels left
+/// to the terminal, they are a single line as they are.
+fn fit_label(
+    label: &str,
+    due: &str,
+    tags_width: usize,
+    indent: usize,
+    width: Option<usize>,
+    long_labels: LongLabels,
+) -> Vec<String> {
+    let Some(width) = width.filter(|_| long_labels != LongLabels::Off) else {
+        return vec![format!("{label}{due}")];
+    };
+    let room = width.saturating_sub(indent).max(MIN_LABEL_WIDTH);
+    match long_labels {
+        // The due date is kept, and the label is cut off to make room for it
+        LongLabels::Truncate => {
+            let label_room = room
+                .saturating_sub(due.chars().count() + tags_width)
+                .max(MIN_LABEL_WIDTH);
+            vec![format!("{}{due}", truncate_label(label, label_room))]
+        }
+        _ => {
+            let mut lines = wrap_text(&format!("{label}{due}"), room);
+            // Tags that don't fit after the last line go on a line of their own
+            if lines.last().map_or(0, |line| line.chars().count()) + tags_width > room {
+                lines.push(String::new());
+            }
+            lines
+        }
+    }
+}
+
+/// The text cut off with "…" if it's wider than `width`.
+fn truncate_label(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let kept: String = text.chars().take(width.saturating_sub(1)).collect();
+    kept + "…"
+}
+
+/// The text split into lines at most `width` wide, between words where possible.
+fn wrap_text(text: &str, width: usize) -> Vec<String> {
+    let mut lines = vec![String::new()];
+    for word in text.split(' ') {
+        let line = lines.last_mut().unwrap();
+        let line_width = line.chars().count();
+        let word_width = word.chars().count();
+        if line_width > 0 && line_width + 1 + word_width <= width {
+            line.push(' ');
+            line.push_str(word);
+            continue;
+        }
+        if line_width > 0 {
+            lines.push(String::new());
+        }
+        // Words wider than a whole line are split across lines
+        let mut chars: Vec<char> = word.chars().collect();
+        while chars.len() > width {
+            let rest = chars.split_off(width);
+            lines.last_mut().unwrap().extend(&chars);
+            lines.push(String::new());
+            chars = rest;
+        }
+        lines.last_mut().unwrap().extend(&chars);
+    }
+    lines
+}
+
+/// Print the time spent on each item, and on all items per day, counting running timers up to
+/// now. Only time since the given date is counted.
+pub fn print_time_report(list: &TodoList, since: Option<NaiveDate>) {
+    let now = Utc::now();
+    let mut items: Vec<(usize, &Todo, TimeDelta)> = Vec::new();
+    let mut days: BTreeMap<NaiveDate, TimeDelta> = BTreeMap::new();
+
+    for (i, item) in list.items().iter().enumerate() {
+        let mut total = TimeDelta::zero();
+        for interval in &item.time_log {
+            let mut start = interval.start;
+            let end = interval.end.unwrap_or(now);
+
+            // An interval that goes past midnight counts towards both days
+            while start < end {
+                let day = dates::local(start).date_naive();
+                let midnight = day
+                    .succ_opt()
+                    .and_then(dates::start_of_day)
+                    .map_or(end, |next| next.min(end));
+                if since.is_none_or(|since| day >= since) {
+                    *days.entry(day).or_insert_with(TimeDelta::zero) += midnight - start;
+                    total += midnight - start;
+                }
+                start = midnight;
+            }
+        }
+        if total > TimeDelta::zero() {
+            items.push((i, item, total));
+        }
+    }
+
+    if items.is_empty() {
+        println!("No time was tracked. Start a timer with `todo start <ITEM>`.");
+        return;
+    }
+
+    println!("{}", "Time per item".bold());
+    for (i, item, total) in &items {
+        println!(
+            "  {:>8}  {}: {}",
+            format_duration(*total),
+            i + 1,
+            item.label
+        );
+    }
+    println!();
+    println!("{}", "Time per day".bold());
+    for (day, total) in &days {
+        println!(
+            "  {:>8}  {}",
+            format_duration(*total),
+            dates::format_date(*day)
+        );
+    }
+    let total: TimeDelta = days.values().sum();
+    println!("  {:>8}  total", format_duration(total).bold());
+}
+
+/// Format a span of time in hours and minutes, i.e., "2h 05m" or "25m".
+pub fn format_duration(duration: TimeDelta) -> String {
+    let minutes = duration.num_minutes();
+    if minutes < 60 {
+        return format!("{minutes}m");
+    }
+    format!("{}h {:02}m", minutes / 60, minutes % 60)
+}
+
+/// Format a due date as YYYY-MM-DD, or relative to today (i.e., "tomorrow" or "3 days ago").
+fn format_due(date: NaiveDate, today: NaiveDate, exact: bool) -> String {
+    if exact {
+        dates::format_date(date)
+    } else {
+        dates::describe(date, today)
+    }
+}
+
+/// Format a timestamp in the local timezone, with the date as set with the "date-format" setting.
+fn format_timestamp(timestamp: DateTime<Utc>) -> String {
+    dates::local(timestamp)
+        .format(&format!("{} %H:%M", dates::date_pattern()))
+        .to_string()
+}
@@ -0,0 +1,42 @@
+use crate::error::TodoError;
+use std::path::PathBuf;
+use std::process::Command;
+use std::{env, fs, process};
+
+/// Let the user edit some text in their editor ($VISUAL, then $EDITOR, falling back to "vi"),
+/// returning the saved text.
+pub fn edit_text(initial: &str) -> Result<String, TodoError> {
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| String::from("vi"));
+
+    // The editor may be given with arguments, i.e., "code --wait"
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or("vi");
+
+    let path = temp_path();
+    fs::write(&path, initial)
+        .map_err(|err| TodoError::io("Could not create a temporary file for the editor", err))?;
+
+    let status = Command::new(program).args(parts).arg(&path).status();
+    let result = match status {
+        Ok(status) if status.success() => fs::read_to_string(&path)
+            .map_err(|err| TodoError::io("Could not read the edited file", err)),
+        Ok(status) => Err(TodoError::Usage(format!(
+            "The editor \"{editor}\" exited with {status}, so nothing was changed."
+        ))),
+        Err(err) => Err(TodoError::io(
+            format!("Could not start the editor \"{editor}\""),
+            err,
+        )),
+    };
+
+    let _ = fs::remove_file(&path);
+    result
+}
+
+fn temp_path() -> PathBuf {
+    let mut path = env::temp_dir();
+    path.push(format!("todo-{}.txt", process::id()));
+    path
+}
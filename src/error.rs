@@ -0,0 +1,265 @@
+use serde_json::{Value, json};
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+/// Errors that can occur while reading, changing, or writing the todo list and settings.
+#[derive(Debug)]
+pub enum TodoError {
+    /// The data directory couldn't be found.
+    NoDataDir,
+    /// The config directory couldn't be found.
+    NoConfigDir,
+    /// Reading or writing a file failed.
+    Io { context: String, source: io::Error },
+    /// A line in the data file isn't a valid todo item.
+    ParseLine {
+        line: String,
+        source: serde_json::Error,
+    },
+    /// The settings file isn't valid.
+    ParseSettings(toml::de::Error),
+    /// The settings file of an older version isn't valid.
+    ParseLegacySettings(serde_json::Error),
+    /// The settings couldn't be serialized.
+    SerializeSettings(toml::ser::Error),
+    /// A todo item couldn't be serialized.
+    Serialize(serde_json::Error),
+    /// The setting, or the option given for it, doesn't exist.
+    InvalidSetting { name: String, value: String },
+    /// A search pattern isn't a valid regular expression.
+    InvalidRegex(regex::Error),
+    /// A format string for printing items can't be used.
+    InvalidTemplate { template: String, reason: String },
+    /// A row of an imported CSV file (counting the header as row 1) isn't a valid item.
+    InvalidCsv { row: usize, reason: String },
+    /// An imported JSON file isn't in the expected shape.
+    InvalidJson(serde_json::Error),
+    /// A data file is in a format version this program can't read.
+    UnsupportedVersion { path: PathBuf, version: u32 },
+    /// A list name that can't be used as a file name.
+    InvalidListName(String),
+    /// Encrypting a data file failed.
+    Encrypt(String),
+    /// An encrypted data file couldn't be decrypted with the passphrase.
+    Decrypt(PathBuf),
+    /// The daemon couldn't carry out a request.
+    Daemon(String),
+    /// Syncing with a git remote or a CalDAV server failed.
+    Sync(String),
+    /// The data file couldn't be watched for changes.
+    Watch(String),
+    /// A desktop notification couldn't be shown.
+    Notification(String),
+    /// A command would write to disk in read-only mode.
+    ReadOnly,
+    /// A command that changes more than the todo list was run with --dry-run.
+    DryRun,
+    /// A command that changes more than the todo list was run in a batch.
+    Batch,
+    /// The items (or archived items, removed items, or backups) a command was given don't exist.
+    NoMatch(String),
+    /// A command was used incorrectly.
+    Usage(String),
+    /// The command an alias stands for asked for help or the version, which isn't an error, but
+    /// is printed by the caller like clap's own help.
+    Help(clap::Error),
+}
+
+impl TodoError {
+    /// The exit code for the error: 1 for a command that was used incorrectly, 2 for data that
+    /// couldn't be read or written, and 3 for items that don't exist. Success (and help) is 0.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            TodoError::Help(_) => 0,
+            TodoError::InvalidSetting { .. }
+            | TodoError::InvalidRegex(_)
+            | TodoError::InvalidTemplate { .. }
+            | TodoError::InvalidListName(_)
+            | TodoError::ReadOnly
+            | TodoError::DryRun
+            | TodoError::Batch
+            | TodoError::Usage(_) => 1,
+            TodoError::NoMatch(_) => 3,
+            _ => 2,
+        }
+    }
+
+    /// A name for the kind of error that stays the same between versions, unlike the message,
+    /// i.e., "no-match".
+    pub fn code(&self) -> &'static str {
+        match self {
+            TodoError::NoDataDir => "no-data-dir",
+            TodoError::NoConfigDir => "no-config-dir",
+            TodoError::Io { .. } => "io",
+            TodoError::ParseLine { .. } => "parse-line",
+            TodoError::ParseSettings(_) => "parse-settings",
+            TodoError::ParseLegacySettings(_) => "parse-legacy-settings",
+            TodoError::SerializeSettings(_) => "serialize-settings",
+            TodoError::Serialize(_) => "serialize",
+            TodoError::InvalidSetting { .. } => "invalid-setting",
+            TodoError::InvalidRegex(_) => "invalid-regex",
+            TodoError::InvalidTemplate { .. } => "invalid-template",
+            TodoError::InvalidCsv { .. } => "invalid-csv",
+            TodoError::InvalidJson(_) => "invalid-json",
+            TodoError::UnsupportedVersion { .. } => "unsupported-version",
+            TodoError::InvalidListName(_) => "invalid-list-name",
+            TodoError::Encrypt(_) => "encrypt",
+            TodoError::Decrypt(_) => "decrypt",
+            TodoError::Daemon(_) => "daemon",
+            TodoError::Sync(_) => "sync",
+            TodoError::Watch(_) => "watch",
+            TodoError::Notification(_) => "notification",
+            TodoError::ReadOnly => "read-only",
+            TodoError::DryRun => "dry-run",
+            TodoError::Batch => "batch",
+            TodoError::NoMatch(_) => "no-match",
+            TodoError::Usage(_) => "usage",
+            TodoError::Help(_) => "help",
+        }
+    }
+
+    /// The details of the error as a JSON object, i.e., the setting and option of an invalid
+    /// setting, or the cause of a failed write. Errors without details have an empty object.
+    pub fn context(&self) -> Value {
+        match self {
+            TodoError::Io { context, source } => {
+                json!({ "context": context, "cause": source.to_string() })
+            }
+            TodoError::ParseLine { line, source } => {
+                json!({ "line": line, "cause": source.to_string() })
+            }
+            TodoError::ParseSettings(err) => json!({ "cause": err.to_string() }),
+            TodoError::SerializeSettings(err) => json!({ "cause": err.to_string() }),
+            TodoError::ParseLegacySettings(err)
+            | TodoError::Serialize(err)
+            | TodoError::InvalidJson(err) => json!({ "cause": err.to_string() }),
+            TodoError::InvalidSetting { name, value } => json!({ "setting": name, "value": value }),
+            TodoError::InvalidRegex(err) => json!({ "cause": err.to_string() }),
+            TodoError::InvalidTemplate { template, reason } => {
+                json!({ "template": template, "reason": reason })
+            }
+            TodoError::InvalidCsv { row, reason } => json!({ "row": row, "reason": reason }),
+            TodoError::UnsupportedVersion { path, version } => {
+                json!({ "path": path, "version": version })
+            }
+            TodoError::InvalidListName(name) => json!({ "list": name }),
+            TodoError::Decrypt(path) => json!({ "path": path }),
+            TodoError::Encrypt(err)
+            | TodoError::Daemon(err)
+            | TodoError::Sync(err)
+            | TodoError::Watch(err)
+            | TodoError::Notification(err) => json!({ "cause": err }),
+            _ => json!({}),
+        }
+    }
+
+    /// The error as a line of JSON, for programs that run `todo` (see `--error-format`): its code,
+    /// exit code, message, and context.
+    pub fn to_json(&self) -> String {
+        json!({
+            "code": self.code(),
+            "exit_code": self.exit_code(),
+            "message": self.to_string(),
+            "context": self.context(),
+        })
+        .to_string()
+    }
+
+    /// Wrap an I/O error with a description of what was being done.
+    pub fn io(context: impl Into<String>, source: io::Error) -> TodoError {
+        TodoError::Io {
+            context: context.into(),
+            source,
+        }
+    }
+}
+
+impl fmt::Display for TodoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TodoError::NoDataDir => write!(f, "Cannot open data directory."),
+            TodoError::NoConfigDir => write!(f, "Could not find config directory."),
+            TodoError::Io { context, source } => write!(f, "{context}: {source}"),
+            TodoError::ParseLine { line, source } => {
+                write!(f, "Could not parse line \"{line}\" in data file: {source}")
+            }
+            TodoError::ParseSettings(err) => write!(f, "Could not parse settings file: {err}"),
+            TodoError::ParseLegacySettings(err) => {
+                write!(f, "Could not parse the old settings file: {err}")
+            }
+            TodoError::SerializeSettings(err) => {
+                write!(f, "Could not serialize the settings: {err}")
+            }
+            TodoError::Serialize(err) => {
+                write!(
+                    f,
+                    "Could not serialize the todo item into JSON format: {err}"
+                )
+            }
+            TodoError::InvalidSetting { name, value } => write!(
+                f,
+                "Failed to change setting \"{name}\" to option \"{value}\", setting or option doesn't exist."
+            ),
+            TodoError::InvalidRegex(err) => write!(f, "Invalid regular expression: {err}"),
+            TodoError::InvalidTemplate { template, reason } => write!(
+                f,
+                "Invalid template \"{template}\": {reason}. Run `todo set help` for the fields it can use."
+            ),
+            TodoError::InvalidCsv { row, reason } => {
+                write!(f, "Could not import row {row} of the CSV file: {reason}.")
+            }
+            TodoError::InvalidJson(err) => write!(f, "Could not import the JSON file: {err}."),
+            TodoError::UnsupportedVersion { path, version } => write!(
+                f,
+                "{} is in version {version} of the data format, which this version of todo can't read. Update todo to use it.",
+                path.display()
+            ),
+            TodoError::InvalidListName(name) => write!(
+                f,
+                "Invalid list name \"{name}\". List names may only contain letters, numbers, '-', and '_'."
+            ),
+            TodoError::Encrypt(err) => write!(f, "Could not encrypt the data: {err}"),
+            TodoError::Decrypt(path) => write!(
+                f,
+                "Could not decrypt {}. The passphrase is wrong or the file is damaged.",
+                path.display()
+            ),
+            TodoError::Daemon(err) => write!(f, "The daemon failed: {err}"),
+            TodoError::Sync(err) => write!(f, "Could not sync: {err}"),
+            TodoError::Watch(err) => write!(f, "Could not watch the data file: {err}"),
+            TodoError::Notification(err) => {
+                write!(f, "Could not show a desktop notification: {err}")
+            }
+            TodoError::ReadOnly => write!(
+                f,
+                "Nothing can be changed in read-only mode. Run without --read-only, or turn it off with `todo set read-only off`."
+            ),
+            TodoError::DryRun => write!(
+                f,
+                "This command changes more than the todo list, so it can't be run with --dry-run."
+            ),
+            TodoError::Batch => write!(
+                f,
+                "This command changes more than the todo list, so it can't be run in a batch."
+            ),
+            TodoError::NoMatch(msg) | TodoError::Usage(msg) => write!(f, "{msg}"),
+            TodoError::Help(help) => write!(f, "{help}"),
+        }
+    }
+}
+
+impl std::error::Error for TodoError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TodoError::Io { source, .. } => Some(source),
+            TodoError::ParseLine { source, .. } => Some(source),
+            TodoError::ParseSettings(err) => Some(err),
+            TodoError::ParseLegacySettings(err) | TodoError::Serialize(err) => Some(err),
+            TodoError::InvalidJson(err) => Some(err),
+            TodoError::SerializeSettings(err) => Some(err),
+            TodoError::InvalidRegex(err) => Some(err),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,226 @@
+//! How much work items are expected to take, and how much of it fits in a day.
+//!
+//! An estimate is either a length of time, i.e., "2h" or "1h30m", or story points, i.e., "3pt".
+//! Times and points can't be compared, so they are added up separately.
+
+use crate::todo::Todo;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// How much work an item is expected to take. It is written in the data file as text, i.e., "2h".
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(try_from = "String", into = "String")]
+pub enum Estimate {
+    Minutes(u32),
+    Points(u32),
+}
+
+/// The sum of a number of estimates, with times and points added up separately.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct Effort {
+    pub minutes: u32,
+    pub points: u32,
+}
+
+impl FromStr for Estimate {
+    type Err = String;
+
+    /// Parse a length of time (i.e., "45m", "2h", "1h30m", or "1.5h") or a number of points
+    /// (i.e., "3pt", or a bare "3").
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "invalid estimate \"{s}\" (expected a time like \"2h\" or \"45m\", or points like \"3pt\")"
+            )
+        };
+        let text = s.trim().to_lowercase();
+        let points = ["points", "pts", "pt", "p"]
+            .iter()
+            .find_map(|unit| text.strip_suffix(unit))
+            .unwrap_or(&text)
+            .trim_end();
+        if !points.is_empty() && points.chars().all(|c| c.is_ascii_digit()) {
+            return points.parse().map(Estimate::Points).map_err(|_| invalid());
+        }
+        if text.is_empty() {
+            return Err(invalid());
+        }
+
+        let mut minutes = 0.0;
+        let mut rest = text.as_str();
+        while !rest.is_empty() {
+            let number_end = rest
+                .find(|c: char| !c.is_ascii_digit() && c != '.')
+                .ok_or_else(invalid)?;
+            let number: f64 = rest[..number_end].parse().map_err(|_| invalid())?;
+            let unit_end = rest[number_end..]
+                .find(|c: char| !c.is_ascii_alphabetic())
+                .map_or(rest.len(), |i| number_end + i);
+            minutes += number
+                * match &rest[number_end..unit_end] {
+                    "h" | "hr" | "hrs" | "hour" | "hours" => 60.0,
+                    "m" | "min" | "mins" | "minute" | "minutes" => 1.0,
+                    _ => return Err(invalid()),
+                };
+            rest = rest[unit_end..].trim_start();
+        }
+        Ok(Estimate::Minutes(minutes.round() as u32))
+    }
+}
+
+impl fmt::Display for Estimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Estimate::Minutes(minutes) if minutes < 60 => write!(f, "{minutes}m"),
+            Estimate::Minutes(minutes) if minutes % 60 == 0 => write!(f, "{}h", minutes / 60),
+            Estimate::Minutes(minutes) => write!(f, "{}h{:02}m", minutes / 60, minutes % 60),
+            Estimate::Points(points) => write!(f, "{points}pt"),
+        }
+    }
+}
+
+impl Estimate {
+    /// Whether both estimates are times, or both are points.
+    pub fn same_unit(&self, other: Estimate) -> bool {
+        matches!(
+            (self, other),
+            (Estimate::Minutes(_), Estimate::Minutes(_))
+                | (Estimate::Points(_), Estimate::Points(_))
+        )
+    }
+}
+
+impl TryFrom<String> for Estimate {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Estimate> for String {
+    fn from(estimate: Estimate) -> String {
+        estimate.to_string()
+    }
+}
+
+impl Effort {
+    /// The estimates of the items added up. Items without an estimate count for nothing.
+    pub fn total<'a>(items: impl IntoIterator<Item = &'a Todo>) -> Effort {
+        let mut effort = Effort::default();
+        for estimate in items.into_iter().filter_map(|item| item.estimate) {
+            effort.add(estimate);
+        }
+        effort
+    }
+
+    pub fn add(&mut self, estimate: Estimate) {
+        match estimate {
+            Estimate::Minutes(minutes) => self.minutes += minutes,
+            Estimate::Points(points) => self.points += points,
+        }
+    }
+
+    /// The part of the effort in the same unit as the capacity, i.e., the hours for a capacity
+    /// of "6h".
+    pub fn in_unit_of(&self, capacity: Estimate) -> Estimate {
+        match capacity {
+            Estimate::Minutes(_) => Estimate::Minutes(self.minutes),
+            Estimate::Points(_) => Estimate::Points(self.points),
+        }
+    }
+
+    /// Whether the effort is more than fits in the capacity. Only the estimates in the unit of
+    /// the capacity count.
+    pub fn exceeds(&self, capacity: Estimate) -> bool {
+        match (self.in_unit_of(capacity), capacity) {
+            (Estimate::Minutes(minutes), Estimate::Minutes(limit)) => minutes > limit,
+            (Estimate::Points(points), Estimate::Points(limit)) => points > limit,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Effort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.minutes, self.points) {
+            (0, 0) => write!(f, "nothing"),
+            (minutes, 0) => write!(f, "{}", Estimate::Minutes(minutes)),
+            (0, points) => write!(f, "{}", Estimate::Points(points)),
+            (minutes, points) => {
+                write!(
+                    f,
+                    "{} + {}",
+                    Estimate::Minutes(minutes),
+                    Estimate::Points(points)
+                )
+            }
+        }
+    }
+}
+
+/// The items picked for a day by `todo plan`, most urgent first, and the work they add up to.
+pub struct Plan<'a> {
+    pub items: Vec<(usize, &'a Todo)>,
+    pub effort: Effort,
+    /// How many items were left out because they have no estimate in the unit of the capacity.
+    pub unestimated: usize,
+}
+
+/// The work scheduled for today: the incomplete items that are due today or overdue, or planned
+/// for today, and that can be worked on.
+pub fn scheduled_today(items: &[Todo], today: NaiveDate) -> Effort {
+    Effort::total(items.iter().filter(|item| {
+        !item.complete && !item.is_hidden(today) && (is_due(item, today) || item.is_planned(today))
+    }))
+}
+
+/// Fill a day with the ranked items, most urgent first, until the capacity is used up. Items due
+/// today or overdue are always picked, even past the capacity. The other items are picked if
+/// they have an estimate in the unit of the capacity that still fits.
+pub fn plan<'a>(ranked: &[(usize, &'a Todo)], capacity: Estimate, today: NaiveDate) -> Plan<'a> {
+    let mut picked: Vec<usize> = Vec::new();
+    let mut effort = Effort::default();
+    for (n, &(_, item)) in ranked.iter().enumerate() {
+        if is_due(item, today) {
+            picked.push(n);
+            if let Some(estimate) = item.estimate {
+                effort.add(estimate);
+            }
+        }
+    }
+
+    let mut unestimated = 0;
+    for (n, &(_, item)) in ranked.iter().enumerate() {
+        if is_due(item, today) {
+            continue;
+        }
+        match item
+            .estimate
+            .filter(|estimate| estimate.same_unit(capacity))
+        {
+            Some(estimate) => {
+                let mut with = effort;
+                with.add(estimate);
+                if !with.exceeds(capacity) {
+                    effort = with;
+                    picked.push(n);
+                }
+            }
+            None => unestimated += 1,
+        }
+    }
+
+    picked.sort();
+    Plan {
+        items: picked.into_iter().map(|n| ranked[n]).collect(),
+        effort,
+        unestimated,
+    }
+}
+
+fn is_due(item: &Todo, today: NaiveDate) -> bool {
+    item.due.is_some_and(|due| due <= today)
+}
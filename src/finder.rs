@@ -0,0 +1,249 @@
+//! Picking items in a fuzzy finder, for commands given `-i` instead of positions.
+//!
+//! The finder takes over the terminal until an item is picked: typing filters the items by their
+//! labels (the letters only have to appear in order, like in skim or fzf), Up and Down move
+//! between them, Tab selects several, Enter picks the selected items (or else the highlighted
+//! one), and Esc cancels.
+
+use crate::error::TodoError;
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+use fuzzy_matcher::FuzzyMatcher;
+use fuzzy_matcher::skim::SkimMatcherV2;
+use std::collections::BTreeSet;
+use std::io::{self, IsTerminal, Stderr, Write};
+
+/// The lines above the matches: the query, and the count of matches.
+const HEADER_LINES: u16 = 2;
+
+/// An entry of the finder, shown as its position and label.
+pub struct Choice {
+    pub position: usize,
+    pub label: String,
+}
+
+struct Finder<'a> {
+    choices: &'a [Choice],
+    matcher: SkimMatcherV2,
+    query: String,
+    /// The indices of the choices matching the query, best match first, with the positions of
+    /// the matched characters in their labels.
+    matches: Vec<(usize, Vec<usize>)>,
+    /// The index in `matches` of the highlighted choice.
+    cursor: usize,
+    /// The index in `matches` of the first choice on screen.
+    scroll: usize,
+    /// The indices of the selected choices.
+    selected: BTreeSet<usize>,
+}
+
+/// Restores the terminal when the finder closes, even if drawing it failed.
+struct Screen(Stderr);
+
+impl Screen {
+    fn open() -> io::Result<Screen> {
+        let mut stderr = io::stderr();
+        terminal::enable_raw_mode()?;
+        execute!(stderr, EnterAlternateScreen)?;
+        Ok(Screen(stderr))
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = execute!(self.0, Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Let the user pick choices in the finder, which is drawn on stderr so that the output of the
+/// command can still be piped. Returns the indices of the picked choices in order, which is empty
+/// if the finder was cancelled.
+pub fn pick(choices: &[Choice], title: &str) -> Result<Vec<usize>, TodoError> {
+    if !io::stdin().is_terminal() || !io::stderr().is_terminal() {
+        return Err(TodoError::Usage(
+            "Items can only be picked with -i in a terminal. Give their positions instead."
+                .to_string(),
+        ));
+    }
+
+    let io_err = |err| TodoError::io("Could not show the finder", err);
+    let mut screen = Screen::open().map_err(io_err)?;
+    let mut finder = Finder {
+        choices,
+        matcher: SkimMatcherV2::default(),
+        query: String::new(),
+        matches: Vec::new(),
+        cursor: 0,
+        scroll: 0,
+        selected: BTreeSet::new(),
+    };
+    finder.filter();
+
+    loop {
+        finder.draw(&mut screen.0, title).map_err(io_err)?;
+        let Event::Key(key) = event::read().map_err(io_err)? else {
+            continue;
+        };
+        if key.kind == KeyEventKind::Release {
+            continue;
+        }
+        if let Some(picked) = finder.handle(key) {
+            return Ok(picked);
+        }
+    }
+}
+
+impl Finder<'_> {
+    /// Match the choices against the query again, moving back to the best match.
+    fn filter(&mut self) {
+        self.matches = if self.query.is_empty() {
+            (0..self.choices.len()).map(|i| (i, Vec::new())).collect()
+        } else {
+            let mut scored: Vec<(i64, usize, Vec<usize>)> = self
+                .choices
+                .iter()
+                .enumerate()
+                .filter_map(|(i, choice)| {
+                    let (score, indices) =
+                        self.matcher.fuzzy_indices(&choice.label, &self.query)?;
+                    Some((score, i, indices))
+                })
+                .collect();
+            // Equal scores keep the order of the todo list
+            scored.sort_by_key(|&(score, i, _)| (-score, i));
+            scored
+                .into_iter()
+                .map(|(_, i, indices)| (i, indices))
+                .collect()
+        };
+        self.cursor = 0;
+        self.scroll = 0;
+    }
+
+    /// Act on a key, returning the picked choices once the finder is closed.
+    fn handle(&mut self, key: KeyEvent) -> Option<Vec<usize>> {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => return Some(Vec::new()),
+            KeyCode::Char('c' | 'g') if ctrl => return Some(Vec::new()),
+            KeyCode::Enter => {
+                if self.selected.is_empty() {
+                    return Some(self.current().into_iter().collect());
+                }
+                return Some(self.selected.iter().copied().collect());
+            }
+            KeyCode::Tab => {
+                self.toggle();
+                self.move_cursor(1);
+            }
+            KeyCode::BackTab => {
+                self.toggle();
+                self.move_cursor(-1);
+            }
+            KeyCode::Up => self.move_cursor(-1),
+            KeyCode::Down => self.move_cursor(1),
+            KeyCode::Char('p' | 'k') if ctrl => self.move_cursor(-1),
+            KeyCode::Char('n' | 'j') if ctrl => self.move_cursor(1),
+            KeyCode::Char('u') if ctrl => {
+                self.query.clear();
+                self.filter();
+            }
+            KeyCode::Backspace if self.query.pop().is_some() => self.filter(),
+            KeyCode::Char(c) if !ctrl => {
+                self.query.push(c);
+                self.filter();
+            }
+            _ => {}
+        }
+        None
+    }
+
+    /// The index of the highlighted choice, if any choice matches.
+    fn current(&self) -> Option<usize> {
+        self.matches.get(self.cursor).map(|(i, _)| *i)
+    }
+
+    fn toggle(&mut self) {
+        if let Some(i) = self.current()
+            && !self.selected.remove(&i)
+        {
+            self.selected.insert(i);
+        }
+    }
+
+    fn move_cursor(&mut self, by: isize) {
+        let last = self.matches.len().saturating_sub(1);
+        self.cursor = self.cursor.saturating_add_signed(by).min(last);
+    }
+
+    fn draw(&mut self, out: &mut Stderr, title: &str) -> io::Result<()> {
+        let (width, height) = terminal::size()?;
+        let rows = usize::from(height.saturating_sub(HEADER_LINES)).max(1);
+        if self.cursor < self.scroll {
+            self.scroll = self.cursor;
+        } else if self.cursor >= self.scroll + rows {
+            self.scroll = self.cursor + 1 - rows;
+        }
+
+        queue!(out, Hide, Clear(ClearType::All), MoveTo(0, 0))?;
+        queue!(out, Print(format!("{title} > {}", self.query)))?;
+        let count = format!(
+            "  {}/{} ({} selected) - Tab to select, Enter to confirm, Esc to cancel",
+            self.matches.len(),
+            self.choices.len(),
+            self.selected.len()
+        );
+        queue!(
+            out,
+            MoveTo(0, 1),
+            SetAttribute(Attribute::Dim),
+            Print(truncate(&count, usize::from(width))),
+            SetAttribute(Attribute::Reset)
+        )?;
+
+        let visible = self.matches.iter().enumerate().skip(self.scroll).take(rows);
+        for (row, (n, (i, indices))) in (HEADER_LINES..).zip(visible) {
+            let choice = &self.choices[*i];
+            let cursor = if n == self.cursor { '>' } else { ' ' };
+            let mark = if self.selected.contains(i) { '*' } else { ' ' };
+            let prefix = format!("{cursor}{mark} {}: ", choice.position);
+            queue!(out, MoveTo(0, row))?;
+            if n == self.cursor {
+                queue!(out, SetAttribute(Attribute::Reverse))?;
+            }
+            queue!(out, Print(&prefix))?;
+
+            // The matched characters are in bold
+            let room = usize::from(width).saturating_sub(prefix.chars().count());
+            for (j, c) in choice.label.chars().take(room).enumerate() {
+                if indices.contains(&j) {
+                    queue!(
+                        out,
+                        SetAttribute(Attribute::Bold),
+                        Print(c),
+                        SetAttribute(Attribute::NormalIntensity)
+                    )?;
+                } else {
+                    queue!(out, Print(c))?;
+                }
+            }
+            queue!(out, SetAttribute(Attribute::Reset))?;
+        }
+
+        let query_width = title.chars().count() + 3 + self.query.chars().count();
+        let column = u16::try_from(query_width)
+            .unwrap_or(u16::MAX)
+            .min(width.saturating_sub(1));
+        queue!(out, MoveTo(column, 0), Show)?;
+        out.flush()
+    }
+}
+
+/// The text cut off to fit in a number of columns.
+fn truncate(text: &str, width: usize) -> String {
+    text.chars().take(width).collect()
+}
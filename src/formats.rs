@@ -0,0 +1,13 @@
+//! Conversions between the todo list and the formats of other tools.
+//!
+//! The `import` function of each format returns items that don't have IDs yet, so they should be
+//! added to a list with `TodoList::push`.
+
+pub mod csv;
+pub mod ics;
+pub mod json;
+pub mod markdown;
+pub mod mstodo;
+pub mod org;
+pub mod reminders;
+pub mod todotxt;
@@ -0,0 +1,166 @@
+//! CSV files, for editing the todo list in bulk or reporting on it in a spreadsheet.
+//!
+//! The first row is a header naming the columns, and every other row is an item:
+//!
+//! - `label`: The label.
+//! - `status`: "pending", "in-progress", "waiting", or "done".
+//! - `due`: The due date as YYYY-MM-DD, or empty.
+//! - `tags`: The tags separated by spaces, i.e., "work urgent".
+//! - `priority`: "high", "medium", "low", or empty.
+//!
+//! Exported files have these columns in this order. Imported files can have them in any order,
+//! and only need the label column; other columns are ignored. Fields holding commas, quotes, or
+//! line breaks are quoted, with quotes written twice (RFC 4180).
+
+use crate::error::TodoError;
+use crate::todo::Todo;
+use chrono::NaiveDate;
+
+/// The columns of an exported file, in order.
+const COLUMNS: [&str; 5] = ["label", "status", "due", "tags", "priority"];
+
+/// Convert the items of a todo list into CSV rows, after a header row.
+pub fn export(items: &[Todo]) -> String {
+    let mut buf = String::new();
+    push_row(&mut buf, &COLUMNS.map(str::to_string));
+    for item in items {
+        push_row(
+            &mut buf,
+            &[
+                item.label.clone(),
+                item.state().name().to_string(),
+                item.due.map_or(String::new(), |due| due.to_string()),
+                item.tags.join(" "),
+                item.priority.map_or(String::new(), |p| p.to_string()),
+            ],
+        );
+    }
+    buf
+}
+
+/// Parse the rows of a CSV file with a header row into items. Blank rows are skipped, and a row
+/// with an invalid field fails the whole import, so nothing is half imported.
+pub fn import(text: &str) -> Result<Vec<Todo>, TodoError> {
+    // Spreadsheets often start the file with a byte order mark
+    let mut rows = parse_rows(text.trim_start_matches('\u{feff}'))
+        .into_iter()
+        .enumerate();
+    let Some((_, header)) = rows.next() else {
+        return Ok(Vec::new());
+    };
+    let invalid = |row: usize, reason: String| TodoError::InvalidCsv {
+        row: row + 1,
+        reason,
+    };
+    let column = |name: &str| {
+        header
+            .iter()
+            .position(|column| column.trim().eq_ignore_ascii_case(name))
+    };
+    let Some(label_column) = column("label") else {
+        return Err(invalid(0, "there is no \"label\" column".to_string()));
+    };
+    let (status_column, due_column) = (column("status"), column("due"));
+    let (tags_column, priority_column) = (column("tags"), column("priority"));
+
+    let mut items: Vec<Todo> = Vec::new();
+    for (i, row) in rows {
+        if row.iter().all(|field| field.trim().is_empty()) {
+            continue;
+        }
+        let field = |column: Option<usize>| {
+            column
+                .and_then(|column| row.get(column))
+                .map_or("", |field| field.trim())
+        };
+
+        let label = field(Some(label_column));
+        if label.is_empty() {
+            return Err(invalid(i, "the label is empty".to_string()));
+        }
+        let mut todo = Todo::new(0, label);
+
+        let status = field(status_column).to_lowercase();
+        if !status.is_empty() {
+            todo.set_state(status.parse().map_err(|err| invalid(i, err))?);
+        }
+        let due = field(due_column);
+        if !due.is_empty() {
+            let date = NaiveDate::parse_from_str(due, "%Y-%m-%d").map_err(|_| {
+                invalid(
+                    i,
+                    format!("invalid due date \"{due}\" (expected YYYY-MM-DD)"),
+                )
+            })?;
+            todo.due = Some(date);
+        }
+        for tag in field(tags_column).split_whitespace() {
+            todo.add_tag(tag);
+        }
+        let priority = field(priority_column);
+        if !priority.is_empty() {
+            todo.priority = Some(
+                priority
+                    .to_lowercase()
+                    .parse()
+                    .map_err(|reason| invalid(i, reason))?,
+            );
+        }
+
+        items.push(todo);
+    }
+
+    Ok(items)
+}
+
+/// Append a row of fields, quoting the ones that need it.
+fn push_row(buf: &mut String, fields: &[String]) {
+    let fields: Vec<String> = fields.iter().map(|field| escape(field)).collect();
+    buf.push_str(&fields.join(","));
+    buf.push('\n');
+}
+
+fn escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Split CSV text into rows of fields. Quoted fields can hold commas, quotes (written twice), and
+/// line breaks, and rows can end with "\n" or "\r\n".
+pub(crate) fn parse_rows(text: &str) -> Vec<Vec<String>> {
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    let mut row: Vec<String> = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted => {
+                if chars.next_if_eq(&'"').is_some() {
+                    field.push('"');
+                } else {
+                    quoted = false;
+                }
+            }
+            '"' if field.is_empty() => quoted = true,
+            ',' if !quoted => row.push(std::mem::take(&mut field)),
+            '\r' if !quoted && chars.peek() == Some(&'\n') => {}
+            '\n' if !quoted => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            c => field.push(c),
+        }
+    }
+    // The last row may not end with a line break
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+
+    rows
+}
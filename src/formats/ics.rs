@@ -0,0 +1,101 @@
+//! The iCalendar format (<https://www.rfc-editor.org/rfc/rfc5545>), for calendar apps.
+//!
+//! Items with a due date become VTODO entries. High, medium, and low priorities map to the
+//! iCalendar priorities 1, 5, and 9, tags map to categories, the note becomes the description, and
+//! recurring items keep their RRULE.
+
+use crate::todo::{Priority, Todo};
+use chrono::{DateTime, Utc};
+
+/// Convert the items of a todo list that have a due date into an iCalendar file.
+pub fn export(items: &[Todo]) -> String {
+    let now = Utc::now();
+    let mut buf = String::new();
+    push_line(&mut buf, "BEGIN:VCALENDAR");
+    push_line(&mut buf, "VERSION:2.0");
+    push_line(&mut buf, "PRODID:-//todo-app//todo//EN");
+    for item in items.iter().filter(|item| item.due.is_some()) {
+        push_vtodo(&mut buf, item, now);
+    }
+    push_line(&mut buf, "END:VCALENDAR");
+    buf
+}
+
+/// Convert a single item into an iCalendar file holding only that item, as CalDAV servers expect.
+pub fn to_calendar(item: &Todo) -> String {
+    export(std::slice::from_ref(item))
+}
+
+/// A unique ID for the item that stays the same across exports.
+/// IDs are only unique within a list, so the creation time is included.
+pub fn uid(item: &Todo) -> String {
+    format!(
+        "{}-{}@todo-app",
+        item.created_at.timestamp_micros(),
+        item.id
+    )
+}
+
+fn push_vtodo(buf: &mut String, item: &Todo, now: DateTime<Utc>) {
+    push_line(buf, "BEGIN:VTODO");
+    push_line(buf, &format!("UID:{}", uid(item)));
+    push_line(buf, &format!("DTSTAMP:{}", timestamp(now)));
+    push_line(buf, &format!("CREATED:{}", timestamp(item.created_at)));
+    push_line(buf, &format!("SUMMARY:{}", escape(&item.label)));
+    if let Some(due) = item.due {
+        push_line(buf, &format!("DUE;VALUE=DATE:{}", due.format("%Y%m%d")));
+    }
+    if let Some(recurrence) = &item.recurrence {
+        push_line(buf, &format!("RRULE:{recurrence}"));
+    }
+    if let Some(priority) = item.priority {
+        let level = match priority {
+            Priority::High => 1,
+            Priority::Medium => 5,
+            Priority::Low => 9,
+        };
+        push_line(buf, &format!("PRIORITY:{level}"));
+    }
+    if !item.tags.is_empty() {
+        let tags: Vec<String> = item.tags.iter().map(|tag| escape(tag)).collect();
+        push_line(buf, &format!("CATEGORIES:{}", tags.join(",")));
+    }
+    if let Some(note) = &item.note {
+        push_line(buf, &format!("DESCRIPTION:{}", escape(note)));
+    }
+    if item.complete {
+        push_line(buf, "STATUS:COMPLETED");
+        if let Some(completed_at) = item.completed_at {
+            push_line(buf, &format!("COMPLETED:{}", timestamp(completed_at)));
+        }
+    } else {
+        push_line(buf, "STATUS:NEEDS-ACTION");
+    }
+    push_line(buf, "END:VTODO");
+}
+
+fn timestamp(time: DateTime<Utc>) -> String {
+    time.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Escape the characters that have a meaning in iCalendar text values.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Add a content line, folding it so no line is longer than 75 bytes.
+fn push_line(buf: &mut String, line: &str) {
+    let mut len = 0;
+    for c in line.chars() {
+        if len + c.len_utf8() > 75 {
+            buf.push_str("\r\n ");
+            len = 1;
+        }
+        buf.push(c);
+        len += c.len_utf8();
+    }
+    buf.push_str("\r\n");
+}
@@ -0,0 +1,74 @@
+//! JSON output for scripting, i.e., `todo list --json | jq '.[].label'`.
+//!
+//! The output is an array of objects with the same field names for every item, even when a field
+//! isn't set (it is then `null` or empty), so scripts don't need to check for missing fields.
+
+use crate::error::TodoError;
+use crate::estimate::Estimate;
+use crate::recur::Recurrence;
+use crate::todo::{Priority, Status, Todo};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+
+#[derive(Serialize)]
+struct JsonItem<'a> {
+    /// The one-based position, as shown in `todo list`.
+    position: usize,
+    id: u64,
+    label: &'a str,
+    complete: bool,
+    status: Status,
+    due: Option<NaiveDate>,
+    start: Option<NaiveDate>,
+    /// The RRULE of a recurring item, i.e., "FREQ=WEEKLY".
+    recurrence: Option<&'a Recurrence>,
+    priority: Option<Priority>,
+    estimate: Option<Estimate>,
+    tags: &'a [String],
+    project: Option<&'a str>,
+    context: Option<&'a str>,
+    assignee: Option<&'a str>,
+    note: Option<&'a str>,
+    url: Option<&'a str>,
+    created_at: DateTime<Utc>,
+    completed_at: Option<DateTime<Utc>>,
+    pinned: bool,
+    focused: bool,
+    planned: Option<NaiveDate>,
+    hidden_until: Option<NaiveDate>,
+    depends_on: &'a [u64],
+}
+
+/// Convert items and their (zero-based) positions in the todo list into a JSON array.
+pub fn export(items: &[(usize, &Todo)]) -> Result<String, TodoError> {
+    let items: Vec<JsonItem> = items
+        .iter()
+        .map(|&(i, item)| JsonItem {
+            position: i + 1,
+            id: item.id,
+            label: &item.label,
+            complete: item.complete,
+            status: item.state(),
+            due: item.due,
+            start: item.start,
+            recurrence: item.recurrence.as_ref(),
+            priority: item.priority,
+            estimate: item.estimate,
+            tags: &item.tags,
+            project: item.project.as_deref(),
+            context: item.context.as_deref(),
+            assignee: item.assignee.as_deref(),
+            note: item.note.as_deref(),
+            url: item.url.as_deref(),
+            created_at: item.created_at,
+            completed_at: item.completed_at,
+            pinned: item.pinned,
+            focused: item.focused,
+            planned: item.planned,
+            hidden_until: item.hidden_until,
+            depends_on: &item.depends_on,
+        })
+        .collect();
+
+    serde_json::to_string_pretty(&items).map_err(TodoError::Serialize)
+}
@@ -0,0 +1,93 @@
+//! Markdown checklists, i.e., for pasting into GitHub issues and READMEs.
+//!
+//! Each item is a task list item, `- [ ] label` or `- [x] label` when completed, and its note
+//! follows as indented lines. The todo list has no subtasks, so nested task list items are
+//! imported as items of their own, in the order they appear. Other lines, like headings, are
+//! skipped.
+
+use crate::todo::Todo;
+
+/// The markers a Markdown list item can start with.
+const MARKERS: [&str; 3] = ["- ", "* ", "+ "];
+
+/// Convert the items of a todo list into a Markdown checklist.
+pub fn export(items: &[Todo]) -> String {
+    let mut buf = String::new();
+    for item in items {
+        let checkbox = if item.complete { "[x]" } else { "[ ]" };
+        buf.push_str(&format!("- {checkbox} {}\n", item.label));
+        if let Some(note) = &item.note {
+            for line in note.lines() {
+                if !line.is_empty() {
+                    buf.push_str("  ");
+                }
+                buf.push_str(line);
+                buf.push('\n');
+            }
+        }
+    }
+    buf
+}
+
+/// Parse the task list items of a Markdown file into items. Indented lines that follow an item,
+/// and aren't list items themselves, become its note.
+pub fn import(text: &str) -> Vec<Todo> {
+    let mut items: Vec<Todo> = Vec::new();
+    // The note lines of the last item, which only continue while lines are indented
+    let mut note: Option<Vec<&str>> = None;
+
+    for line in text.lines() {
+        if let Some((complete, label)) = parse_task(line) {
+            finish_note(&mut items, note.take());
+            let mut todo = Todo::new(0, label);
+            todo.set_complete(complete);
+            items.push(todo);
+            note = Some(Vec::new());
+        } else if let Some(lines) = &mut note {
+            let indented = line.starts_with(' ') || line.starts_with('\t');
+            if line.trim().is_empty() {
+                lines.push("");
+            } else if indented && !is_list_item(line) {
+                lines.push(line.trim());
+            } else {
+                finish_note(&mut items, note.take());
+            }
+        }
+    }
+    finish_note(&mut items, note);
+
+    items
+}
+
+/// Parse a task list item such as "- [x] label", at any indentation, into whether it is checked
+/// and its label.
+fn parse_task(line: &str) -> Option<(bool, &str)> {
+    let line = line.trim_start();
+    let rest = MARKERS
+        .iter()
+        .find_map(|marker| line.strip_prefix(marker))?;
+    let complete = match rest.get(..3)? {
+        "[ ]" => false,
+        "[x]" | "[X]" => true,
+        _ => return None,
+    };
+    let label = rest[3..].trim();
+    (!label.is_empty()).then_some((complete, label))
+}
+
+fn is_list_item(line: &str) -> bool {
+    let line = line.trim_start();
+    MARKERS.iter().any(|marker| line.starts_with(marker))
+}
+
+/// Give the last item the note lines that followed it, without the blank lines around them.
+fn finish_note(items: &mut [Todo], lines: Option<Vec<&str>>) {
+    let (Some(item), Some(lines)) = (items.last_mut(), lines) else {
+        return;
+    };
+    let note = lines.join("\n");
+    let note = note.trim_matches('\n');
+    if !note.is_empty() {
+        item.note = Some(note.to_string());
+    }
+}
@@ -0,0 +1,197 @@
+//! JSON exports of Microsoft To Do, for moving over from it.
+//!
+//! The tasks are read as the Microsoft Graph API returns them
+//! (<https://learn.microsoft.com/graph/api/resources/todotask>), which is also what the export
+//! tools for To Do write. The file can hold an array of lists, each with its `displayName` and
+//! its `tasks`, or an array of tasks, and either can be wrapped in a `{"value": [...]}` page.
+//!
+//! - `title`: The label.
+//! - `status`: "completed", "inProgress", and "waitingOnOthers" become done, in progress, and
+//!   waiting.
+//! - `importance`: "high" and "low" become high and low priority. "normal" has no priority.
+//! - `dueDateTime`: The due date. Times in UTC are moved to the local timezone first (see the
+//!   "timezone" setting), and times in another timezone are taken to be local already.
+//! - `body`: The note. HTML tags are removed.
+//! - `categories`: The tags.
+//! - `createdDateTime` and `completedDateTime`: When the item was added and completed.
+
+use crate::dates;
+use crate::error::TodoError;
+use crate::todo::{Priority, Status, Todo};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use serde::Deserialize;
+use serde_json::Value;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Task {
+    title: String,
+    #[serde(default)]
+    status: String,
+    #[serde(default)]
+    importance: String,
+    due_date_time: Option<GraphDateTime>,
+    body: Option<Body>,
+    #[serde(default)]
+    categories: Vec<String>,
+    created_date_time: Option<DateTime<Utc>>,
+    completed_date_time: Option<GraphDateTime>,
+}
+
+/// A time without an offset, which is in UTC unless the app was asked for another time zone.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphDateTime {
+    date_time: String,
+    time_zone: Option<String>,
+}
+
+impl GraphDateTime {
+    /// The time, which Microsoft To Do gives with seven digits of the seconds.
+    fn time(&self) -> Option<NaiveDateTime> {
+        NaiveDateTime::parse_from_str(&self.date_time, "%Y-%m-%dT%H:%M:%S%.f").ok()
+    }
+
+    /// The date in the local timezone. A time in UTC may be on another day there, i.e., a task
+    /// due at midnight in Berlin is due at 23:00 the day before in UTC.
+    fn local_date(&self) -> Option<NaiveDate> {
+        let time = self.time()?;
+        match self.time_zone.as_deref() {
+            None | Some("UTC" | "Etc/UTC") => Some(dates::local(time.and_utc()).date_naive()),
+            Some(_) => Some(time.date()),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Body {
+    #[serde(default)]
+    content: String,
+    #[serde(default)]
+    content_type: String,
+}
+
+/// Parse an exported file into items, each with the name of the To Do list it was in.
+/// A task that isn't valid fails the whole import.
+pub fn import(text: &str) -> Result<Vec<(Option<String>, Todo)>, TodoError> {
+    let value: Value = serde_json::from_str(text.trim_start_matches('\u{feff}'))
+        .map_err(TodoError::InvalidJson)?;
+
+    let mut items: Vec<(Option<String>, Todo)> = Vec::new();
+    for mut entry in entries(value) {
+        match entry.get_mut("tasks").map(Value::take) {
+            Some(tasks) => {
+                let name = entry
+                    .get("displayName")
+                    .and_then(Value::as_str)
+                    .map(str::to_string);
+                for task in entries(tasks) {
+                    items.push((name.clone(), todo(task)?));
+                }
+            }
+            None => items.push((None, todo(entry)?)),
+        }
+    }
+
+    Ok(items)
+}
+
+/// The elements of an array, unwrapping a `{"value": [...]}` page first. Anything else is a
+/// single element.
+fn entries(value: Value) -> Vec<Value> {
+    let value = match value {
+        Value::Object(mut page) if page.contains_key("value") => page["value"].take(),
+        value => value,
+    };
+    match value {
+        Value::Array(entries) => entries,
+        entry => vec![entry],
+    }
+}
+
+fn todo(task: Value) -> Result<Todo, TodoError> {
+    let task: Task = serde_json::from_value(task).map_err(TodoError::InvalidJson)?;
+
+    let mut todo = Todo::new(0, task.title.trim());
+    todo.priority = match task.importance.as_str() {
+        "high" => Some(Priority::High),
+        "low" => Some(Priority::Low),
+        _ => None,
+    };
+    todo.due = task.due_date_time.and_then(|due| due.local_date());
+    if let Some(body) = task.body {
+        let note = if body.content_type.eq_ignore_ascii_case("html") {
+            strip_tags(&body.content)
+        } else {
+            body.content
+        };
+        todo.note = Some(note.trim().to_string()).filter(|note| !note.is_empty());
+    }
+    for category in &task.categories {
+        todo.add_tag(&category.replace(char::is_whitespace, "-"));
+    }
+    if let Some(created_at) = task.created_date_time {
+        todo.created_at = created_at;
+    }
+    if task.status == "completed" {
+        todo.set_complete(true);
+        if let Some(completed_at) = task.completed_date_time.and_then(|time| time.time()) {
+            todo.completed_at = Some(completed_at.and_utc());
+        }
+    }
+    match task.status.as_str() {
+        "inProgress" => todo.set_state(Status::InProgress),
+        "waitingOnOthers" => todo.set_state(Status::Waiting),
+        _ => {}
+    }
+
+    Ok(todo)
+}
+
+/// The text of an HTML body, with line breaks kept and the tags removed.
+fn strip_tags(html: &str) -> String {
+    let html = html.replace("<br>", "\n").replace("</p>", "\n");
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn due(date_time: &str, time_zone: &str) -> Option<NaiveDate> {
+        let task = format!(
+            r#"{{"title":"a","dueDateTime":{{"dateTime":"{date_time}","timeZone":"{time_zone}"}}}}"#
+        );
+        import(&task).unwrap()[0].1.due
+    }
+
+    #[test]
+    fn due_dates_in_utc_are_moved_to_the_local_timezone() {
+        let time = NaiveDate::from_ymd_opt(2026, 3, 1)
+            .unwrap()
+            .and_hms_opt(23, 0, 0)
+            .unwrap();
+        assert_eq!(
+            due("2026-03-01T23:00:00.0000000", "UTC"),
+            Some(dates::local(time.and_utc()).date_naive())
+        );
+        assert_eq!(
+            due("2026-03-01T23:00:00.0000000", "W. Europe Standard Time"),
+            NaiveDate::from_ymd_opt(2026, 3, 1)
+        );
+    }
+}
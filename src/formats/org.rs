@@ -0,0 +1,80 @@
+//! Emacs Org-mode outlines (<https://orgmode.org/manual/>), for pulling the todo list into the
+//! agenda.
+//!
+//! Each item is a `* TODO` or `* DONE` heading, i.e., `* TODO [#A] call mom :family:`. High,
+//! medium, and low priorities map to the cookies `[#A]`, `[#B]`, and `[#C]`, the due date becomes
+//! a DEADLINE, the start or snooze date (whichever is later) becomes SCHEDULED, and the completion
+//! time becomes CLOSED. The note follows as indented body text.
+
+use crate::dates;
+use crate::todo::{Priority, Todo};
+use chrono::NaiveDate;
+
+/// Convert the items of a todo list into top-level Org headings.
+pub fn export(items: &[Todo]) -> String {
+    let mut buf = String::new();
+    for item in items {
+        let keyword = if item.complete { "DONE" } else { "TODO" };
+        let mut heading = format!("* {keyword} ");
+        if let Some(priority) = item.priority {
+            let letter = match priority {
+                Priority::High => 'A',
+                Priority::Medium => 'B',
+                Priority::Low => 'C',
+            };
+            heading.push_str(&format!("[#{letter}] "));
+        }
+        heading.push_str(&item.label);
+        if !item.tags.is_empty() {
+            let tags: Vec<String> = item.tags.iter().map(|tag| tag_name(tag)).collect();
+            heading.push_str(&format!(" :{}:", tags.join(":")));
+        }
+        buf.push_str(&heading);
+        buf.push('\n');
+
+        let mut planning: Vec<String> = Vec::new();
+        if let Some(completed_at) = item.completed_at.filter(|_| item.complete) {
+            let closed = dates::local(completed_at).format("%Y-%m-%d %a %H:%M");
+            planning.push(format!("CLOSED: [{closed}]"));
+        }
+        if let Some(due) = item.due {
+            planning.push(format!("DEADLINE: {}", timestamp(due)));
+        }
+        if let Some(date) = item.hidden_until.max(item.start) {
+            planning.push(format!("SCHEDULED: {}", timestamp(date)));
+        }
+        if !planning.is_empty() {
+            buf.push_str(&format!("  {}\n", planning.join(" ")));
+        }
+
+        // Indenting the note keeps lines starting with "*" from becoming headings
+        if let Some(note) = &item.note {
+            for line in note.lines() {
+                if !line.is_empty() {
+                    buf.push_str("  ");
+                }
+                buf.push_str(line);
+                buf.push('\n');
+            }
+        }
+    }
+    buf
+}
+
+/// An active Org timestamp, i.e., `<2025-03-01 Sat>`, which shows up in the agenda.
+fn timestamp(date: NaiveDate) -> String {
+    format!("<{}>", date.format("%Y-%m-%d %a"))
+}
+
+/// Org tags can only hold letters, digits, "_", "@", "#", and "%", so other characters become "_".
+fn tag_name(tag: &str) -> String {
+    tag.chars()
+        .map(|c| {
+            if c.is_alphanumeric() || "_@#%".contains(c) {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,124 @@
+//! CSV exports of Apple Reminders, for moving over from the Reminders app.
+//!
+//! Reminders has no export of its own, so the file is the one written by the common export
+//! shortcuts and apps: a header row, then a row for every reminder. The columns are matched by
+//! name, in any order and ignoring case, and only `Title` is required:
+//!
+//! - `Title`: The label.
+//! - `Notes`: The note.
+//! - `Due Date`: The due date as YYYY-MM-DD or MM/DD/YYYY, optionally followed by a time, which
+//!   is dropped.
+//! - `Priority`: "High", "Medium", "Low", or "None", or Apple's numbers (1 to 4 is high, 5 is
+//!   medium, 6 to 9 is low, and 0 is none).
+//! - `Completed`: "Yes", "true", or "1" for completed reminders.
+//! - `List`: The name of the Reminders list.
+//! - `Tags`: The tags, separated by spaces or commas, with or without "#".
+
+use crate::error::TodoError;
+use crate::formats::csv::parse_rows;
+use crate::todo::{Priority, Todo};
+use chrono::NaiveDate;
+
+/// Parse the rows of an exported file into items, each with the name of the Reminders list it
+/// was in. Blank rows are skipped, and a row with an invalid field fails the whole import.
+pub fn import(text: &str) -> Result<Vec<(Option<String>, Todo)>, TodoError> {
+    let mut rows = parse_rows(text.trim_start_matches('\u{feff}'))
+        .into_iter()
+        .enumerate();
+    let Some((_, header)) = rows.next() else {
+        return Ok(Vec::new());
+    };
+    let invalid = |row: usize, reason: String| TodoError::InvalidCsv {
+        row: row + 1,
+        reason,
+    };
+    let column = |name: &str| {
+        header
+            .iter()
+            .position(|column| column.trim().eq_ignore_ascii_case(name))
+    };
+    let Some(title_column) = column("title") else {
+        return Err(invalid(0, "there is no \"Title\" column".to_string()));
+    };
+    let (notes_column, due_column) = (column("notes"), column("due date"));
+    let (priority_column, completed_column) = (column("priority"), column("completed"));
+    let (list_column, tags_column) = (column("list"), column("tags"));
+
+    let mut items: Vec<(Option<String>, Todo)> = Vec::new();
+    for (i, row) in rows {
+        if row.iter().all(|field| field.trim().is_empty()) {
+            continue;
+        }
+        let field = |column: Option<usize>| {
+            column
+                .and_then(|column| row.get(column))
+                .map_or("", |field| field.trim())
+        };
+
+        let title = field(Some(title_column));
+        if title.is_empty() {
+            return Err(invalid(i, "the title is empty".to_string()));
+        }
+        let mut todo = Todo::new(0, title);
+
+        let notes = field(notes_column);
+        if !notes.is_empty() {
+            todo.note = Some(notes.to_string());
+        }
+        let due = field(due_column);
+        if !due.is_empty() {
+            todo.due = Some(parse_due(due).ok_or_else(|| {
+                invalid(
+                    i,
+                    format!("invalid due date \"{due}\" (expected YYYY-MM-DD or MM/DD/YYYY)"),
+                )
+            })?);
+        }
+        todo.priority =
+            parse_priority(field(priority_column)).map_err(|reason| invalid(i, reason))?;
+        match field(completed_column).to_lowercase().as_str() {
+            "yes" | "true" | "1" => todo.set_complete(true),
+            "no" | "false" | "0" | "" => {}
+            completed => {
+                return Err(invalid(
+                    i,
+                    format!("invalid completed value \"{completed}\" (expected yes or no)"),
+                ));
+            }
+        }
+        for tag in field(tags_column).split([' ', ',']) {
+            todo.add_tag(tag.trim_start_matches('#'));
+        }
+
+        let list = Some(field(list_column))
+            .filter(|list| !list.is_empty())
+            .map(str::to_string);
+        items.push((list, todo));
+    }
+
+    Ok(items)
+}
+
+/// The date at the start of a due date, i.e., "2025-03-01" of "2025-03-01 09:00".
+fn parse_due(due: &str) -> Option<NaiveDate> {
+    let date = due.split([' ', 'T', ',']).next()?;
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(date, "%m/%d/%Y"))
+        .ok()
+}
+
+fn parse_priority(priority: &str) -> Result<Option<Priority>, String> {
+    if let Ok(number) = priority.parse::<u8>() {
+        return match number {
+            0 => Ok(None),
+            1..=4 => Ok(Some(Priority::High)),
+            5 => Ok(Some(Priority::Medium)),
+            6..=9 => Ok(Some(Priority::Low)),
+            _ => Err(format!("invalid priority \"{priority}\" (expected 0 to 9)")),
+        };
+    }
+    match priority.to_lowercase().as_str() {
+        "" | "none" => Ok(None),
+        priority => priority.parse().map(Some),
+    }
+}
@@ -0,0 +1,132 @@
+//! The todo.txt format (<https://github.com/todotxt/todo.txt>).
+//!
+//! Each line is a single item, i.e., `x (A) 2025-01-01 call mom +family @phone due:2025-01-05`.
+//! Priorities A, B, and C map to high, medium, and low (D-Z are imported as low), projects map to
+//! tags, and `due:` maps to the due date. Contexts are kept as part of the label. Creation and
+//! completion dates are kept, and imported as midnight UTC on that day.
+
+use crate::todo::{Priority, Todo};
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+
+/// Convert the items of a todo list into todo.txt lines.
+pub fn export(items: &[Todo]) -> String {
+    let mut buf = String::new();
+    for item in items {
+        buf.push_str(&to_line(item));
+        buf.push('\n');
+    }
+    buf
+}
+
+/// Parse todo.txt lines into items. Blank lines are skipped.
+pub fn import(text: &str) -> Vec<Todo> {
+    text.lines().filter_map(parse_line).collect()
+}
+
+/// Convert a single item into a todo.txt line.
+pub fn to_line(item: &Todo) -> String {
+    let mut parts: Vec<String> = Vec::new();
+
+    if item.complete {
+        parts.push("x".to_string());
+        // A completed item needs a completion date before its creation date
+        if let Some(completed_at) = item.completed_at {
+            parts.push(completed_at.date_naive().to_string());
+            parts.push(item.created_at.date_naive().to_string());
+        }
+    } else {
+        if let Some(priority) = item.priority {
+            parts.push(format!("({})", priority_letter(priority)));
+        }
+        parts.push(item.created_at.date_naive().to_string());
+    }
+
+    parts.push(item.label.clone());
+    parts.extend(item.tags.iter().map(|tag| format!("+{tag}")));
+
+    if let Some(due) = item.due {
+        parts.push(format!("due:{due}"));
+    }
+    // Completed items can't have a leading priority, so it's kept as an extension instead
+    if item.complete
+        && let Some(priority) = item.priority
+    {
+        parts.push(format!("pri:{}", priority_letter(priority)));
+    }
+
+    parts.join(" ")
+}
+
+/// Parse a single todo.txt line into an item, or None if the line is blank.
+pub fn parse_line(line: &str) -> Option<Todo> {
+    let mut tokens = line.split_whitespace().peekable();
+    tokens.peek()?;
+
+    let mut todo = Todo::new(0, "");
+
+    if tokens.next_if_eq(&"x").is_some() {
+        todo.set_complete(true);
+        if let Some(completed) = tokens.next_if(|token| parse_date(token).is_some()) {
+            todo.completed_at = parse_date(completed).map(midnight);
+        }
+        if let Some(created) = tokens.next_if(|token| parse_date(token).is_some()) {
+            todo.created_at = parse_date(created).map(midnight).unwrap();
+        }
+    } else {
+        if let Some(token) = tokens.next_if(|token| parse_priority(token).is_some()) {
+            todo.priority = parse_priority(token);
+        }
+        if let Some(created) = tokens.next_if(|token| parse_date(token).is_some()) {
+            todo.created_at = parse_date(created).map(midnight).unwrap();
+        }
+    }
+
+    let mut words: Vec<&str> = Vec::new();
+    for token in tokens {
+        if let Some(tag) = token.strip_prefix('+')
+            && !tag.is_empty()
+        {
+            todo.add_tag(tag);
+        } else if let Some(due) = token.strip_prefix("due:").and_then(parse_date) {
+            todo.due = Some(due);
+        } else if let Some(letter) = token.strip_prefix("pri:")
+            && let Some(priority) = parse_priority(&format!("({letter})"))
+        {
+            todo.priority = Some(priority);
+        } else {
+            words.push(token);
+        }
+    }
+
+    todo.label = words.join(" ");
+    Some(todo)
+}
+
+fn priority_letter(priority: Priority) -> char {
+    match priority {
+        Priority::High => 'A',
+        Priority::Medium => 'B',
+        Priority::Low => 'C',
+    }
+}
+
+/// Parse a priority such as "(A)".
+fn parse_priority(token: &str) -> Option<Priority> {
+    let letter = token.strip_prefix('(')?.strip_suffix(')')?;
+    match letter {
+        "A" => Some(Priority::High),
+        "B" => Some(Priority::Medium),
+        _ if letter.len() == 1 && letter.chars().all(|c| c.is_ascii_uppercase()) => {
+            Some(Priority::Low)
+        }
+        _ => None,
+    }
+}
+
+fn parse_date(token: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(token, "%Y-%m-%d").ok()
+}
+
+fn midnight(date: NaiveDate) -> DateTime<Utc> {
+    date.and_time(NaiveTime::MIN).and_utc()
+}
@@ -0,0 +1,151 @@
+//! Keeping the data directory in a git repository, to sync the todo lists between machines.
+//!
+//! When the "git-commit" setting is on, the changes of every command are committed, and
+//! `todo sync` pulls from and pushes to the "git-remote" setting. Conflicting edits of a data file
+//! are merged by keeping the lines of both sides, after which an item that was edited on both
+//! machines keeps the remote's version. Encrypted files can't be merged line by line, so the
+//! remote's version of the whole file wins instead.
+
+use crate::error::TodoError;
+use crate::list::TodoList;
+use crate::store::{TodoStore, check_writable};
+use crate::todo::Todo;
+use log::{debug, info};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+const REMOTE: &str = "origin";
+
+/// The temporary files and backups next to the data files, which aren't committed.
+const IGNORED: &str = "*.lock\n*.tmp\n*.undo\n*.sock\n*.bak.*\n";
+
+/// Commit every change in the data directory, making it a git repository if it isn't one yet.
+/// Nothing is committed if nothing changed.
+pub fn commit(dir: &Path, message: &str) -> Result<(), TodoError> {
+    check_writable()?;
+    if !dir.join(".git").exists() {
+        git(dir, &["init", "--quiet"])?;
+        fs::write(dir.join(".gitignore"), IGNORED)
+            .map_err(|err| TodoError::io("Could not write the .gitignore file", err))?;
+    }
+
+    git(dir, &["add", "--all"])?;
+    if git(dir, &["status", "--porcelain"])?.is_empty() {
+        return Ok(());
+    }
+    git(dir, &["commit", "--quiet", "--message", message])?;
+    info!("Committed the changes in {}", dir.display());
+    Ok(())
+}
+
+/// Commit the changes in the data directory, merge the changes from the remote repository, and
+/// push the result to it.
+pub fn sync(dir: &Path, remote: &str, encrypted: bool) -> Result<(), TodoError> {
+    commit(dir, "todo sync")?;
+
+    // The merge strategy isn't committed, since it depends on whether this machine encrypts
+    let info = dir.join(".git").join("info");
+    let attributes = if encrypted {
+        ""
+    } else {
+        "*.dat merge=union\n*.jsonl merge=union\n"
+    };
+    fs::create_dir_all(&info)
+        .and_then(|_| fs::write(info.join("attributes"), attributes))
+        .map_err(|err| TodoError::io("Could not write the git attributes", err))?;
+
+    if succeeds(dir, &["remote", "get-url", REMOTE]) {
+        git(dir, &["remote", "set-url", REMOTE, remote])?;
+    } else {
+        git(dir, &["remote", "add", REMOTE, remote])?;
+    }
+    git(dir, &["fetch", "--quiet", REMOTE])?;
+
+    let branch = git(dir, &["symbolic-ref", "--short", "HEAD"])?;
+    let upstream = format!("{REMOTE}/{branch}");
+    if succeeds(dir, &["rev-parse", "--verify", "--quiet", &upstream]) {
+        let mut args = vec![
+            "merge",
+            "--quiet",
+            "--no-edit",
+            "--allow-unrelated-histories",
+        ];
+        if encrypted {
+            args.extend(["--strategy-option", "theirs"]);
+        }
+        args.push(&upstream);
+        if let Err(err) = git(dir, &args) {
+            let _ = git(dir, &["merge", "--abort"]);
+            return Err(err);
+        }
+
+        for name in TodoStore::list_names()? {
+            let store = TodoStore::open_list(&name)?.encrypted(encrypted);
+            if store.path().exists() {
+                remove_duplicates(&store)?;
+            }
+        }
+        commit(dir, "todo sync: merge")?;
+    }
+
+    git(dir, &["push", "--quiet", REMOTE, &branch])?;
+    Ok(())
+}
+
+/// Clean up a todo list after its lines were merged. An item that was edited on both sides is in
+/// it twice, so only the last (the remote's) version is kept. Different items that were given the
+/// same ID on both sides are all kept, with new IDs for the later ones.
+fn remove_duplicates(store: &TodoStore) -> Result<(), TodoError> {
+    let mut items: Vec<Todo> = Vec::new();
+    let mut changed = false;
+    let merged = store.load_file()?;
+    for item in merged.items() {
+        let same = items
+            .iter_mut()
+            .find(|other| other.id == item.id && other.created_at == item.created_at);
+        match same {
+            Some(other) => {
+                *other = item.clone();
+                changed = true;
+            }
+            None => items.push(item.clone()),
+        }
+    }
+
+    let mut list = TodoList::new().with_next_id(merged.next_id());
+    for item in items {
+        let id = item.id;
+        changed |= list.restore(item) != id;
+    }
+    if changed {
+        store.save_file(&list)?;
+    }
+    Ok(())
+}
+
+/// Run a git command in the data directory, returning its output.
+fn git(dir: &Path, args: &[&str]) -> Result<String, TodoError> {
+    debug!("Running `git {}` in {}", args.join(" "), dir.display());
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|err| TodoError::io("Could not run git", err))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(TodoError::Sync(format!(
+            "`git {}` failed: {}",
+            args.join(" "),
+            stderr.trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Whether a git command succeeds, for commands that only check something.
+fn succeeds(dir: &Path, args: &[&str]) -> bool {
+    git(dir, args).is_ok()
+}
@@ -0,0 +1,163 @@
+use crate::error::TodoError;
+use crate::store::{check_writable, read_data, write_data};
+use crate::todo::Todo;
+use chrono::{DateTime, Utc};
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// A command that changed the todo list, and what it changed.
+#[derive(Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// When the command was run.
+    pub time: DateTime<Utc>,
+    /// The command line, i.e., "todo check 2".
+    pub command: String,
+    /// The items that were added, removed, or changed.
+    pub changes: Vec<ItemChange>,
+    /// Whether the order of the items changed.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub reordered: bool,
+}
+
+/// An item before and after a command. An added item has no `before`, and a removed item has no
+/// `after`.
+#[derive(Serialize, Deserialize)]
+pub struct ItemChange {
+    pub before: Option<Todo>,
+    pub after: Option<Todo>,
+}
+
+/// The append-only log of the commands that changed a todo list, kept next to the data file.
+/// Each line is a single entry in JSON format.
+pub struct History {
+    path: PathBuf,
+    encrypt: bool,
+}
+
+impl HistoryEntry {
+    /// Record what a command changed, given the items before and after it ran.
+    /// Returns None if nothing changed.
+    pub fn new(command: String, before: &[Todo], after: &[Todo]) -> Option<HistoryEntry> {
+        let mut changes: Vec<ItemChange> = Vec::new();
+        for old in before {
+            match after.iter().find(|new| new.id == old.id) {
+                Some(new) if new == old => {}
+                new => changes.push(ItemChange {
+                    before: Some(old.clone()),
+                    after: new.cloned(),
+                }),
+            }
+        }
+        for new in after
+            .iter()
+            .filter(|new| !before.iter().any(|old| old.id == new.id))
+        {
+            changes.push(ItemChange {
+                before: None,
+                after: Some(new.clone()),
+            });
+        }
+
+        // Only the order of the items that are in both lists counts
+        let kept: Vec<u64> = after
+            .iter()
+            .map(|item| item.id)
+            .filter(|id| before.iter().any(|old| old.id == *id))
+            .collect();
+        let reordered = !before
+            .iter()
+            .map(|item| item.id)
+            .filter(|id| kept.contains(id))
+            .eq(kept.iter().copied());
+
+        if changes.is_empty() && !reordered {
+            return None;
+        }
+        Some(HistoryEntry {
+            time: Utc::now(),
+            command,
+            changes,
+            reordered,
+        })
+    }
+}
+
+impl History {
+    /// Use the history file at the given path.
+    pub fn new(path: impl Into<PathBuf>) -> History {
+        History {
+            path: path.into(),
+            encrypt: false,
+        }
+    }
+
+    /// Encrypt the history file when writing it.
+    pub fn encrypted(mut self, encrypt: bool) -> History {
+        self.encrypt = encrypt;
+        self
+    }
+
+    /// The path of the history file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Read the entries, oldest first. A missing history file has no entries.
+    pub fn load(&self) -> Result<Vec<HistoryEntry>, TodoError> {
+        let mut entries: Vec<HistoryEntry> = Vec::new();
+
+        if let Some(str) = read_data(&self.path)? {
+            for line in str.lines() {
+                let entry = serde_json::from_str(line).map_err(|source| TodoError::ParseLine {
+                    line: line.to_string(),
+                    source,
+                })?;
+                entries.push(entry);
+            }
+        }
+
+        Ok(entries)
+    }
+
+    /// Write the entries to the history file, i.e., to encrypt or decrypt it.
+    pub fn save(&self, entries: &[HistoryEntry]) -> Result<(), TodoError> {
+        let mut buf = String::new();
+        for entry in entries {
+            let entry_serialized = serde_json::to_string(entry).map_err(TodoError::Serialize)?;
+            buf.push_str(&entry_serialized);
+            buf.push('\n');
+        }
+
+        write_data(
+            &self.path,
+            &buf,
+            self.encrypt,
+            "Could not write to the history file",
+        )
+    }
+
+    /// Add an entry to the end of the history file. An encrypted history file has to be written
+    /// again as a whole.
+    pub fn append(&self, entry: HistoryEntry) -> Result<(), TodoError> {
+        if self.encrypt {
+            let mut entries = self.load()?;
+            entries.push(entry);
+            return self.save(&entries);
+        }
+
+        let mut line = serde_json::to_string(&entry).map_err(TodoError::Serialize)?;
+        line.push('\n');
+        check_writable()?;
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .map_err(|err| TodoError::io("Could not write to the history file", err))?;
+        info!("Added the command to {}", self.path.display());
+        Ok(())
+    }
+}
@@ -0,0 +1,90 @@
+//! Running the user's scripts when items change, set with the "on-add", "on-check", "on-uncheck",
+//! "on-edit", "on-remove", and "on-write" settings.
+//!
+//! A hook is run with `sh -c` (`cmd /C` on Windows) once for every affected item, which it gets on
+//! stdin in JSON format, as it is stored in the data file. The "on-write" hook is run once after
+//! every change, and gets a JSON array of all the affected items. The event is in the TODO_HOOK
+//! environment variable. Commands run by a hook don't run hooks themselves, so a hook can use
+//! `todo` without running itself again.
+
+use crate::error::TodoError;
+use crate::history::HistoryEntry;
+use crate::settings::HookSettings;
+use crate::todo::Todo;
+use log::{debug, info};
+use std::env;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+const HOOK_VAR: &str = "TODO_HOOK";
+
+/// Run the hooks for the changes a command made, given the items before and after it ran. A hook
+/// that fails only prints a warning, since the changes are already saved.
+pub fn run(hooks: &HookSettings, command: String, before: &[Todo], after: &[Todo]) {
+    if env::var_os(HOOK_VAR).is_some() {
+        return;
+    }
+    let Some(entry) = HistoryEntry::new(command, before, after) else {
+        return;
+    };
+
+    let mut affected: Vec<&Todo> = Vec::new();
+    for change in &entry.changes {
+        let (event, item) = match (&change.before, &change.after) {
+            (None, Some(item)) => ("on-add", item),
+            (Some(item), None) => ("on-remove", item),
+            (Some(old), Some(new)) if !old.complete && new.complete => ("on-check", new),
+            (Some(old), Some(new)) if old.complete && !new.complete => ("on-uncheck", new),
+            (Some(_), Some(new)) => ("on-edit", new),
+            (None, None) => continue,
+        };
+        affected.push(item);
+
+        if let Some(script) = hooks.get(event)
+            && let Err(err) = serde_json::to_string(item)
+                .map_err(TodoError::Serialize)
+                .and_then(|json| run_script(event, script, &json))
+        {
+            eprintln!("WARNING: The {event} hook failed: {err}");
+        }
+    }
+
+    if let Some(script) = hooks.get("on-write")
+        && let Err(err) = serde_json::to_string(&affected)
+            .map_err(TodoError::Serialize)
+            .and_then(|json| run_script("on-write", script, &json))
+    {
+        eprintln!("WARNING: The on-write hook failed: {err}");
+    }
+}
+
+/// Run a hook's script in the shell, writing the JSON to its stdin.
+fn run_script(event: &str, script: &str, json: &str) -> Result<(), TodoError> {
+    info!("Running the {event} hook: {script}");
+    debug!("The {event} hook gets {json}");
+    let (shell, flag) = if cfg!(windows) {
+        ("cmd", "/C")
+    } else {
+        ("sh", "-c")
+    };
+    let mut child = Command::new(shell)
+        .args([flag, script])
+        .env(HOOK_VAR, event)
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|err| TodoError::io(format!("Could not run \"{script}\""), err))?;
+
+    // A script that doesn't read its input closes stdin early, which isn't an error
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(json.as_bytes());
+    }
+    let status = child
+        .wait()
+        .map_err(|err| TodoError::io(format!("Could not run \"{script}\""), err))?;
+    if !status.success() {
+        return Err(TodoError::Usage(format!(
+            "\"{script}\" exited with {status}."
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,113 @@
+use crate::error::TodoError;
+use crate::store::{check_writable, read_data, write_data};
+use crate::todo::Todo;
+use chrono::{DateTime, SecondsFormat, Utc};
+use log::info;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// An item that was checked, as recorded in the done log.
+pub struct DoneEntry {
+    /// When the item was checked.
+    pub at: DateTime<Utc>,
+    pub label: String,
+}
+
+/// The append-only log of the items checked in a todo list, kept next to the data file, so what
+/// was done can be looked back on after the items are removed.
+/// Each line is a UTC timestamp and the label, i.e., "2025-03-01T09:30:00Z call mom".
+pub struct DoneLog {
+    path: PathBuf,
+    encrypt: bool,
+}
+
+impl DoneEntry {
+    /// Record the checking of an item, at the time it was completed.
+    pub fn new(item: &Todo) -> DoneEntry {
+        DoneEntry {
+            at: item.completed_at.unwrap_or_else(Utc::now),
+            label: item.label.clone(),
+        }
+    }
+
+    /// The entry as a line of the log. Line breaks in the label become spaces.
+    fn to_line(&self) -> String {
+        format!(
+            "{} {}\n",
+            self.at.to_rfc3339_opts(SecondsFormat::Secs, true),
+            self.label.replace(['\r', '\n'], " ")
+        )
+    }
+
+    fn parse(line: &str) -> Option<DoneEntry> {
+        let (at, label) = line.split_once(' ')?;
+        Some(DoneEntry {
+            at: DateTime::parse_from_rfc3339(at).ok()?.to_utc(),
+            label: label.to_string(),
+        })
+    }
+}
+
+impl DoneLog {
+    /// Use the done log at the given path.
+    pub fn new(path: impl Into<PathBuf>) -> DoneLog {
+        DoneLog {
+            path: path.into(),
+            encrypt: false,
+        }
+    }
+
+    /// Encrypt the done log when writing it.
+    pub fn encrypted(mut self, encrypt: bool) -> DoneLog {
+        self.encrypt = encrypt;
+        self
+    }
+
+    /// The path of the done log.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Read the entries, oldest first. A missing done log has no entries, and lines that can't be
+    /// read (i.e., after editing the log by hand) are skipped.
+    pub fn load(&self) -> Result<Vec<DoneEntry>, TodoError> {
+        let contents = read_data(&self.path)?.unwrap_or_default();
+        Ok(contents.lines().filter_map(DoneEntry::parse).collect())
+    }
+
+    /// Write the entries to the done log, i.e., to encrypt or decrypt it.
+    pub fn save(&self, entries: &[DoneEntry]) -> Result<(), TodoError> {
+        let buf: String = entries.iter().map(DoneEntry::to_line).collect();
+        write_data(
+            &self.path,
+            &buf,
+            self.encrypt,
+            "Could not write to the done log",
+        )
+    }
+
+    /// Add entries to the end of the done log. An encrypted done log has to be written again as
+    /// a whole.
+    pub fn append(&self, entries: Vec<DoneEntry>) -> Result<(), TodoError> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+        if self.encrypt {
+            let mut all = self.load()?;
+            all.extend(entries);
+            return self.save(&all);
+        }
+
+        let buf: String = entries.iter().map(DoneEntry::to_line).collect();
+        check_writable()?;
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(buf.as_bytes()))
+            .map_err(|err| TodoError::io("Could not write to the done log", err))?;
+        info!("Added {} item(s) to {}", entries.len(), self.path.display());
+        Ok(())
+    }
+}
@@ -1,454 +1,2302 @@
+mod archive;
+mod browser;
+mod caldav;
+mod cli;
+mod crypto;
+#[cfg(unix)]
+mod daemon;
+pub mod dates;
+mod display;
+mod editor;
+mod error;
+mod estimate;
+mod finder;
+pub mod formats;
+mod git;
+mod history;
+mod hooks;
+mod journal;
+mod list;
+pub mod logging;
+mod migrate;
+mod next;
+#[cfg(unix)]
+mod pager;
+mod pomodoro;
+mod recur;
+mod remind;
+mod settings;
+mod stats;
+mod store;
+mod template;
+mod todo;
+mod trash;
+mod watch;
+
+pub use archive::{Archive, ArchivedTodo};
+pub use cli::{Cli, ErrorFormat};
+pub use error::TodoError;
+pub use list::{Filter, ItemRef, Selection, SortMode, TodoList};
+pub use recur::Recurrence;
+pub use settings::Settings;
+pub use stats::{Counts, Stats};
+pub use store::TodoStore;
+pub use todo::{Comment, Priority, Todo, WorkInterval};
+pub use trash::{Trash, TrashedTodo};
+
+use chrono::{Days, Utc};
+use clap::error::{ContextKind, ContextValue, ErrorKind};
+use clap::{CommandFactory, Parser};
+use cli::{
+    ArchiveAction, BackupAction, Command, ExportFormat, ImportFormat, ListsAs, ReportKind,
+    TrashAction,
+};
 use colored::Colorize;
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::io::Write;
-use std::path::PathBuf;
-use std::{fs, io, process};
+use display::{
+    Checkbox, ListOptions, Theme, format_duration, list_items, print_agenda, print_archive,
+    print_backups, print_board, print_counts, print_done, print_dry_run, print_effort,
+    print_history, print_next, print_overdue, print_plan, print_projects, print_replacements,
+    print_stats, print_status, print_time_report, print_trash, search_items, show_item,
+};
+use estimate::Estimate;
+use finder::Choice;
+use history::HistoryEntry;
+use journal::DoneEntry;
+use log::{debug, info};
+use pomodoro::Pomodoro;
+use regex::{NoExpand, Regex};
+use settings::{ColorMode, EditMode, OutputFormat, SETTING_CHOICES};
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::{env, fs, io};
+use store::StoreLock;
+use template::Template;
 
-const DATA_FILE_NAME: &'static str = "todo.dat";
+/// Run the todo app.
+/// @param cli - The parsed command line arguments.
+pub fn run(cli: Cli) -> Result<(), TodoError> {
+    debug!("Parsed the command line: {:?}", cli.command);
+    // The setting itself can still be changed, so it can be turned off again
+    let setting_read_only = matches!(
+        &cli.command,
+        Some(Command::Set { setting, .. }) if setting.as_deref() == Some("read-only")
+    );
+    if cli.read_only
+        || !setting_read_only && Settings::read().is_ok_and(|settings| settings.storage.read_only)
+    {
+        store::set_read_only();
+    }
+    if cli.dry_run {
+        store::set_dry_run();
+    }
+    if let Some(Command::External(args)) = &cli.command {
+        let argv: Vec<OsString> = env::args_os().collect();
+        return run(expand_alias(&Settings::load()?, &argv, args)?);
+    }
+    if let Some(Command::Completions { shell }) = cli.command {
+        clap_complete::generate(shell, &mut Cli::command(), "todo", &mut io::stdout());
+        return Ok(());
+    }
+    if let Some(Command::Init) = cli.command {
+        return init_local();
+    }
+
+    let settings = Settings::load()?;
+    set_color(cli.no_color, settings.display.color);
+    let list_name = cli
+        .list
+        .clone()
+        .unwrap_or_else(|| settings.storage.list.clone());
+    let file = cli.file.or_else(|| {
+        env::var_os("TODO_DATA_FILE")
+            .filter(|file| !file.is_empty())
+            .map(PathBuf::from)
+    });
+    // An explicit --list wins over the project's list, which wins over the "list" setting
+    let local = match (&file, &cli.list) {
+        (None, None) => env::current_dir()
+            .ok()
+            .and_then(|dir| TodoStore::find_local(&dir)),
+        _ => None,
+    };
+    let store = match (file, local) {
+        (Some(file), _) => TodoStore::new(file),
+        (None, Some(local)) => local,
+        (None, None) => TodoStore::open_list(&list_name)?,
+    };
+    info!("Using the data file {}", store.path().display());
+    let store = store
+        .encrypted(settings.storage.encryption)
+        .backups(settings.storage.backups);
+    // The status is printed on every shell prompt, so it doesn't wait for the lock or clean up
+    // the trash, and leaves the data file as it is
+    if let Some(Command::Status {
+        item: None,
+        porcelain,
+        ..
+    }) = cli.command
+    {
+        let counts = Counts::collect(&store.peek()?, dates::today());
+        print_status(&counts, porcelain);
+        return Ok(());
+    }
+    let lock = store.lock()?;
+    let list = store.load()?;
+    let before = list.items().to_vec();
+    let quiet = cli.quiet || settings.behavior.silent;
+    let skip_confirm = cli.yes || !settings.behavior.confirm;
+    let strict = cli.strict || settings.behavior.strict;
+    let json = settings.display.format == OutputFormat::Json;
+    let table = settings.display.format == OutputFormat::Table;
+    let theme = settings.theme.theme()?;
+    let checkbox = settings.display.checkbox()?;
+    let template = match &settings.display.template {
+        Some(template) if settings.display.format == OutputFormat::Text => {
+            Some(Template::parse(template)?)
+        }
+        _ => None,
+    };
+    let trash = store.trash();
+    if !store::read_only() {
+        trash.purge(settings.storage.trash_days)?;
+    }
 
-#[derive(Serialize, Deserialize)]
-struct Todo {
-    label: String,
-    complete: bool,
+    // Make listing the todos the default action
+    let command = cli.command.unwrap_or(Command::List {
+        tags: Vec::new(),
+        filter: Filter::default(),
+        json: false,
+        table: false,
+        dates: false,
+        group_by: None,
+        watch: false,
+        limit: None,
+        no_pager: false,
+    });
+    let sort = settings
+        .display
+        .sort
+        .map(|mode| (mode, settings.display.sort_reverse));
+    let project = settings.behavior.project.clone();
+    let context = settings.behavior.context.clone();
+    let list_options = ListOptions {
+        filter: Filter {
+            project: project.clone(),
+            context: context.clone(),
+            ..Filter::default()
+        },
+        json,
+        table,
+        template: template.clone(),
+        theme,
+        checkbox: checkbox.clone(),
+        sort,
+        exact_dates: settings.display.exact_dates,
+        numbering: settings.display.numbering,
+        progress: settings.display.progress,
+        long_labels: settings.display.long_labels,
+        width: display::terminal_width(),
+        focus: true,
+        ..ListOptions::default()
+    };
+    let mut session = Session {
+        settings,
+        store,
+        lock: Some(lock),
+        list,
+        before,
+        list_name,
+        quiet,
+        skip_confirm,
+        strict,
+        json,
+        table,
+        theme,
+        checkbox,
+        template,
+        trash,
+        sort,
+        project,
+        context,
+        list_options,
+        trashed: Vec::new(),
+        archived: Vec::new(),
+        trash_items: None,
+        archive_items: None,
+        reports: Vec::new(),
+        save: false,
+    };
+    match command {
+        Command::Batch { path } => run_batch(&path, &mut session)?,
+        command => execute(command, &mut session)?,
+    }
+    if !session.save {
+        return Ok(());
+    }
+    let Session {
+        settings,
+        store,
+        lock: _lock,
+        list,
+        before,
+        quiet,
+        trash,
+        list_options,
+        trashed,
+        archived,
+        trash_items,
+        archive_items,
+        reports,
+        ..
+    } = session;
+
+    if let Some(capacity) = settings.behavior.capacity {
+        warn_capacity(&before, list.items(), capacity);
+    }
+    if cli.dry_run {
+        print_dry_run(HistoryEntry::new(command_line(), &before, list.items()).as_ref());
+        if !quiet {
+            list_items(&list, &list_options)?;
+        }
+        return Ok(());
+    }
+
+    // The data file is written first, so that its undo file keeps the trash and archive from
+    // before the command too
+    store.save(&list)?;
+    if let Some(items) = trash_items {
+        trash.save(&items)?;
+    }
+    trash.append(trashed)?;
+    let archive = store.archive();
+    if let Some(items) = archive_items {
+        archive.save(&items)?;
+    }
+    if !archived.is_empty() {
+        archive.append(archived)?;
+    }
+    for report in reports {
+        println!("{report}");
+    }
+    record_history(&store, &before, &list)?;
+    auto_commit(&settings, &store)?;
+    hooks::run(&settings.hooks, command_line(), &before, list.items());
+
+    if !quiet {
+        list_items(&list, &list_options)?;
+    }
+    Ok(())
 }
 
-#[derive(Serialize, Deserialize)]
-struct Settings {
-    silent: String,
+/// Everything a command works with, read once before it runs, and the changes it makes to be
+/// written after it.
+struct Session {
+    settings: Settings,
+    store: TodoStore,
+    /// Released by commands that keep running while other commands use the todo list.
+    lock: Option<StoreLock>,
+    list: TodoList,
+    /// The items before any command changed them.
+    before: Vec<Todo>,
+    list_name: String,
+    quiet: bool,
+    skip_confirm: bool,
+    strict: bool,
+    json: bool,
+    table: bool,
+    theme: Theme,
+    checkbox: Checkbox,
+    template: Option<Template>,
+    trash: Trash,
+    sort: Option<(SortMode, bool)>,
+    project: Option<String>,
+    context: Option<String>,
+    list_options: ListOptions,
+    /// The items moved to the trash or archive, which are written along with the todo list.
+    trashed: Vec<Todo>,
+    archived: Vec<Todo>,
+    /// The trash and archive without the items a command restored from them, which are written
+    /// along with the todo list.
+    trash_items: Option<Vec<TrashedTodo>>,
+    archive_items: Option<Vec<ArchivedTodo>>,
+    /// What the commands did, i.e., "Archived 2 item(s).", which is printed once it is saved. A
+    /// dry run leaves them out, since it saves nothing and lists the changes instead.
+    reports: Vec<String>,
+    /// Whether the command changed the todo list, so it has to be written. Commands that only
+    /// print, or write on their own, leave it unset.
+    save: bool,
 }
 
-/// Run the todo app.
-/// @param action - The action string chosen by the user.
-/// @param params - Any parameters passed after the action.
-pub fn run(action: &String, params: Vec<String>) {
-    let mut settings = extract_settings();
-    let (data_path, mut todo_data) = read_to_vec(dirs::data_dir());
-    match action.as_str() {
-        "add" => {
-            add_items(&mut todo_data, params, &data_path);
-            if settings.silent == "off" {
-                print_list(&todo_data);
+/// Run a command on the todo list of a session, without writing the todo list.
+fn execute(command: Command, session: &mut Session) -> Result<(), TodoError> {
+    let Session {
+        settings,
+        store,
+        lock,
+        list,
+        before,
+        list_name,
+        trash,
+        list_options,
+        trashed,
+        archived,
+        trash_items,
+        archive_items,
+        reports,
+        ..
+    } = session;
+    let (quiet, skip_confirm, strict) = (session.quiet, session.skip_confirm, session.strict);
+    let (json, table, theme, sort) = (session.json, session.table, session.theme, session.sort);
+    let (checkbox, template) = (session.checkbox.clone(), session.template.clone());
+    let (project, context) = (session.project.clone(), session.context.clone());
+
+    match command {
+        Command::Add { items } => {
+            let items = if items.is_empty() {
+                prompt_items()?
+            } else {
+                items
+            };
+            if items.is_empty() {
+                println!("Nothing was added.");
+                return Ok(());
+            }
+            for item in items {
+                if settings.behavior.warn_duplicates
+                    && let Some(i) = list.find_duplicate(&item, false)
+                {
+                    eprintln!(
+                        "WARNING: \"{item}\" is already item {} of the todo list.",
+                        i + 1
+                    );
+                }
+                let id = list.add(item);
+                if let Some(todo) = list.get_mut(ItemRef::Id(id)) {
+                    todo.project = project.clone();
+                    todo.context = context.clone();
+                }
             }
         }
-        "list" => print_list(&todo_data),
-        "remove" => {
-            remove_items(&mut todo_data, params, &data_path);
-            if settings.silent == "off" {
-                print_list(&todo_data);
+        Command::Dedupe { ignore_case } => {
+            let merged = list.dedupe(ignore_case);
+            for (kept, item) in &merged {
+                reports.push(format!(
+                    "Merged \"{}\" (id:{}) into id:{kept}.",
+                    item.label, item.id
+                ));
+            }
+            reports.push(format!("Removed {} duplicate(s).", merged.len()));
+            trashed.extend(merged.into_iter().map(|(_, item)| item));
+        }
+        Command::List {
+            tags,
+            mut filter,
+            json: json_flag,
+            table: table_flag,
+            dates,
+            group_by,
+            watch,
+            limit,
+            no_pager,
+        } => {
+            filter.tags.extend(tags);
+            // Only a plain `todo list` is limited to the focused items
+            let focus = filter.is_empty();
+            if filter.project.is_none() {
+                filter.project = project;
+            }
+            if filter.context.is_none() {
+                filter.context = context;
+            }
+            if filter.mine {
+                let Some(user) = &settings.sync.user else {
+                    return Err(TodoError::Usage(
+                        "Set who you are with `todo set user <NAME>` to list your items."
+                            .to_string(),
+                    ));
+                };
+                filter.assignee = Some(user.clone());
             }
+            let options = ListOptions {
+                filter,
+                json: (json && !table_flag) || json_flag,
+                table: table || table_flag,
+                template: template.filter(|_| !table_flag),
+                theme,
+                checkbox,
+                dates,
+                exact_dates: settings.display.exact_dates,
+                numbering: settings.display.numbering,
+                progress: settings.display.progress,
+                long_labels: settings.display.long_labels,
+                width: display::terminal_width(),
+                focus,
+                sort,
+                group_by,
+                limit,
+            };
+            if watch {
+                // Other commands wait for the lock, so it can't be held while watching
+                lock.take();
+                return watch::watch(store, &options);
+            }
+            #[cfg(unix)]
+            let _pager = if settings.display.pager && !no_pager {
+                pager::start()
+            } else {
+                None
+            };
+            return list_items(list, &options);
+        }
+        Command::Search { query, regex } => {
+            return search_items(list, &query, regex, &list_options.checkbox);
+        }
+        Command::Replace { from, to, regex } => {
+            let pattern = if regex {
+                from.clone()
+            } else {
+                regex::escape(&from)
+            };
+            let pattern = Regex::new(&pattern).map_err(TodoError::InvalidRegex)?;
+            let changed = if regex {
+                list.replace(&pattern, to.as_str())
+            } else {
+                list.replace(&pattern, NoExpand(&to))
+            };
+            if changed.is_empty() {
+                println!("No labels contain \"{from}\".");
+                return Ok(());
+            }
+            print_replacements(list, &changed);
+            reports.push(format!("Changed {} label(s).", changed.len()));
+        }
+        Command::Remove { items, interactive } => {
+            let Some(items) = pick_items(list, items, interactive, "remove", |_| true)? else {
+                return Ok(());
+            };
+            let items = disambiguate(list, items, strict)?;
+            if items.iter().any(|item| matches!(item, Selection::All))
+                && !confirm(&format!("Remove all {} item(s)?", list.len()), skip_confirm)?
+            {
+                return Ok(());
+            }
+            let removed = list.remove(&items);
+            reports.push(format!("Moved {} item(s) to the trash.", removed.len()));
+            trashed.extend(removed);
+        }
+        Command::Clear => {
+            if !confirm(&format!("Remove all {} item(s)?", list.len()), skip_confirm)? {
+                return Ok(());
+            }
+            let removed = list.remove(&[Selection::All]);
+            reports.push(format!("Moved {} item(s) to the trash.", removed.len()));
+            trashed.extend(removed);
+        }
+        Command::Check { items, interactive } => {
+            let Some(items) = pick_items(list, items, interactive, "check", |item| !item.complete)?
+            else {
+                return Ok(());
+            };
+            let blocked = list.blocked();
+            let count = list.items().len();
+            list.set_complete(&disambiguate(list, items, strict)?, true);
+            let still_blocked = list.blocked();
+            for (i, item) in
+                list.find(|item| blocked.contains(&item.id) && !still_blocked.contains(&item.id))
+            {
+                println!("Item {} is no longer blocked: {}", i + 1, item.label);
+            }
+            for (i, item) in list.items().iter().enumerate().skip(count) {
+                let due = item.due.map_or(String::new(), dates::format_date);
+                println!("Item {} repeats on {due}: {}", i + 1, item.label);
+            }
+        }
+        Command::Depends {
+            item,
+            blockers,
+            remove,
+            ..
+        } => {
+            let Some(id) = list.get(item).map(|todo| todo.id) else {
+                return Err(TodoError::NoMatch(format!("There is no item {item}.")));
+            };
+            for blocker in blockers {
+                let Some(blocker_id) = list.get(blocker).map(|todo| todo.id) else {
+                    return Err(TodoError::NoMatch(format!("There is no item {blocker}.")));
+                };
+                if !remove && blocker_id == id {
+                    return Err(TodoError::Usage(
+                        "An item can't wait for itself.".to_string(),
+                    ));
+                }
+                if !remove && list.depends_on(blocker_id, id) {
+                    return Err(TodoError::Usage(format!(
+                        "Item {item} can't wait for item {blocker}, since item {blocker} already waits for item {item}."
+                    )));
+                }
+
+                let Some(todo) = list.get_mut(ItemRef::Id(id)) else {
+                    continue;
+                };
+                if remove {
+                    todo.depends_on.retain(|&other| other != blocker_id);
+                } else if !todo.depends_on.contains(&blocker_id) {
+                    todo.depends_on.push(blocker_id);
+                }
+            }
+        }
+        Command::Uncheck { items, interactive } => {
+            let Some(items) =
+                pick_items(list, items, interactive, "uncheck", |item| item.complete)?
+            else {
+                return Ok(());
+            };
+            list.set_complete(&disambiguate(list, items, strict)?, false);
+        }
+        Command::Status {
+            item: Some(item),
+            status: Some(status),
+            ..
+        } => {
+            list.set_status(&disambiguate(list, vec![item], strict)?, status);
+        }
+        Command::Pin { items } => {
+            list.set_pinned(&disambiguate(list, items, strict)?, true);
+        }
+        Command::Unpin { items } => {
+            list.set_pinned(&disambiguate(list, items, strict)?, false);
+        }
+        Command::Focus { items } => {
+            list.set_focused(&disambiguate(list, items, strict)?, true);
+        }
+        Command::Unfocus { items } if items.is_empty() => {
+            list.set_focused(&[Selection::All], false);
+        }
+        Command::Unfocus { items } => {
+            list.set_focused(&disambiguate(list, items, strict)?, false);
+        }
+        Command::Sort { mode, reverse } => list.sort(mode, reverse),
+        Command::Move { from, to } => {
+            let Some(from) = list.resolve(from) else {
+                return Err(TodoError::NoMatch(format!("There is no item {from}.")));
+            };
+            let to = match to {
+                ItemRef::Position(pos) => pos - 1,
+                ItemRef::Id(_) => list
+                    .resolve(to)
+                    .ok_or_else(|| TodoError::NoMatch(format!("There is no item {to}.")))?,
+            };
+            list.move_item(from, to);
+        }
+        Command::Swap { a, b } => {
+            let resolve = |item| {
+                list.resolve(item)
+                    .ok_or_else(|| TodoError::NoMatch(format!("There is no item {item}.")))
+            };
+            let (i, j) = (resolve(a)?, resolve(b)?);
+            list.swap(i, j);
+        }
+        Command::Set { setting, value } => return set_setting(settings, setting, value),
+        Command::Alias {
+            name,
+            command,
+            remove,
+        } => return set_alias(settings, name, command, remove),
+        Command::Note { item, text } => {
+            let Some(todo) = list.get_mut(item) else {
+                return Err(TodoError::NoMatch(format!("There is no item {item}.")));
+            };
+            let text = match text {
+                Some(text) => text,
+                None => editor::edit_text(todo.note.as_deref().unwrap_or(""))?,
+            };
+            let text = text.trim_end();
+            todo.note = (!text.is_empty()).then(|| text.to_string());
+        }
+        Command::Comment { item, text } => {
+            let Some(todo) = list.get_mut(item) else {
+                return Err(TodoError::NoMatch(format!("There is no item {item}.")));
+            };
+            let text = text.trim();
+            if text.is_empty() {
+                return Err(TodoError::Usage("A comment can't be empty.".to_string()));
+            }
+            todo.comments.push(Comment {
+                at: Utc::now(),
+                author: settings.sync.user.clone(),
+                text: text.to_string(),
+            });
+        }
+        Command::Copy { item, to } => {
+            let Some(todo) = list.get(item) else {
+                return Err(TodoError::NoMatch(format!("There is no item {item}.")));
+            };
+            let copy = todo.duplicate();
+            match to {
+                Some(name) if TodoStore::open_list(&name)?.path() != store.path() => {
+                    let id = push_to_list(settings, &name, vec![copy])?[0];
+                    println!("Copied item {item} to the \"{name}\" list as id:{id}.");
+                    return Ok(());
+                }
+                _ => {
+                    let id = list.push(copy);
+                    println!("Copied item {item} as id:{id}.");
+                }
+            }
+        }
+        Command::Split { item, labels } => {
+            let Some(i) = list.resolve(item) else {
+                return Err(TodoError::NoMatch(format!("There is no item {item}.")));
+            };
+            let (original, ids) = list.split(i, &labels);
+            reports.push(format!(
+                "Split \"{}\" into {} items (id:{} to id:{}), and moved it to the trash.",
+                original.label,
+                ids.len(),
+                ids[0],
+                ids[ids.len() - 1]
+            ));
+            trashed.push(original);
         }
-        "clear" => {
-            remove_items(&mut todo_data, vec!["all".to_string()], &data_path);
-            if settings.silent == "off" {
-                print_list(&todo_data);
+        Command::Merge { item, other } => {
+            let Some(keep) = list.resolve(item) else {
+                return Err(TodoError::NoMatch(format!("There is no item {item}.")));
+            };
+            let Some(i) = list.resolve(other) else {
+                return Err(TodoError::NoMatch(format!("There is no item {other}.")));
+            };
+            if i == keep {
+                return Err(TodoError::Usage(
+                    "An item can't be merged into itself.".to_string(),
+                ));
             }
+            let merged = list.merge(keep, i);
+            reports.push(format!(
+                "Merged \"{}\" (id:{}) into item {item}, and moved it to the trash.",
+                merged.label, merged.id
+            ));
+            trashed.push(merged);
         }
-        "check" => {
-            check_items(&mut todo_data, params, &data_path);
-            if settings.silent == "off" {
-                print_list(&todo_data);
+        Command::Link { item, url } => {
+            let Some(todo) = list.get_mut(item) else {
+                return Err(TodoError::NoMatch(format!("There is no item {item}.")));
+            };
+            if url != "none" && !browser::is_url(&url) {
+                return Err(TodoError::Usage(format!(
+                    "\"{url}\" isn't a URL. Give the whole link, i.e., \"https://example.com\"."
+                )));
+            }
+            todo.url = (url != "none").then_some(url);
+        }
+        Command::Open { item } => {
+            let Some(todo) = list.get(item) else {
+                return Err(TodoError::NoMatch(format!("There is no item {item}.")));
+            };
+            let Some(url) = &todo.url else {
+                return Err(TodoError::Usage(format!(
+                    "Item {item} has no link. Add one with `todo link {item} <URL>`."
+                )));
+            };
+            return browser::open(url);
+        }
+        Command::Show { item } => {
+            let Some(i) = list.resolve(item) else {
+                return Err(TodoError::NoMatch(format!("There is no item {item}.")));
+            };
+            show_item(i, &list.items()[i]);
+            return Ok(());
+        }
+        Command::Remind { days, no_notify } => {
+            let days = days.unwrap_or(settings.behavior.remind_days);
+            let today = dates::today();
+            return remind::remind(list, today, days, !no_notify);
+        }
+        Command::Stats {
+            chart,
+            json: json_flag,
+            days,
+        } => {
+            let stats = Stats::collect(
+                list,
+                &store.archive().load()?,
+                &store.history().load()?,
+                dates::today(),
+                days,
+            );
+            return print_stats(&stats, chart, json || json_flag);
+        }
+        Command::Count {
+            summary,
+            json: json_flag,
+        } => {
+            let counts = Counts::collect(list, dates::today());
+            return print_counts(&counts, summary, json || json_flag);
+        }
+        Command::Lists => return print_lists(list_name),
+        Command::Project { name: None, .. } => {
+            match &project {
+                Some(project) => println!("Commands are scoped to the project \"{project}\"."),
+                None => println!("Commands aren't scoped to a project."),
+            }
+            return Ok(());
+        }
+        Command::Project {
+            name: Some(name),
+            items,
+        } if items.is_empty() => {
+            settings.set("project", &name)?;
+            settings.save()?;
+            if name == "none" {
+                println!("Commands are no longer scoped to a project.");
+            } else {
+                println!("Now using project \"{name}\".");
+            }
+            return Ok(());
+        }
+        Command::Project {
+            name: Some(name),
+            items,
+        } => {
+            let project = (name != "none").then_some(name.as_str());
+            list.set_project(&disambiguate(list, items, strict)?, project);
+        }
+        Command::Context { name: None, .. } => {
+            match &context {
+                Some(context) => println!("Commands are scoped to the context @{context}."),
+                None => println!("Commands aren't scoped to a context."),
+            }
+            return Ok(());
+        }
+        Command::Context {
+            name: Some(name),
+            items,
+        } if items.is_empty() => {
+            settings.set("context", &name)?;
+            settings.save()?;
+            match &settings.behavior.context {
+                Some(context) => println!("Now using context @{context}."),
+                None => println!("Commands are no longer scoped to a context."),
+            }
+            return Ok(());
+        }
+        Command::Context {
+            name: Some(name),
+            items,
+        } => {
+            let context = (name != "none").then(|| name.trim_start_matches('@'));
+            list.set_context(&disambiguate(list, items, strict)?, context);
+        }
+        Command::Start { item } => {
+            let Some(i) = list.resolve(item) else {
+                return Err(TodoError::NoMatch(format!("There is no item {item}.")));
+            };
+            let now = Utc::now();
+            if let Some((stopped, spent)) = list.start_timer(i, now) {
+                println!(
+                    "Stopped the timer on item {} after {}.",
+                    stopped + 1,
+                    format_duration(spent)
+                );
+            }
+            println!("Started a timer on item {}.", i + 1);
+        }
+        Command::Stop => match list.stop_timer(Utc::now()) {
+            Some((i, spent)) => {
+                println!(
+                    "Stopped the timer on item {} after {}.",
+                    i + 1,
+                    format_duration(spent)
+                );
+            }
+            None => return Err(TodoError::Usage("There is no timer running.".to_string())),
+        },
+        Command::Pomodoro {
+            item,
+            work,
+            rest,
+            rounds,
+            notify,
+        } => {
+            let Some(todo) = list.get(item) else {
+                return Err(TodoError::NoMatch(format!("There is no item {item}.")));
+            };
+            let (id, label) = (todo.id, todo.label.clone());
+            // Other commands wait for the lock, so it is only taken to log finished work
+            lock.take();
+            let pomodoro = Pomodoro {
+                work,
+                rest,
+                rounds,
+                notify,
+            };
+            return pomodoro.run(&label, |start, end| {
+                let _lock = store.lock()?;
+                let mut list = store.load()?;
+                let before = list.items().to_vec();
+                if let Some(todo) = list.get_mut(ItemRef::Id(id)) {
+                    todo.time_log.push(WorkInterval {
+                        start,
+                        end: Some(end),
+                    });
+                    todo.pomodoros += 1;
+                }
+                store.save(&list)?;
+                record_history(store, &before, &list)?;
+                auto_commit(settings, store)
+            });
+        }
+        Command::Report {
+            kind: ReportKind::Time { since },
+        } => {
+            print_time_report(list, since);
+            return Ok(());
+        }
+        Command::Estimate { item: None, .. } => {
+            let today = dates::today();
+            let pending =
+                list.find(|item| !item.complete && list_options.filter.matches(item, today));
+            let scheduled = estimate::scheduled_today(list.items(), today);
+            print_effort(&pending, scheduled, settings.behavior.capacity);
+            return Ok(());
+        }
+        Command::Estimate {
+            item: Some(item),
+            estimate,
+        } => {
+            let Some(todo) = list.get_mut(item) else {
+                return Err(TodoError::NoMatch(format!("There is no item {item}.")));
+            };
+            todo.estimate = estimate.and_then(|estimate| estimate.0);
+        }
+        Command::Projects => {
+            print_projects(list, project.as_deref());
+            return Ok(());
+        }
+        Command::Sync => {
+            let sync = &settings.sync;
+            if sync.git_remote.is_none() && sync.caldav_url.is_none() {
+                return Err(TodoError::Usage(
+                    "There is nothing to sync with. Set a git remote with `todo set git-remote <URL>`, or a CalDAV calendar with `todo set caldav-url <URL>`."
+                        .to_string(),
+                ));
+            }
+            if let Some(remote) = &sync.git_remote {
+                git::sync(&store::data_dir()?, remote, settings.storage.encryption)?;
+                println!("Synced the todo lists with {remote}.");
+            }
+            if let Some(url) = &sync.caldav_url {
+                // Merging may have changed the todo list
+                let list = store.load()?;
+                let count = caldav::sync(list.items(), url, sync.caldav_user.as_deref())?;
+                println!("Uploaded {count} item(s) to the CalDAV calendar.");
+            }
+            return Ok(());
+        }
+        Command::Daemon => {
+            // Other commands wait for the lock, so it can't be held while serving
+            lock.take();
+            #[cfg(unix)]
+            return daemon::serve(store.clone());
+            #[cfg(not(unix))]
+            return Err(TodoError::Usage(
+                "The daemon is only available on Unix-like systems.".to_string(),
+            ));
+        }
+        Command::Encrypt => {
+            if !settings.storage.encryption {
+                crypto::new_passphrase()?;
+            }
+            rewrite_lists(store, true)?;
+            settings.set("encryption", "on")?;
+            settings.save()?;
+            println!("Encrypted the todo lists. Keep the passphrase safe, it can't be recovered.");
+            return Ok(());
+        }
+        Command::Decrypt => {
+            rewrite_lists(store, false)?;
+            settings.set("encryption", "off")?;
+            settings.save()?;
+            println!("Decrypted the todo lists.");
+            return Ok(());
+        }
+        Command::Completions { .. }
+        | Command::Init
+        | Command::Status { .. }
+        | Command::External(_)
+        | Command::Batch { .. } => {
+            unreachable!(
+                "completions, init, status, aliases, and batches are handled before loading"
+            )
+        }
+        Command::Export { format } => {
+            let text = match format {
+                ExportFormat::Todotxt => formats::todotxt::export(list.items()),
+                ExportFormat::Json => formats::json::export(&list.find(|_| true))? + "\n",
+                ExportFormat::Ics => formats::ics::export(list.items()),
+                ExportFormat::Md => formats::markdown::export(list.items()),
+                ExportFormat::Csv => formats::csv::export(list.items()),
+                ExportFormat::Org => formats::org::export(list.items()),
+            };
+            print!("{text}");
+            return Ok(());
+        }
+        Command::Import {
+            format,
+            path,
+            lists_as,
+        } => {
+            let text = fs::read_to_string(&path)
+                .map_err(|err| TodoError::io(format!("Could not read {}", path.display()), err))?;
+            let unlisted = |items: Vec<Todo>| items.into_iter().map(|item| (None, item)).collect();
+            let items: Vec<(Option<String>, Todo)> = match format {
+                ImportFormat::Todotxt => unlisted(formats::todotxt::import(&text)),
+                ImportFormat::Md => unlisted(formats::markdown::import(&text)),
+                ImportFormat::Csv => unlisted(formats::csv::import(&text)?),
+                ImportFormat::Reminders => formats::reminders::import(&text)?,
+                ImportFormat::Mstodo => formats::mstodo::import(&text)?,
+            };
+
+            // The items of other lists, in the order their lists first come up
+            let mut others: Vec<(String, Vec<Todo>)> = Vec::new();
+            let mut count = 0;
+            for (name, mut item) in items {
+                match (lists_as, name.map(|name| list_slug(&name))) {
+                    (_, None) => {}
+                    (_, Some(name)) if name.is_empty() => {}
+                    (ListsAs::Tags, Some(name)) => item.add_tag(&name),
+                    (ListsAs::Lists, Some(name)) => {
+                        if TodoStore::open_list(&name)?.path() != store.path() {
+                            match others.iter_mut().find(|(other, _)| *other == name) {
+                                Some((_, items)) => items.push(item),
+                                None => others.push((name, vec![item])),
+                            }
+                            continue;
+                        }
+                    }
+                }
+                list.push(item);
+                count += 1;
+            }
+
+            if count > 0 || others.is_empty() {
+                reports.push(format!("Imported {count} item(s)."));
+            }
+            for (name, items) in others {
+                let ids = push_to_list(settings, &name, items)?;
+                println!("Imported {} item(s) into the \"{name}\" list.", ids.len());
+            }
+        }
+        Command::Archive { action: None } => {
+            let completed = list.remove(&[Selection::Checked]);
+            reports.push(format!("Archived {} item(s).", completed.len()));
+            archived.extend(completed);
+        }
+        Command::Archive {
+            action: Some(ArchiveAction::List),
+        } => {
+            print_archive(&store.archive().load()?, &list_options.checkbox);
+            return Ok(());
+        }
+        Command::Archive {
+            action: Some(ArchiveAction::Restore { items }),
+        } => {
+            let mut archived = match archive_items.take() {
+                Some(items) => items,
+                None => store.archive().load()?,
+            };
+
+            let ids: Vec<u64> = archived.iter().map(|a| a.todo.id).collect();
+            let indices = restore_indices(&items, &ids).map_err(|item| {
+                TodoError::NoMatch(format!(
+                    "There is no archived item {item}. See `todo archive list`."
+                ))
+            })?;
+
+            for &i in indices.iter().rev() {
+                list.restore(archived.remove(i).todo);
+            }
+            reports.push(format!("Restored {} item(s).", indices.len()));
+            *archive_items = Some(archived);
+        }
+        Command::Trash {
+            action: TrashAction::List,
+        } => {
+            print_trash(&trash.load()?, &list_options.checkbox);
+            return Ok(());
+        }
+        Command::Trash {
+            action: TrashAction::Empty,
+        } => {
+            let count = trash.load()?.len();
+            let question = format!("Permanently delete {count} item(s)?");
+            if count > 0 && !confirm(&question, skip_confirm)? {
+                return Ok(());
+            }
+            trash.save(&[])?;
+            println!("Permanently deleted {count} item(s).");
+            return Ok(());
+        }
+        Command::Restore { items } => {
+            let mut trashed = match trash_items.take() {
+                Some(items) => items,
+                None => trash.load()?,
+            };
+
+            let ids: Vec<u64> = trashed.iter().map(|t| t.todo.id).collect();
+            let indices = restore_indices(&items, &ids).map_err(|item| {
+                TodoError::NoMatch(format!(
+                    "There is no removed item {item}. See `todo trash list`."
+                ))
+            })?;
+
+            for &i in indices.iter().rev() {
+                list.restore(trashed.remove(i).todo);
+            }
+            reports.push(format!("Restored {} item(s).", indices.len()));
+            *trash_items = Some(trashed);
+        }
+        Command::Undo => {
+            if !store.undo()? {
+                println!("Nothing to undo.");
+                return Ok(());
+            }
+            let list = store.load()?;
+            record_history(store, before, &list)?;
+            auto_commit(settings, store)?;
+            if !quiet {
+                list_items(&list, list_options)?;
+            }
+            return Ok(());
+        }
+        Command::Backup {
+            action: BackupAction::List,
+        } => {
+            print_backups(&store.backup_paths())?;
+            return Ok(());
+        }
+        Command::Backup {
+            action: BackupAction::Restore { n },
+        } => {
+            if !store.restore_backup(n)? {
+                return Err(TodoError::NoMatch(format!(
+                    "There is no backup {n}. See `todo backup list`."
+                )));
+            }
+            println!("Restored backup {n}.");
+            let list = store.load()?;
+            record_history(store, before, &list)?;
+            auto_commit(settings, store)?;
+            if !quiet {
+                list_items(&list, list_options)?;
+            }
+            return Ok(());
+        }
+        Command::History { count } => {
+            let entries = store.history().load()?;
+            print_history(&entries[entries.len().saturating_sub(count)..]);
+            return Ok(());
+        }
+        Command::Did { day, since } => {
+            let today = dates::today();
+            let (first, last) = match (day, since) {
+                (_, Some(since)) => (since, today),
+                (day, None) => (day.unwrap_or(today), day.unwrap_or(today)),
+            };
+            print_done(&store.done_log().load()?, first, last, today);
+            return Ok(());
+        }
+        Command::Use { name } => {
+            settings.set("list", &name)?;
+            settings.save()?;
+            println!("Now using list \"{name}\".");
+            return Ok(());
+        }
+        Command::Edit { all: true, .. } => trashed.extend(edit_all(list)?),
+        Command::Today => {
+            return print_agenda(list, dates::today(), 1, list_options);
+        }
+        Command::Week => {
+            let today = dates::today();
+            let end = dates::start_of_week(today) + Days::new(7);
+            let days = (end - today).num_days() as u64;
+            return print_agenda(list, today, days, list_options);
+        }
+        Command::Overdue => {
+            return print_overdue(list, dates::today(), list_options);
+        }
+        Command::Board => {
+            return print_board(list, list_options);
+        }
+        Command::Next => {
+            let today = dates::today();
+            let next = next::next(list, &list_options.filter, &settings.next, today);
+            return print_next(next, list_options);
+        }
+        Command::Plan { clear: true, .. } => list.plan(&HashSet::new(), dates::today()),
+        Command::Plan { capacity, .. } => {
+            let Some(capacity) = capacity.or(settings.behavior.capacity) else {
+                return Err(TodoError::Usage(
+                    "Set how much work fits in a day with `todo set capacity 6h` (or --capacity) to plan it."
+                        .to_string(),
+                ));
+            };
+            let today = dates::today();
+            let ranked = next::ranked(list, &list_options.filter, &settings.next, today);
+            let plan = estimate::plan(&ranked, capacity, today);
+            if plan.items.is_empty() {
+                println!(
+                    "Nothing could be planned. Add estimates to items with `todo estimate <ITEM> <ESTIMATE>`."
+                );
+                return Ok(());
+            }
+            print_plan(&plan, capacity, list_options);
+            if !confirm("Plan these items for today?", skip_confirm)? {
+                return Ok(());
+            }
+            let ids: HashSet<u64> = plan.items.iter().map(|(_, item)| item.id).collect();
+            list.plan(&ids, today);
+        }
+        Command::Review => trashed.extend(review(list)?),
+        Command::Edit {
+            args,
+            editor,
+            interactive,
+            ..
+        } => {
+            let args = if interactive {
+                let Some(items) = pick_items(list, Vec::new(), true, "edit", |_| true)? else {
+                    return Ok(());
+                };
+                items.iter().map(Selection::to_string).collect()
+            } else {
+                args
+            };
+            let editor = editor || settings.behavior.edit_mode == EditMode::Editor;
+            edit_items(list, &args, editor, strict)?
+        }
+        Command::Due { item, date, every } => {
+            let Some(todo) = list.get_mut(item) else {
+                return Err(TodoError::NoMatch(format!("There is no item {item}.")));
+            };
+            if let Some(date) = date {
+                todo.due = date.0;
+                if todo.due.is_none() {
+                    todo.recurrence = None;
+                }
+            }
+            if let Some(every) = every {
+                // Without a due date, the item is due on the first day of the recurrence
+                if todo.due.is_none()
+                    && let Some(recurrence) = &every.0
+                {
+                    let today = dates::today();
+                    let yesterday = today.pred_opt().unwrap_or(today);
+                    todo.due = recurrence.following(yesterday, today).map(|(due, _)| due);
+                }
+                todo.recurrence = every.0;
             }
         }
-        "uncheck" => {
-            uncheck_items(&mut todo_data, params, &data_path);
-            if settings.silent == "off" {
-                print_list(&todo_data);
+        Command::Snooze { item, until } => {
+            let Some(todo) = list.get_mut(item) else {
+                return Err(TodoError::NoMatch(format!("There is no item {item}.")));
+            };
+            todo.hidden_until = until.0;
+        }
+        Command::StartDate { item, date } => {
+            let Some(todo) = list.get_mut(item) else {
+                return Err(TodoError::NoMatch(format!("There is no item {item}.")));
+            };
+            todo.start = date.0;
+            if let (Some(start), Some(due)) = (todo.start, todo.due)
+                && start > due
+            {
+                eprintln!("WARNING: Item {item} starts after it is due.");
             }
         }
-        "sort" => {
-            sort_items(&mut todo_data, params, &data_path);
-            if settings.silent == "off" {
-                print_list(&todo_data);
+        Command::Priority { item, level } => {
+            let Some(todo) = list.get_mut(item) else {
+                return Err(TodoError::NoMatch(format!("There is no item {item}.")));
+            };
+            todo.priority = level.0;
+        }
+        Command::Assign { item, name } => {
+            let Some(todo) = list.get_mut(item) else {
+                return Err(TodoError::NoMatch(format!("There is no item {item}.")));
+            };
+            todo.assignee = (name != "none").then_some(name);
+        }
+        Command::Tag { item, tags } => {
+            let Some(todo) = list.get_mut(item) else {
+                return Err(TodoError::NoMatch(format!("There is no item {item}.")));
+            };
+            for tag in &tags {
+                todo.add_tag(tag);
             }
         }
-        "set" => set_setting(&mut settings, params),
-        "edit" => {
-            edit_item(&mut todo_data, params, &data_path);
-            if settings.silent == "off" {
-                print_list(&todo_data);
+        Command::Untag { item, tags } => {
+            let Some(todo) = list.get_mut(item) else {
+                return Err(TodoError::NoMatch(format!("There is no item {item}.")));
+            };
+            if tags.iter().any(|tag| tag == "all") {
+                todo.tags.clear();
+            } else {
+                for tag in &tags {
+                    todo.remove_tag(tag);
+                }
             }
         }
-        "help" => show_help(),
-        _ => println!("Invalid action: {action}"),
     }
+
+    session.save = true;
+    Ok(())
 }
 
-/// Read the data file from disk and convert the String data into a String Vector.
-/// The output is a tuple where the first element is the finalized data file path
-/// and the second element is the data Vector.
-/// @param dir - An Option<PathBuf>, where the PathBuf points to the parent directory of the
-/// "todo-app" folder that contains the data file.
-fn read_to_vec(dir: Option<PathBuf>) -> (String, Vec<Todo>) {
-    let mut data: Vec<Todo> = Vec::new();
+/// Run the commands of a batch file (or stdin, for "-") one after the other on the todo list of
+/// the session. Each line is a command as it would follow `todo`, and blank lines and lines
+/// starting with "#" are skipped. A command that fails stops the batch before anything is written.
+fn run_batch(path: &Path, session: &mut Session) -> Result<(), TodoError> {
+    let text = if path == Path::new("-") {
+        io::read_to_string(io::stdin())
+            .map_err(|err| TodoError::io("Could not read the commands from stdin", err))?
+    } else {
+        fs::read_to_string(path)
+            .map_err(|err| TodoError::io(format!("Could not read {}", path.display()), err))?
+    };
 
-    let mut path_buf: PathBuf = dir.unwrap_or_else(|| {
-        eprintln!("ERROR: Cannot open data directory.");
-        process::exit(1);
+    // Commands that write more than the todo list would write before the batch is done
+    store::set_batch(true);
+    let result = text.lines().enumerate().try_for_each(|(n, line)| {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return Ok(());
+        }
+        batch_command(line, &session.settings)
+            .and_then(|command| {
+                debug!("Parsed line {} of the batch: {command:?}", n + 1);
+                execute(command, session)
+            })
+            .inspect_err(|_| {
+                eprintln!(
+                    "Stopped at line {} of the batch, so nothing was saved.",
+                    n + 1
+                );
+            })
     });
+    store::set_batch(false);
+    result
+}
 
-    path_buf.push("todo-app");
+/// The global flags given on a command line, i.e., "--list".
+fn global_flags(cli: &Cli) -> Vec<&'static str> {
+    [
+        ("--quiet", cli.quiet),
+        ("--verbose", cli.verbose > 0),
+        ("--list", cli.list.is_some()),
+        ("--file", cli.file.is_some()),
+        ("--no-color", cli.no_color),
+        ("--read-only", cli.read_only),
+        ("--strict", cli.strict),
+        ("--dry-run", cli.dry_run),
+        ("--error-format", cli.error_format != ErrorFormat::Text),
+        ("--yes", cli.yes),
+    ]
+    .into_iter()
+    .filter_map(|(flag, given)| given.then_some(flag))
+    .collect()
+}
 
-    if let Err(e) = fs::create_dir_all(&path_buf) {
-        eprintln!(
-            "ERROR: Could not create the data directory at {}: {e}",
-            path_buf.to_str().unwrap()
-        );
-        process::exit(1);
+/// Parse a line of a batch file into a command, expanding an alias. A leading "todo" is
+/// optional. Global flags only apply to the whole batch, so lines can't have them.
+fn batch_command(line: &str, settings: &Settings) -> Result<Command, TodoError> {
+    let mut words = split_words(line);
+    if words.first().is_some_and(|word| word == "todo") {
+        words.remove(0);
     }
+    let parse = |words: Vec<String>| {
+        let cli = Cli::try_parse_from(std::iter::once("todo".to_string()).chain(words)).map_err(
+            |err| {
+                TodoError::Usage(format!(
+                    "Invalid command \"{line}\": {}",
+                    clap_message(&err)
+                ))
+            },
+        )?;
+        let flags = global_flags(&cli);
+        if !flags.is_empty() {
+            return Err(TodoError::Usage(format!(
+                "\"{line}\" has {}, which can't be given on a line of a batch. Give it to `todo batch` instead, to use it for the whole batch.",
+                flags.join(" and ")
+            )));
+        }
+        Ok(cli)
+    };
 
-    path_buf.push(DATA_FILE_NAME);
+    let mut command = parse(words)?.command;
+    if let Some(Command::External(args)) = &command {
+        let (name, rest) = args.split_first().expect("clap passes the alias's name");
+        let Some(expansion) = settings.aliases.get(name) else {
+            return Err(TodoError::Usage(format!(
+                "There is no command or alias \"{name}\". Run `todo help` for the commands, or `todo alias` for the aliases."
+            )));
+        };
+        command = parse(
+            split_words(expansion)
+                .into_iter()
+                .chain(rest.iter().cloned())
+                .collect(),
+        )?
+        .command;
+    }
 
-    if let Ok(str) = fs::read_to_string(&path_buf) {
-        for line in str.lines() {
-            let todo = serde_json::from_str(line).unwrap_or_else(|err| {
-                eprintln!("ERROR: Could not parse line \"{line}\" in data file: {err}");
-                process::exit(1);
-            });
-            data.push(todo);
+    match command {
+        Some(Command::Batch { .. }) => Err(TodoError::Usage(
+            "A batch can't run another batch.".to_string(),
+        )),
+        Some(Command::External(_)) => Err(TodoError::Usage(format!(
+            "The alias in \"{line}\" doesn't stand for a command."
+        ))),
+        Some(Command::Completions { .. } | Command::Init | Command::Status { item: None, .. }) => {
+            Err(TodoError::Usage(format!(
+                "\"{line}\" doesn't change the todo list, so it can't be run in a batch."
+            )))
+        }
+        Some(Command::List { watch: true, .. } | Command::Pomodoro { .. } | Command::Daemon) => {
+            Err(TodoError::Usage(format!(
+                "\"{line}\" keeps running, so it can't be run in a batch."
+            )))
         }
+        Some(command) => Ok(command),
+        None => Err(TodoError::Usage(format!(
+            "The line \"{line}\" has no command."
+        ))),
     }
-
-    (path_buf.into_os_string().into_string().unwrap(), data)
 }
 
-/// Add items to the todo list.
-fn add_items(data: &mut Vec<Todo>, params: Vec<String>, data_path: &String) {
-    for param in params {
-        data.push(Todo {
-            label: param,
-            complete: false,
-        });
+/// The items a command was given, or with `-i`, the items the user picks in the fuzzy finder
+/// from those matching the predicate. None if nothing was picked, so nothing should change.
+fn pick_items(
+    list: &TodoList,
+    items: Vec<Selection>,
+    interactive: bool,
+    action: &str,
+    predicate: impl Fn(&Todo) -> bool,
+) -> Result<Option<Vec<Selection>>, TodoError> {
+    if !interactive {
+        return Ok(Some(items));
     }
 
-    write_data(data, data_path);
+    let candidates = list.find(predicate);
+    if candidates.is_empty() {
+        return Err(TodoError::NoMatch(format!(
+            "There are no items to {action}."
+        )));
+    }
+    let choices: Vec<Choice> = candidates
+        .iter()
+        .map(|(i, item)| Choice {
+            position: i + 1,
+            label: item.label.clone(),
+        })
+        .collect();
+    let picked = finder::pick(&choices, &format!("Items to {action}"))?;
+    if picked.is_empty() {
+        println!("Nothing was picked, so nothing was changed.");
+        return Ok(None);
+    }
+    Ok(Some(
+        picked
+            .into_iter()
+            .map(|n| Selection::Item(ItemRef::Id(candidates[n].1.id)))
+            .collect(),
+    ))
 }
 
-/// Remove items from the todo list.
-/// Items are specified by their position (as shown in "todo list" command) or with "all".
-fn remove_items(data: &mut Vec<Todo>, params: Vec<String>, data_path: &String) {
-    if params.len() == 0 {
-        eprintln!("ERROR: Invalid use of `remove`. See `todo help` for options");
-        process::exit(1);
-    }
-    if params[0] == "all" {
-        data.clear();
-        write_data(data, data_path);
-        return;
-    } else if params[0] == "checked" || params[0] == "completed" {
-        data.retain(|item| item.complete == false);
-        write_data(data, data_path);
-        return;
+/// Edit items. When the arguments after the first item aren't items themselves, they are the
+/// new label of the first item (i.e., `todo edit 2 buy oat milk`). Otherwise, the user is
+/// prompted for the new label of every given item, or edits it in $EDITOR.
+fn edit_items(
+    list: &mut TodoList,
+    args: &[String],
+    editor: bool,
+    strict: bool,
+) -> Result<(), TodoError> {
+    let parse_item = |arg: &String| {
+        arg.parse::<Selection>()
+            .map_err(|err| TodoError::Usage(format!("Invalid item \"{arg}\" for `edit`: {err}")))
+    };
+
+    // A second argument that isn't an item (or range) is the new label
+    let is_item = |arg: &String| !matches!(arg.parse(), Err(_) | Ok(Selection::Label(_)));
+    if args.len() >= 2 && !is_item(&args[1]) {
+        let item = match disambiguate(list, vec![parse_item(&args[0])?], strict)?.as_slice() {
+            [Selection::Item(item)] => *item,
+            _ => {
+                return Err(TodoError::Usage(
+                    "Only a single item can be given a new label at once.".to_string(),
+                ));
+            }
+        };
+        let Some(todo) = list.get_mut(item) else {
+            return Err(TodoError::NoMatch(format!("There is no item {item}.")));
+        };
+        todo.label = args[1..].join(" ");
+        return Ok(());
     }
 
-    let mut positions: Vec<usize> = params
-        .iter()
-        .map(|s| s.parse::<usize>().unwrap_or_else(|err| {
-            eprintln!("ERROR: Cannot convert position string \"{s}\" into a valid position value: {err}");
-            process::exit(1);
-        })).collect();
+    let selections = args.iter().map(parse_item).collect::<Result<Vec<_>, _>>()?;
+    let selections = disambiguate(list, selections, strict)?;
+
+    for i in list.select(&selections) {
+        let position = ItemRef::Position(i + 1);
+        if editor {
+            edit_in_editor(list.get_mut(position).unwrap())?;
+            continue;
+        }
+
+        let original = list.get(position).unwrap();
+        println!("Original: {}", original.label);
 
-    positions.sort();
-    positions.reverse();
+        // There is no input to read when stdin is closed, i.e., in scripts
+        let Some(label) = prompt("New (leave empty to keep): ")? else {
+            return Err(TodoError::Usage(
+                "No new label was given. Pass it as an argument instead: todo edit <item> \"new label\""
+                    .to_string(),
+            ));
+        };
 
-    // Out-of-bound positions are ignored
-    for pos in positions {
-        if pos <= data.len() {
-            data.remove(pos - 1);
+        if !label.is_empty() {
+            list.get_mut(position).unwrap().label = label;
         }
     }
 
-    write_data(data, data_path);
+    Ok(())
 }
 
-/// Check items in the todo list.
-fn check_items(data: &mut Vec<Todo>, params: Vec<String>, data_path: &String) {
-    if params.len() == 0 {
-        eprintln!("ERROR: Invalid use of `check`. See `todo help` for options");
-        process::exit(1);
+/// Edit the label of an item, with its note below it, in $EDITOR. An empty label keeps the old one.
+fn edit_in_editor(todo: &mut Todo) -> Result<(), TodoError> {
+    let mut text = format!("{}\n", todo.label);
+    if let Some(note) = &todo.note {
+        text.push_str(&format!("\n{note}\n"));
     }
-    if params[0] == "all" {
-        for item in data.iter_mut() {
-            item.complete = true;
-        }
-        write_data(data, data_path);
-        return;
+
+    let edited = editor::edit_text(&text)?;
+    let (label, note) = edited.split_once('\n').unwrap_or((&edited, ""));
+    let label = label.trim();
+    if !label.is_empty() {
+        todo.label = label.to_string();
     }
+    let note = note.trim_start_matches('\n').trim_end();
+    todo.note = (!note.is_empty()).then(|| note.to_string());
+    Ok(())
+}
 
-    let positions: Vec<usize> = params
-        .iter()
-        .map(|s| s.parse::<usize>().unwrap_or_else(|err| {
-            eprintln!("ERROR: Cannot convert position string \"{s}\" into a valid position value: {err}");
-            process::exit(1);
-        })).collect();
+const EDIT_ALL_HELP: &str = "\
+# One item per line: \"[ ] label\", or \"[x] label\" for a completed item.
+# Remove a line to remove its item, or add a line to add an item. Keep the \"id:N\" of a line to
+# keep the item's due date, priority, tags, and note. Lines starting with # are ignored.
+";
+
+/// Edit the whole todo list in $EDITOR, one item per line. The items are put in the order of the
+/// lines, and items whose line was removed are returned.
+fn edit_all(list: &mut TodoList) -> Result<Vec<Todo>, TodoError> {
+    let mut text = EDIT_ALL_HELP.to_string();
+    for item in list.items() {
+        let checkbox = if item.complete { "[x]" } else { "[ ]" };
+        text.push_str(&format!("{checkbox} id:{} {}\n", item.id, item.label));
+    }
+    let edited = editor::edit_text(&text)?;
+    Ok(apply_edits(list, &edited))
+}
+
+/// Change the todo list to match the lines edited in `edit_all`, returning the items whose line
+/// was removed.
+fn apply_edits(list: &mut TodoList, edited: &str) -> Vec<Todo> {
+    let mut remaining = list.items().to_vec();
+    let mut edited_list = TodoList::new().with_next_id(list.next_id());
+    for line in edited.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
 
-    // Out-of-bound positions are ignored
-    for pos in positions {
-        if pos <= data.len() {
-            data[pos - 1].complete = true;
+        let (complete, rest) = match line.get(..3) {
+            Some("[x]" | "[X]") => (true, line[3..].trim_start()),
+            Some("[ ]") => (false, line[3..].trim_start()),
+            _ => (false, line),
+        };
+        let (id, label) = match rest
+            .strip_prefix("id:")
+            .and_then(|rest| rest.split_once(' '))
+            .and_then(|(id, label)| Some((id.parse::<u64>().ok()?, label)))
+        {
+            Some((id, label)) => (Some(id), label.trim()),
+            None => (None, rest),
+        };
+        if label.is_empty() {
+            continue;
         }
+
+        // An ID that is given twice only keeps the item for its first line
+        let existing = id.and_then(|id| remaining.iter().position(|item| item.id == id));
+        let mut item = match existing {
+            Some(i) => remaining.remove(i),
+            None => Todo::new(0, label),
+        };
+        item.label = label.to_string();
+        // Checking or unchecking the item resets its progress, so it is left alone otherwise
+        if item.complete != complete {
+            item.set_complete(complete);
+        }
+        edited_list.restore(item);
     }
 
-    write_data(data, data_path);
+    *list = edited_list;
+    remaining
 }
 
-/// Uncheck items in the todo list.
-fn uncheck_items(data: &mut Vec<Todo>, params: Vec<String>, data_path: &String) {
-    if params.len() == 0 {
-        eprintln!("ERROR: Invalid use of `uncheck`. See `todo help` for options");
-        process::exit(1);
-    }
-    if params[0] == "all" {
-        for item in data.iter_mut() {
-            item.complete = false;
+/// Make sure each label given to select items matches exactly one item, asking which item was
+/// meant when several match (or whether all of them were), and that some item is selected.
+/// Positions and IDs that don't exist are skipped with a warning, or fail the command if strict.
+fn disambiguate(
+    list: &TodoList,
+    selections: Vec<Selection>,
+    strict: bool,
+) -> Result<Vec<Selection>, TodoError> {
+    let mut resolved = Vec::new();
+    for selection in selections {
+        let Selection::Label(text) = &selection else {
+            resolved.push(selection);
+            continue;
+        };
+
+        let matches = list.select(std::slice::from_ref(&selection));
+        match matches.as_slice() {
+            [] => return Err(TodoError::NoMatch(format!("No item matches \"{text}\"."))),
+            [i] => resolved.push(Selection::Item(ItemRef::Position(i + 1))),
+            _ => {
+                println!("Several items match \"{text}\":");
+                for &i in &matches {
+                    println!("  {}: {}", i + 1, list.items()[i].label);
+                }
+
+                let answer = prompt("Which one? (its position, or \"all\"): ")?;
+                let pick = answer.as_deref().map(str::trim);
+                match pick.and_then(|pick| pick.parse::<usize>().ok()) {
+                    Some(pos) if pos >= 1 && matches.contains(&(pos - 1)) => {
+                        resolved.push(Selection::Item(ItemRef::Position(pos)));
+                    }
+                    _ if pick == Some("all") => resolved.push(selection),
+                    _ => {
+                        return Err(TodoError::Usage(format!(
+                            "Several items match \"{text}\". Give the position or ID of one instead."
+                        )));
+                    }
+                }
+            }
         }
-        write_data(data, data_path);
-        return;
     }
 
-    let positions: Vec<usize> = params
+    let missing: Vec<String> = resolved
         .iter()
-        .map(|s| s.parse::<usize>().unwrap_or_else(|err| {
-            eprintln!("ERROR: Cannot convert position string \"{s}\" into a valid position value: {err}");
-            process::exit(1);
-        })).collect();
+        .filter_map(|selection| match *selection {
+            Selection::Item(item) if list.resolve(item).is_none() => Some(item.to_string()),
+            Selection::Range(start, end) if end > list.len() => {
+                let start = start.max(list.len() + 1);
+                Some(if start == end {
+                    start.to_string()
+                } else {
+                    format!("{start}-{end}")
+                })
+            }
+            _ => None,
+        })
+        .collect();
+    if strict && !missing.is_empty() {
+        return Err(TodoError::NoMatch(format!(
+            "These items don't exist: {}. Nothing was changed.",
+            missing.join(", ")
+        )));
+    }
+    if list.select(&resolved).is_empty() {
+        let given: Vec<String> = resolved.iter().map(Selection::to_string).collect();
+        return Err(TodoError::NoMatch(format!(
+            "No items match {}.",
+            given.join(", ")
+        )));
+    }
+    if !missing.is_empty() {
+        eprintln!(
+            "WARNING: These items don't exist, and were skipped: {}.",
+            missing.join(", ")
+        );
+    }
+    Ok(resolved)
+}
 
-    // Out-of-bound positions are ignored
-    for pos in positions {
-        if pos <= data.len() {
-            data[pos - 1].complete = false;
+/// Walk through the pending items that aren't snoozed or deferred, asking what to do with each.
+/// Nothing is saved until the end, or until the review is stopped early. Returns the removed items.
+fn review(list: &mut TodoList) -> Result<Vec<Todo>, TodoError> {
+    let today = dates::today();
+    let ids: Vec<u64> = list
+        .items()
+        .iter()
+        .filter(|item| !item.complete && !item.is_hidden(today))
+        .map(|item| item.id)
+        .collect();
+    if ids.is_empty() {
+        println!("There are no pending items to review.");
+        return Ok(Vec::new());
+    }
+
+    let (mut completed, mut edited, mut snoozed) = (0, 0, 0);
+    let mut removed: Vec<Selection> = Vec::new();
+    'items: for (n, &id) in ids.iter().enumerate() {
+        let item = ItemRef::Id(id);
+        let Some(i) = list.resolve(item) else {
+            continue;
+        };
+        println!("\n{}", format!("Item {} of {}", n + 1, ids.len()).dimmed());
+        show_item(i, &list.items()[i]);
+
+        loop {
+            let question = "[k]eep, [c]omplete, [e]dit, [s]nooze, [d]elete, or [q]uit? ";
+            let Some(answer) = prompt(question)? else {
+                break 'items;
+            };
+            match answer.trim() {
+                "" | "k" | "keep" => {}
+                "c" | "complete" => {
+                    list.set_complete(&[Selection::Item(item)], true);
+                    completed += 1;
+                }
+                "e" | "edit" => match prompt("New label (leave empty to keep it): ")? {
+                    Some(label) if !label.trim().is_empty() => {
+                        if let Some(todo) = list.get_mut(item) {
+                            todo.label = label.trim().to_string();
+                        }
+                        edited += 1;
+                    }
+                    _ => continue,
+                },
+                "s" | "snooze" => {
+                    let Some(until) = prompt("Snooze until (i.e., \"monday\" or \"3d\"): ")? else {
+                        continue;
+                    };
+                    match dates::parse_date(until.trim(), today) {
+                        Ok(date) => {
+                            if let Some(todo) = list.get_mut(item) {
+                                todo.hidden_until = Some(date);
+                            }
+                            snoozed += 1;
+                        }
+                        Err(err) => {
+                            println!("{err}");
+                            continue;
+                        }
+                    }
+                }
+                "d" | "delete" => removed.push(Selection::Item(item)),
+                "q" | "quit" => break 'items,
+                _ => continue,
+            }
+            break;
         }
     }
 
-    write_data(data, data_path);
+    let removed = list.remove(&removed);
+    println!(
+        "\nCompleted {completed}, edited {edited}, snoozed {snoozed}, and removed {} item(s).",
+        removed.len()
+    );
+    Ok(removed)
 }
 
-/// Sort items (by default the completed items will be listed last).
-/// TODO: implement param options for sorting (i.e., completed first or completed last)
-fn sort_items(data: &mut Vec<Todo>, _params: Vec<String>, data_path: &String) {
-    data.sort_by_key(|item| item.complete);
-    write_data(data, data_path);
-}
+/// Ask whether to go ahead with a command that removes many items, unless asked not to.
+/// Returns whether to go ahead, which is not the case when stdin is closed.
+fn confirm(question: &str, skip: bool) -> Result<bool, TodoError> {
+    if skip {
+        return Ok(true);
+    }
 
-/// Print the todo list
-fn print_list(data: &Vec<Todo>) {
-    if data.len() == 0 {
-        println!("Nothing to do!\n\nRun `todo help` for help.");
-        return;
+    let answer = prompt(&format!("{question} [y/N] "))?;
+    let yes = matches!(answer.as_deref().map(str::trim), Some("y" | "Y" | "yes"));
+    if !yes {
+        println!("Nothing was changed. Pass --yes to skip this question.");
     }
+    Ok(yes)
+}
 
-    for (i, item) in data.iter().enumerate() {
-        println!(
-            "{}",
-            if item.complete {
-                format!("☑ {}: {}", i + 1, item.label).green()
-            } else {
-                format!("☐ {}: {}", i + 1, item.label).white()
-            }
-        );
+/// Ask for a line of input, returning None if stdin is closed.
+fn prompt(text: &str) -> Result<Option<String>, TodoError> {
+    print!("{text}");
+    io::stdout().flush().expect("Failed to flush stdout");
+
+    let mut buffer = String::new();
+    let read = io::stdin()
+        .read_line(&mut buffer)
+        .map_err(|err| TodoError::io("Could not read user input", err))?;
+    if read == 0 {
+        println!();
+        return Ok(None);
     }
+    Ok(Some(buffer.trim_end().to_string()))
 }
 
-/// Write todo data to disk
-fn write_data(data: &Vec<Todo>, data_path: &String) {
-    let mut buf = String::new();
-    for item in data {
-        let item_serialized = serde_json::to_string(item).unwrap_or_else(|err| {
-            eprintln!("ERROR: Could not serialize the todo item into JSON format: {err}");
-            process::exit(1);
-        });
-        buf.push_str(&item_serialized);
-        buf.push('\n');
+/// Ask for the labels of items to add, one per line, until an empty line or the end of input.
+/// Labels piped to stdin are read the same way, without asking.
+fn prompt_items() -> Result<Vec<String>, TodoError> {
+    if !io::stdin().is_terminal() {
+        let text = io::read_to_string(io::stdin())
+            .map_err(|err| TodoError::io("Could not read the items from stdin", err))?;
+        return Ok(text
+            .lines()
+            .map(str::trim)
+            .take_while(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect());
     }
 
-    fs::write(data_path, buf).unwrap_or_else(|err| {
-        eprintln!("ERROR: Could not write to the data file: {err}");
-        process::exit(1);
-    });
+    println!("Enter the items to add, one per line. An empty line (or Ctrl-D) finishes.");
+    let mut items: Vec<String> = Vec::new();
+    while let Some(line) = prompt(&format!("{}. ", items.len() + 1))? {
+        let line = line.trim();
+        if line.is_empty() {
+            break;
+        }
+        items.push(line.to_string());
+    }
+    Ok(items)
 }
 
-/// Print the help information
-fn show_help() {
-    println!("
-add <items...>
-        Add item(s) to the todo list
+/// Print the names of all lists and how many items they have, marking the list in use.
+fn print_lists(current: &str) -> Result<(), TodoError> {
+    let mut names = TodoStore::list_names()?;
+    if !names.iter().any(|name| name == current) {
+        names.push(current.to_string());
+    }
 
-edit <item_positions...>
-        Edit item(s) in the todo list
+    for name in names {
+        let list = TodoStore::open_list(&name)?.load()?;
+        let done = list.items().iter().filter(|item| item.complete).count();
+        let line = format!("{name} ({done}/{} done)", list.len());
 
-list
-        Print the todo list. Use the numeric positions listed for commands with <item_positions...> parameters
+        if name == current {
+            println!("{}", format!("* {line}").bold());
+        } else {
+            println!("  {line}");
+        }
+    }
 
-remove <item_positions...> | \"all\" | \"checked\" | \"completed\"
-        Remove item(s) from the todo list
+    Ok(())
+}
 
-clear
-        Clears all items from the todo list (equivalent to \"remove all\")
+/// Write the current todo list and every named list again, encrypted or not.
+fn rewrite_lists(current: &TodoStore, encrypt: bool) -> Result<(), TodoError> {
+    TodoStore::new(current.path())
+        .encrypted(encrypt)
+        .rewrite()?;
 
-check <item_positions...> | \"all\" 
-        Mark item(s) as completed
+    for name in TodoStore::list_names()? {
+        let store = TodoStore::open_list(&name)?.encrypted(encrypt);
+        if store.path() == current.path() || !store.path().exists() {
+            continue;
+        }
+        let _lock = store.lock()?;
+        store.rewrite()?;
+    }
+    Ok(())
+}
 
-uncheck <item_positions...> | \"all\" 
-        Mark item(s) as incomplete
+/// Add items to another list the way a command run on that list would: the list is locked
+/// while it changes, and the change is recorded in its history, committed, and passed to the
+/// hooks. Returns the IDs of the items in that list.
+fn push_to_list(settings: &Settings, name: &str, items: Vec<Todo>) -> Result<Vec<u64>, TodoError> {
+    let store = TodoStore::open_list(name)?
+        .encrypted(settings.storage.encryption)
+        .backups(settings.storage.backups);
+    let _lock = store.lock()?;
+    let mut list = store.load()?;
+    let before = list.items().to_vec();
+    // The IDs of the items they wait for mean nothing in the other list
+    let ids = items
+        .into_iter()
+        .map(|item| {
+            list.push(Todo {
+                depends_on: Vec::new(),
+                ..item
+            })
+        })
+        .collect();
+
+    store.save(&list)?;
+    record_history(&store, &before, &list)?;
+    auto_commit(settings, &store)?;
+    hooks::run(&settings.hooks, command_line(), &before, list.items());
+    Ok(ids)
+}
+
+/// A list name, which is also a tag, for the name of a list in another app, i.e.,
+/// "groceries-home" for "Groceries & Home".
+fn list_slug(name: &str) -> String {
+    name.to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
 
-sort 
-        Sort items such that completed items appear last
+/// Find the (zero-based, sorted) indices of the items to restore from the archive or trash, given
+/// the IDs of the items there. The most recently added item is used if IDs were reused.
+/// Returns the first item that doesn't exist as the error.
+fn restore_indices(items: &[ItemRef], ids: &[u64]) -> Result<Vec<usize>, ItemRef> {
+    let mut indices: Vec<usize> = Vec::new();
+    for &item in items {
+        let index = match item {
+            ItemRef::Position(pos) => (pos <= ids.len()).then(|| pos - 1),
+            ItemRef::Id(id) => ids.iter().rposition(|&other| other == id),
+        };
+        match index {
+            Some(i) if !indices.contains(&i) => indices.push(i),
+            Some(_) => {}
+            None => return Err(item),
+        }
+    }
+    indices.sort();
+    Ok(indices)
+}
 
-set(?) <setting> <option>
-        Change config setting to have value <option>
+/// Commit the changes to the data directory when the "git-commit" setting is on, with the command
+/// line as the message. Project lists and files given with --file aren't in the data directory.
+fn auto_commit(settings: &Settings, store: &TodoStore) -> Result<(), TodoError> {
+    if !settings.sync.git_commit {
+        return Ok(());
+    }
+    let dir = store::data_dir()?;
+    if !store.path().starts_with(&dir) {
+        return Ok(());
+    }
 
-Any parameters with <...> signify that you can use multiple space-separated parameters.
-Any action marked with a (?) has further documentation (i.e, run `todo set help`)");
+    git::commit(&dir, &command_line())
 }
 
-/// Extract settings from config file.
-/// If a config doesn't exist, make one.
-fn extract_settings() -> Settings {
-    let mut config_path = dirs::config_dir().unwrap_or_else(|| {
-        eprintln!("ERROR: Could not find config directory.");
-        process::exit(1);
-    });
+/// Add what a command changed to the history of the todo list, if it changed anything, and the
+/// items it checked to the done log.
+fn record_history(store: &TodoStore, before: &[Todo], after: &TodoList) -> Result<(), TodoError> {
+    let checked: Vec<DoneEntry> = after
+        .items()
+        .iter()
+        .filter(|item| item.complete && before.iter().any(|old| old.id == item.id && !old.complete))
+        .map(DoneEntry::new)
+        .collect();
+    store.done_log().append(checked)?;
+
+    match HistoryEntry::new(command_line(), before, after.items()) {
+        Some(entry) => store.history().append(entry),
+        None => Ok(()),
+    }
+}
 
-    config_path.push("todo-app");
+/// The command line the app was run with, i.e., "todo check 2".
+fn command_line() -> String {
+    let args: Vec<String> = env::args().skip(1).collect();
+    format!("todo {}", args.join(" ")).trim_end().to_string()
+}
 
-    fs::create_dir_all(&config_path).unwrap_or_else(|err| {
-        eprintln!("ERROR: Could not create config file: {err}");
-        process::exit(1);
-    });
+/// Turn colored output on or off. With the "auto" setting, output is only colored in a terminal,
+/// and never when the NO_COLOR environment variable is set (see https://no-color.org).
+fn set_color(no_color: bool, setting: ColorMode) {
+    let color = match setting {
+        _ if no_color => false,
+        ColorMode::Always => true,
+        ColorMode::Never => false,
+        ColorMode::Auto => {
+            io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+        }
+    };
+    colored::control::set_override(color);
+}
 
-    config_path.push("settings.json");
+/// Warn when a command scheduled more work for today than fits in the capacity, i.e., by adding
+/// an estimate to an item due today.
+fn warn_capacity(before: &[Todo], after: &[Todo], capacity: Estimate) {
+    let today = dates::today();
+    let scheduled = estimate::scheduled_today(after, today);
+    if scheduled.exceeds(capacity) && scheduled != estimate::scheduled_today(before, today) {
+        eprintln!(
+            "WARNING: {} of work is scheduled for today, more than the capacity of {capacity}.",
+            scheduled.in_unit_of(capacity)
+        );
+    }
+}
 
-    if config_path.exists() {
-        let settings_str = fs::read_to_string(config_path).unwrap();
-        let settings: Settings = serde_json::from_str(&settings_str).unwrap_or_else(|err| {
-            eprintln!("ERROR: Could not parse settings file: {err}");
-            process::exit(1);
-        });
-        return settings;
+/// Create an empty project data file in the current directory.
+fn init_local() -> Result<(), TodoError> {
+    let path = PathBuf::from(store::LOCAL_FILE_NAME);
+    if path.exists() {
+        return Err(TodoError::Usage(format!(
+            "There already is a todo list in this directory ({}).",
+            store::LOCAL_FILE_NAME
+        )));
     }
 
-    let settings = Settings {
-        silent: String::from("off"),
+    store::check_writable()?;
+    store::write_atomic(&path, "")
+        .map_err(|err| TodoError::io("Could not create the data file", err))?;
+    println!(
+        "Created {}. Commands run in this directory (and its subdirectories) now use it.",
+        store::LOCAL_FILE_NAME
+    );
+    Ok(())
+}
+
+/// Change a setting and write it to disk, or show help for the settings.
+fn set_setting(
+    settings: &mut Settings,
+    setting: Option<String>,
+    value: Option<String>,
+) -> Result<(), TodoError> {
+    let (name, value) = match (setting, value) {
+        (None, _) => {
+            print_setting_help();
+            return Ok(());
+        }
+        (Some(name), _) if name == "help" => {
+            print_setting_help();
+            return Ok(());
+        }
+        (Some(name), Some(value)) => (name, value),
+        (Some(_), None) => {
+            return Err(TodoError::Usage(
+                "Parameter format is incorrect. See `todo set help` for information.\nUsage: todo set <setting> <value>"
+                    .to_string(),
+            ));
+        }
     };
-    write_settings(&config_path, &settings);
-    settings
+
+    settings.set(&name, &value)?;
+    settings.save()?;
+
+    println!("Successfully changed setting \"{name}\" to \"{value}\".");
+    Ok(())
 }
 
-fn set_setting(settings: &mut Settings, params: Vec<String>) {
-    let setting_choices = vec![(
-        "silent",
-        vec![String::from("on"), String::from("off")],
-        "Don't print the todo list after each mutation command (Default = off)",
-    )];
+/// Replace an alias with the command it stands for, keeping the options given before it and the
+/// arguments given after it. `argv` is the whole command line, and `args` are the alias and its
+/// arguments at the end of it.
+fn expand_alias(settings: &Settings, argv: &[OsString], args: &[String]) -> Result<Cli, TodoError> {
+    let (name, rest) = args.split_first().expect("clap passes the alias's name");
+    let Some(expansion) = settings.aliases.get(name) else {
+        return Err(TodoError::Usage(format!(
+            "There is no command or alias \"{name}\". Run `todo help` for the commands, or `todo alias` for the aliases."
+        )));
+    };
 
-    if params.len() >= 1 && params[0] == "help" {
-        print_setting_help(setting_choices);
-        return;
+    let before = &argv[..argv.len() - args.len()];
+    let expanded = before
+        .iter()
+        .cloned()
+        .chain(split_words(expansion).into_iter().map(OsString::from))
+        .chain(rest.iter().map(OsString::from));
+    let cli = Cli::try_parse_from(expanded).map_err(|err| {
+        // Help and the version are printed as usual, by main
+        if !err.use_stderr() {
+            return TodoError::Help(err);
+        }
+        TodoError::Usage(format!(
+            "Invalid command for the alias \"{name}\" (\"{expansion}\"): {}",
+            clap_message(&err)
+        ))
+    })?;
+
+    // Aliases can't stand for other aliases, which could go on forever
+    if let Some(Command::External(_)) = cli.command {
+        return Err(TodoError::Usage(format!(
+            "The alias \"{name}\" doesn't stand for a command: \"{expansion}\". Fix it with `todo alias {name} <COMMAND>`."
+        )));
     }
+    Ok(cli)
+}
 
-    let mut setting_map = HashMap::from([("silent", &mut settings.silent)]);
+/// The first line of an error parsing a command line, without clap's "error: " prefix.
+fn clap_message(err: &clap::Error) -> String {
+    let message = err.to_string();
+    let first = message.lines().next().unwrap_or_default();
+    first.trim_start_matches("error: ").to_string()
+}
 
-    if params.len() != 2 {
-        eprintln!(
-            "ERROR: Parameter format is incorrect. See `todo set help` for information.\nUsage: todo set <setting> <value>"
-        );
-        process::exit(1);
+/// Whether a command line only fails to parse because arguments are missing at its end, which
+/// can still be given after an alias, i.e., `todo alias t list --tag` for `todo t work`.
+fn is_incomplete(err: &clap::Error) -> bool {
+    match err.kind() {
+        ErrorKind::MissingRequiredArgument
+        | ErrorKind::MissingSubcommand
+        | ErrorKind::DisplayHelpOnMissingArgumentOrSubcommand => true,
+        // A missing value is an empty invalid value
+        ErrorKind::InvalidValue => matches!(
+            err.get(ContextKind::InvalidValue),
+            Some(ContextValue::String(value)) if value.is_empty()
+        ),
+        _ => false,
     }
+}
 
-    let mut success = false;
-
-    for opt in setting_choices {
-        if opt.0 == params[0] {
-            if opt.1.contains(&params[1]) {
-                let setting = setting_map.get_mut(opt.0).unwrap();
-                setting.clear();
-                setting.push_str(&params[1]);
-                success = true;
+/// Split a command into words at whitespace, keeping text in double quotes together.
+fn split_words(command: &str) -> Vec<String> {
+    let mut words: Vec<String> = Vec::new();
+    let mut word: Option<String> = None;
+    let mut quoted = false;
+    for c in command.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                word.get_or_insert_with(String::new);
             }
+            c if c.is_whitespace() && !quoted => words.extend(word.take()),
+            c => word.get_or_insert_with(String::new).push(c),
         }
     }
+    words.extend(word);
+    words
+}
 
-    if !success {
-        eprintln!(
-            "ERROR: Failed to change setting \"{}\" to option \"{}\", setting or option doesn't exist.",
-            params[0], params[1]
-        );
-        process::exit(1);
+/// List the aliases, or add, change, or remove one.
+fn set_alias(
+    settings: &mut Settings,
+    name: Option<String>,
+    command: Vec<String>,
+    remove: bool,
+) -> Result<(), TodoError> {
+    let Some(name) = name else {
+        if settings.aliases.is_empty() {
+            println!("There are no aliases. Add one with `todo alias <NAME> <COMMAND>`.");
+        }
+        for (name, expansion) in &settings.aliases {
+            println!("{name} = {expansion}");
+        }
+        return Ok(());
+    };
+
+    if remove {
+        if settings.aliases.remove(&name).is_none() {
+            return Err(TodoError::Usage(format!("There is no alias \"{name}\".")));
+        }
+        settings.save()?;
+        println!("Removed the alias \"{name}\".");
+        return Ok(());
     }
 
-    let mut settings_path = dirs::config_dir().unwrap();
-    settings_path.push("todo-app/settings.json");
-    write_settings(&settings_path, settings);
+    let Some(first) = command.first() else {
+        return match settings.aliases.get(&name) {
+            Some(expansion) => {
+                println!("{name} = {expansion}");
+                Ok(())
+            }
+            None => Err(TodoError::Usage(format!("There is no alias \"{name}\"."))),
+        };
+    };
+    if Cli::command().find_subcommand(&name).is_some() {
+        return Err(TodoError::Usage(format!(
+            "\"{name}\" is already a command, so it can't be an alias."
+        )));
+    }
+    if Cli::command().find_subcommand(first).is_none() {
+        return Err(TodoError::Usage(format!(
+            "\"{first}\" isn't a command. Aliases have to stand for a command, i.e., `todo alias work list --tag work`."
+        )));
+    }
 
-    println!(
-        "Successfully changed setting \"{}\" to \"{}\".",
-        params[0], params[1]
-    );
+    // Arguments with spaces are quoted, so they are split the same way again
+    let words: Vec<String> = command
+        .iter()
+        .map(|word| {
+            if word.is_empty() || word.contains(char::is_whitespace) {
+                format!("\"{word}\"")
+            } else {
+                word.clone()
+            }
+        })
+        .collect();
+    let expansion = words.join(" ");
+    let parsed =
+        Cli::try_parse_from(std::iter::once("todo".to_string()).chain(split_words(&expansion)));
+    if let Err(err) = parsed
+        && err.use_stderr()
+        && !is_incomplete(&err)
+    {
+        return Err(TodoError::Usage(format!(
+            "\"{expansion}\" isn't a valid command: {}",
+            clap_message(&err)
+        )));
+    }
+    settings.aliases.insert(name.clone(), expansion.clone());
+    settings.save()?;
+    println!("\"todo {name}\" now runs \"todo {expansion}\".");
+    Ok(())
 }
 
 /// Show help for settings
-fn print_setting_help(setting_choices: Vec<(&'static str, Vec<String>, &'static str)>) {
+fn print_setting_help() {
     println!(
         "Change settings with \"todo set <setting> <option>\".
 Commands:"
     );
-    for setting in setting_choices {
-        print!("\t{} <", setting.0);
-        for (i, opt) in setting.1.iter().enumerate() {
-            print!(
-                "{}{}",
-                opt,
-                if i < setting.1.len() - 1 {
-                    " | ".to_string()
-                } else {
-                    format!(">\t{}\n", setting.2)
-                }
-            );
-        }
+    for (setting, options, description) in SETTING_CHOICES {
+        let options = if options.is_empty() {
+            "value".to_string()
+        } else {
+            options.join(" | ")
+        };
+        println!("\t{setting} <{options}>\t{description}");
+    }
+    println!("Fields for \"template\" (dates take a format, i.e., {{due:%d.%m.}}):");
+    for (field, description) in template::FIELDS {
+        println!("\t{{{field}}}\t{description}");
     }
 }
 
-/// Edit an item
-fn edit_item(data: &mut Vec<Todo>, params: Vec<String>, data_path: &String) {
-    if params.len() == 0 {
-        eprintln!("ERROR: Invalid use of `edit`. See `todo help` for options");
-        process::exit(1);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use todo::Status;
+
+    /// A session for a command on a todo list, whose files are never written.
+    fn session(list: TodoList) -> Session {
+        let store = TodoStore::new(env::temp_dir().join("todo-test-session.dat"));
+        Session {
+            settings: Settings::default(),
+            trash: store.trash(),
+            store,
+            lock: None,
+            before: list.items().to_vec(),
+            list,
+            list_name: store::DEFAULT_LIST.to_string(),
+            quiet: true,
+            skip_confirm: true,
+            strict: false,
+            json: false,
+            table: false,
+            theme: Theme::default(),
+            checkbox: Checkbox::default(),
+            template: None,
+            sort: None,
+            project: None,
+            context: None,
+            list_options: ListOptions::default(),
+            trashed: Vec::new(),
+            archived: Vec::new(),
+            trash_items: None,
+            archive_items: None,
+            reports: Vec::new(),
+            save: false,
+        }
     }
 
-    let positions: Vec<usize> = params
-        .iter()
-        .map(|s| s.parse::<usize>().unwrap_or_else(|err| {
-            eprintln!("ERROR: Cannot convert position string \"{s}\" into a valid position value: {err}");
-            process::exit(1);
-        })).collect();
-
-    for pos in positions {
-        if pos <= data.len() {
-            let original = &data[pos - 1];
-            println!("Original: {}", original.label);
-
-            print!("New: ");
-            io::stdout().flush().expect("Failed to flush stdout");
-
-            let mut buffer = String::new();
-            let stdin = io::stdin();
-            stdin.read_line(&mut buffer).unwrap_or_else(|err| {
-                eprintln!("ERROR: Could not read user input: {err}");
-                process::exit(1);
-            });
-
-            data[pos - 1].label = buffer.trim_end().to_string();
+    #[test]
+    fn commands_leave_what_they_did_to_be_reported_after_saving() {
+        let mut list = TodoList::new();
+        list.add("buy milk");
+        list.add("buy milk");
+        list.add("call mom");
+        list.set_complete(&[Selection::Item(ItemRef::Position(3))], true);
+        let mut session = session(list);
+
+        let commands = [
+            Command::Archive { action: None },
+            Command::Dedupe { ignore_case: false },
+            Command::Replace {
+                from: "milk".to_string(),
+                to: "eggs".to_string(),
+                regex: false,
+            },
+        ];
+        for command in commands {
+            execute(command, &mut session).unwrap();
         }
+        assert_eq!(
+            session.reports,
+            [
+                "Archived 1 item(s).",
+                "Merged \"buy milk\" (id:2) into id:1.",
+                "Removed 1 duplicate(s).",
+                "Changed 1 label(s)."
+            ]
+        );
+        assert!(session.save);
     }
 
-    write_data(data, data_path);
-}
+    #[test]
+    fn aliases_leave_help_and_errors_to_the_caller() {
+        let mut settings = Settings::default();
+        settings
+            .aliases
+            .insert("work".to_string(), "list --tag work".to_string());
+        let argv = |args: &[&str]| -> Vec<OsString> {
+            std::iter::once("todo")
+                .chain(args.iter().copied())
+                .map(OsString::from)
+                .collect()
+        };
+        let expand = |args: &[&str]| {
+            let words: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+            expand_alias(&settings, &argv(args), &words)
+        };
+
+        assert!(matches!(
+            expand(&["work"]),
+            Ok(Cli {
+                command: Some(Command::List { .. }),
+                ..
+            })
+        ));
+        assert!(matches!(
+            expand(&["work", "--help"]),
+            Err(TodoError::Help(_))
+        ));
+        assert!(matches!(
+            expand(&["work", "--bogus"]),
+            Err(TodoError::Usage(_))
+        ));
+    }
 
-/// Write settings to disk.
-fn write_settings(path: &PathBuf, settings: &Settings) {
-    let settings_str = serde_json::to_string(&settings).unwrap();
-    fs::write(path, settings_str).unwrap_or_else(|err| {
-        eprintln!("ERROR: Could not create the config file: {err}");
-        process::exit(1);
-    });
+    #[test]
+    fn editing_all_items_keeps_their_progress() {
+        let mut list = TodoList::new();
+        list.add("a");
+        list.add("b");
+        list.add("c");
+        list.set_status(&[Selection::Item(ItemRef::Position(1))], Status::InProgress);
+        list.set_status(&[Selection::Item(ItemRef::Position(2))], Status::Waiting);
+
+        let removed = apply_edits(&mut list, "[ ] id:2 b\n[ ] id:1 a\n[x] id:3 c\n");
+        assert!(removed.is_empty());
+        let states: Vec<(&str, Status)> = list
+            .items()
+            .iter()
+            .map(|item| (item.label.as_str(), item.state()))
+            .collect();
+        assert_eq!(
+            states,
+            [
+                ("b", Status::Waiting),
+                ("a", Status::InProgress),
+                ("c", Status::Done)
+            ]
+        );
+    }
+
+    #[test]
+    fn batch_lines_cant_have_global_flags() {
+        let settings = Settings::default();
+        assert!(matches!(
+            batch_command("add c", &settings),
+            Ok(Command::Add { .. })
+        ));
+        for line in ["--list work add c", "add c --list work", "todo -q check 1"] {
+            assert!(
+                matches!(batch_command(line, &settings), Err(TodoError::Usage(_))),
+                "{line}"
+            );
+        }
+    }
 }
@@ -0,0 +1,914 @@
+use crate::dates;
+use crate::todo::{Status, Todo, WorkInterval};
+use chrono::{DateTime, NaiveDate, TimeDelta, Utc};
+use clap::{Args, ValueEnum};
+use regex::{Regex, Replacer};
+use serde::{Deserialize, Serialize};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+
+/// The items of a todo list, in the order they are listed.
+#[derive(Default, Clone)]
+pub struct TodoList {
+    items: Vec<Todo>,
+    /// The lowest ID a new item can get, which is kept past removing the items with the highest
+    /// IDs so that their IDs aren't given out again.
+    next_id: u64,
+}
+
+/// A reference to a single item, either by its position in the todo list or by its stable ID.
+#[derive(Clone, Copy, Debug)]
+pub enum ItemRef {
+    /// A one-based position, as shown in `todo list`.
+    Position(usize),
+    /// A stable item ID, given as "id:<id>".
+    Id(u64),
+}
+
+/// A selection of items for commands that accept several items at once.
+#[derive(Clone, Debug)]
+pub enum Selection {
+    /// Every item in the todo list.
+    All,
+    /// Every completed item in the todo list.
+    Checked,
+    /// The items between two one-based positions (inclusive), given as "<start>-<end>".
+    Range(usize, usize),
+    /// A single item.
+    Item(ItemRef),
+    /// Every item whose label contains the text (case-insensitive). Anything that doesn't start
+    /// with a digit or "id:" is a label.
+    Label(String),
+}
+
+/// Conditions an item must meet to be listed. Every given condition must hold.
+#[derive(Args, Debug, Default)]
+pub struct Filter {
+    /// Only list incomplete items
+    #[arg(long, conflicts_with = "done")]
+    pub pending: bool,
+    /// Only list completed items
+    #[arg(long)]
+    pub done: bool,
+    /// Only list items with this status: pending, in-progress, waiting, or done
+    #[arg(long, value_name = "STATUS")]
+    pub status: Option<Status>,
+    /// Only list items with this tag (can be given more than once)
+    #[arg(long = "tag", id = "tag", value_name = "TAG")]
+    pub tags: Vec<String>,
+    /// Only list items in this project (instead of the one chosen with `todo project`)
+    #[arg(long, value_name = "NAME")]
+    pub project: Option<String>,
+    /// Only list items in this context (instead of the one chosen with `todo context`)
+    #[arg(long, value_name = "@CONTEXT")]
+    pub context: Option<String>,
+    /// Only list items assigned to this person
+    #[arg(long, value_name = "NAME")]
+    pub assignee: Option<String>,
+    /// Only list items assigned to you (set with `todo set user <NAME>`)
+    #[arg(long, conflicts_with = "assignee")]
+    pub mine: bool,
+    /// Only list items due on or before this date (i.e., "today" or "friday")
+    #[arg(long, value_name = "DATE", value_parser = parse_date)]
+    pub due: Option<NaiveDate>,
+    /// Only list incomplete items whose due date has passed
+    #[arg(long)]
+    pub overdue: bool,
+    /// Only list items whose label contains this text (case-insensitive)
+    #[arg(long, value_name = "TEXT")]
+    pub search: Option<String>,
+    /// Only list items that don't depend on an incomplete item
+    #[arg(long)]
+    pub unblocked: bool,
+    /// Only list items planned for today with `todo plan`
+    #[arg(long)]
+    pub planned: bool,
+    /// Also list items that are snoozed or can't be started yet
+    #[arg(short, long)]
+    pub all: bool,
+}
+
+/// The orders the todo list can be sorted in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortMode {
+    /// Completed items appear last
+    Completed,
+    /// Items are ordered from high to low priority, and items without a priority last
+    Priority,
+    /// Items are ordered alphabetically by label, ignoring case
+    Alpha,
+    /// Items are ordered from oldest to newest
+    Created,
+    /// Items are ordered by due date, soonest first, and items without a due date last
+    Due,
+}
+
+/// The sections `todo list` can group items into.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum GroupBy {
+    /// One section per tag; items with several tags are listed in each of them
+    Tag,
+    /// One section per priority, from high to low
+    Priority,
+    /// One section per week of the due date (starting on the "week-start" setting), soonest first
+    Due,
+    /// One section per project
+    Project,
+}
+
+impl TodoList {
+    /// Create an empty todo list.
+    pub fn new() -> TodoList {
+        TodoList::default()
+    }
+
+    /// Create a todo list from existing items.
+    /// Items without an ID (i.e., from older data files) are given one, and completed items
+    /// without a completion time are considered completed now.
+    pub fn from_items(items: Vec<Todo>) -> TodoList {
+        let mut list = TodoList { items, next_id: 0 };
+
+        let now = Utc::now();
+        for item in list.items.iter_mut().filter(|item| item.complete) {
+            item.completed_at.get_or_insert(now);
+        }
+
+        let first_id = list.next_id();
+        let missing = list.items.iter_mut().filter(|item| item.id == 0);
+        for (id, item) in (first_id..).zip(missing) {
+            item.id = id;
+        }
+
+        list
+    }
+
+    /// Give new items IDs of at least the given one, i.e., the next ID stored with the todo list.
+    pub fn with_next_id(mut self, next_id: u64) -> TodoList {
+        self.next_id = self.next_id.max(next_id);
+        self
+    }
+
+    /// The items in the todo list.
+    pub fn items(&self) -> &[Todo] {
+        &self.items
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// The ID to use for a new item in the todo list. IDs are never given out twice, even after
+    /// the item that had one was removed.
+    pub fn next_id(&self) -> u64 {
+        let max_id = self.items.iter().map(|item| item.id).max().unwrap_or(0);
+        self.next_id.max(max_id + 1)
+    }
+
+    /// Add a new item to the end of the todo list, returning its ID.
+    pub fn add(&mut self, label: impl Into<String>) -> u64 {
+        self.push(Todo::new(0, label))
+    }
+
+    /// Add an existing item to the end of the todo list, keeping its ID unless another item
+    /// already has it. Returns the ID of the item.
+    pub fn restore(&mut self, item: Todo) -> u64 {
+        if item.id == 0 || self.items.iter().any(|other| other.id == item.id) {
+            return self.push(item);
+        }
+        let id = item.id;
+        self.next_id = self.next_id.max(id + 1);
+        self.items.push(item);
+        id
+    }
+
+    /// Add an existing item to the end of the todo list, giving it a new ID which is returned.
+    pub fn push(&mut self, mut item: Todo) -> u64 {
+        let id = self.next_id();
+        item.id = id;
+        self.next_id = id + 1;
+        self.items.push(item);
+        id
+    }
+
+    /// Find the (zero-based) index of an item in the todo list.
+    /// Out-of-bound positions and IDs that don't belong to any item resolve to None.
+    pub fn resolve(&self, item: ItemRef) -> Option<usize> {
+        match item {
+            ItemRef::Position(pos) => (pos >= 1 && pos <= self.items.len()).then(|| pos - 1),
+            ItemRef::Id(id) => self.items.iter().position(|item| item.id == id),
+        }
+    }
+
+    /// Find the (zero-based) indices of the selected items in the todo list, in the order given.
+    /// Items that don't exist are ignored, and each item is only included once.
+    pub fn select(&self, selections: &[Selection]) -> Vec<usize> {
+        let mut indices: Vec<usize> = Vec::new();
+        for selection in selections {
+            let selected: Vec<usize> = match selection {
+                Selection::All => (0..self.items.len()).collect(),
+                Selection::Checked => (0..self.items.len())
+                    .filter(|&i| self.items[i].complete)
+                    .collect(),
+                Selection::Range(start, end) => {
+                    (*start - 1..(*end).min(self.items.len())).collect()
+                }
+                Selection::Item(item) => self.resolve(*item).into_iter().collect(),
+                Selection::Label(text) => {
+                    let text = text.to_lowercase();
+                    (0..self.items.len())
+                        .filter(|&i| self.items[i].label.to_lowercase().contains(&text))
+                        .collect()
+                }
+            };
+            for i in selected {
+                if !indices.contains(&i) {
+                    indices.push(i);
+                }
+            }
+        }
+        indices
+    }
+
+    /// The items matching a predicate, alongside their (zero-based) positions in the todo list.
+    pub fn find(&self, predicate: impl Fn(&Todo) -> bool) -> Vec<(usize, &Todo)> {
+        self.items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| predicate(item))
+            .collect()
+    }
+
+    pub fn get(&self, item: ItemRef) -> Option<&Todo> {
+        self.resolve(item).map(|i| &self.items[i])
+    }
+
+    pub fn get_mut(&mut self, item: ItemRef) -> Option<&mut Todo> {
+        self.resolve(item).map(|i| &mut self.items[i])
+    }
+
+    /// Remove the selected items from the todo list, returning the removed items.
+    pub fn remove(&mut self, selections: &[Selection]) -> Vec<Todo> {
+        let mut indices = self.select(selections);
+
+        indices.sort();
+        indices.reverse();
+
+        let mut removed: Vec<Todo> = indices.into_iter().map(|i| self.items.remove(i)).collect();
+        removed.reverse();
+        removed
+    }
+
+    /// Find an item with the same label, ignoring surrounding whitespace (and optionally case),
+    /// returning its (zero-based) index.
+    pub fn find_duplicate(&self, label: &str, ignore_case: bool) -> Option<usize> {
+        let key = duplicate_key(label, ignore_case);
+        self.items
+            .iter()
+            .position(|item| duplicate_key(&item.label, ignore_case) == key)
+    }
+
+    /// Replace every match of a pattern in the labels of the items. Returns the (zero-based)
+    /// position and old label of each item whose label changed.
+    pub fn replace(
+        &mut self,
+        pattern: &Regex,
+        mut replacement: impl Replacer,
+    ) -> Vec<(usize, String)> {
+        let mut changed: Vec<(usize, String)> = Vec::new();
+        for (i, item) in self.items.iter_mut().enumerate() {
+            let label = pattern
+                .replace_all(&item.label, replacement.by_ref())
+                .into_owned();
+            if label != item.label {
+                changed.push((i, std::mem::replace(&mut item.label, label)));
+            }
+        }
+        changed
+    }
+
+    /// Remove the items whose label is the same as another item's, keeping the one that was
+    /// created first. Items are only the same when both are completed or both aren't, and they
+    /// repeat in the same way, so the next occurrence of a checked recurring item isn't one of its
+    /// duplicates. The tags of a removed item are added to the kept item, and so are its due
+    /// date, priority, and note if the kept item has none. Returns the ID of the kept item along
+    /// with each removed item.
+    pub fn dedupe(&mut self, ignore_case: bool) -> Vec<(u64, Todo)> {
+        let mut order: Vec<usize> = (0..self.items.len()).collect();
+        order.sort_by_key(|&i| self.items[i].created_at);
+
+        // The index of every duplicate, with the index of the item it is a duplicate of
+        let mut duplicates: Vec<(usize, usize)> = Vec::new();
+        let mut kept: Vec<usize> = Vec::new();
+        for i in order {
+            let item = &self.items[i];
+            let key = duplicate_key(&item.label, ignore_case);
+            match kept.iter().find(|&&k| {
+                let other = &self.items[k];
+                duplicate_key(&other.label, ignore_case) == key
+                    && other.complete == item.complete
+                    && other.recurrence == item.recurrence
+            }) {
+                Some(&k) => duplicates.push((i, k)),
+                None => kept.push(i),
+            }
+        }
+
+        for &(i, k) in &duplicates {
+            let duplicate = self.items[i].clone();
+            let item = &mut self.items[k];
+            for tag in &duplicate.tags {
+                item.add_tag(tag);
+            }
+            item.due = item.due.or(duplicate.due);
+            item.priority = item.priority.or(duplicate.priority);
+            item.note = item.note.take().or(duplicate.note);
+        }
+
+        // Removing from the back keeps the other indices valid
+        let mut duplicates: Vec<(usize, u64)> = duplicates
+            .into_iter()
+            .map(|(i, k)| (i, self.items[k].id))
+            .collect();
+        duplicates.sort_by_key(|&(i, _)| Reverse(i));
+        let mut merged: Vec<(u64, Todo)> = duplicates
+            .into_iter()
+            .map(|(i, kept)| (kept, self.items.remove(i)))
+            .collect();
+        merged.reverse();
+        merged
+    }
+
+    /// Merge the item at one (zero-based) index into the item at another, removing it. The kept
+    /// item gets both labels and notes, every tag, the earlier creation and due dates, the higher
+    /// priority, and the details that only the merged item has. Items that waited for the merged
+    /// item wait for the kept item instead. Returns the merged item.
+    pub fn merge(&mut self, keep: usize, other: usize) -> Todo {
+        let merged = self.items.remove(other);
+        let keep = if other < keep { keep - 1 } else { keep };
+
+        let item = &mut self.items[keep];
+        if duplicate_key(&item.label, true) != duplicate_key(&merged.label, true) {
+            item.label = format!("{} / {}", item.label, merged.label);
+        }
+        item.note = match (item.note.take(), merged.note.clone()) {
+            (Some(note), Some(other)) if note != other => Some(format!("{note}\n\n{other}")),
+            (note, other) => note.or(other),
+        };
+        for tag in &merged.tags {
+            item.add_tag(tag);
+        }
+        item.created_at = item.created_at.min(merged.created_at);
+        item.due = item.due.into_iter().chain(merged.due).min();
+        item.recurrence = item.recurrence.take().or(merged.recurrence.clone());
+        item.priority = item.priority.into_iter().chain(merged.priority).min();
+        item.project = item.project.take().or(merged.project.clone());
+        item.context = item.context.take().or(merged.context.clone());
+        item.assignee = item.assignee.take().or(merged.assignee.clone());
+        item.estimate = item.estimate.or(merged.estimate);
+        item.url = item.url.take().or(merged.url.clone());
+        item.pinned |= merged.pinned;
+        item.focused |= merged.focused;
+        // Listed as soon as either of them would be
+        item.hidden_until = item.hidden_until.min(merged.hidden_until);
+        item.start = item.start.min(merged.start);
+        if !merged.complete {
+            item.set_complete(false);
+        }
+        item.comments.extend(merged.comments.iter().cloned());
+        item.comments.sort_by_key(|comment| comment.at);
+        item.time_log.extend(merged.time_log.iter().cloned());
+        item.time_log.sort_by_key(|interval| interval.start);
+        item.pomodoros += merged.pomodoros;
+
+        let id = item.id;
+        for &blocker in &merged.depends_on {
+            if blocker != id && !item.depends_on.contains(&blocker) {
+                item.depends_on.push(blocker);
+            }
+        }
+        for other in &mut self.items {
+            if other.depends_on.contains(&merged.id) {
+                other.depends_on.retain(|&blocker| blocker != merged.id);
+                if other.id != id && !other.depends_on.contains(&id) {
+                    other.depends_on.push(id);
+                }
+            }
+        }
+
+        merged
+    }
+
+    /// Remove every item from the todo list.
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+
+    /// Mark the selected items as completed (or incomplete), returning how many were selected.
+    /// Checking a recurring item adds its next occurrence to the end of the list, which takes
+    /// over the recurrence.
+    pub fn set_complete(&mut self, selections: &[Selection], complete: bool) -> usize {
+        let indices = self.select(selections);
+        let today = dates::today();
+        for &i in &indices {
+            let item = &mut self.items[i];
+            if complete
+                && !item.complete
+                && let Some(next) = item.next_occurrence(today)
+            {
+                item.recurrence = None;
+                item.set_complete(true);
+                self.push(next);
+                continue;
+            }
+            item.set_complete(complete);
+        }
+        indices.len()
+    }
+
+    /// Move the selected items to a stage of their workflow, returning how many were selected.
+    /// Moving them to done checks them, like `set_complete`.
+    pub fn set_status(&mut self, selections: &[Selection], status: Status) -> usize {
+        if status == Status::Done {
+            return self.set_complete(selections, true);
+        }
+        let indices = self.select(selections);
+        for &i in &indices {
+            self.items[i].set_state(status);
+        }
+        indices.len()
+    }
+
+    /// Plan the items with the given IDs for a day, instead of the items planned for it before.
+    pub fn plan(&mut self, ids: &HashSet<u64>, day: NaiveDate) {
+        for item in &mut self.items {
+            if ids.contains(&item.id) {
+                item.planned = Some(day);
+            } else if item.planned == Some(day) {
+                item.planned = None;
+            }
+        }
+    }
+
+    /// Pin (or unpin) the selected items, returning how many were selected.
+    pub fn set_pinned(&mut self, selections: &[Selection], pinned: bool) -> usize {
+        let indices = self.select(selections);
+        for &i in &indices {
+            self.items[i].pinned = pinned;
+        }
+        indices.len()
+    }
+
+    /// Focus on (or stop focusing on) the selected items, returning how many were selected.
+    pub fn set_focused(&mut self, selections: &[Selection], focused: bool) -> usize {
+        let indices = self.select(selections);
+        for &i in &indices {
+            self.items[i].focused = focused;
+        }
+        indices.len()
+    }
+
+    /// Whether any item is focused on, so `todo list` only shows the focused items.
+    pub fn is_focused(&self) -> bool {
+        self.items.iter().any(|item| item.focused)
+    }
+
+    /// Move the selected items into a project, or out of any project. Returns how many items were
+    /// selected.
+    pub fn set_project(&mut self, selections: &[Selection], project: Option<&str>) -> usize {
+        let indices = self.select(selections);
+        for &i in &indices {
+            self.items[i].project = project.map(str::to_string);
+        }
+        indices.len()
+    }
+
+    /// Move the selected items into a context, or out of any context. Returns how many items were
+    /// selected.
+    pub fn set_context(&mut self, selections: &[Selection], context: Option<&str>) -> usize {
+        let indices = self.select(selections);
+        for &i in &indices {
+            self.items[i].context = context.map(str::to_string);
+        }
+        indices.len()
+    }
+
+    /// The projects of the items, in the order they first appear, with how many of their items
+    /// are completed and how many there are.
+    pub fn projects(&self) -> Vec<(&str, usize, usize)> {
+        let mut projects: Vec<(&str, usize, usize)> = Vec::new();
+        for item in &self.items {
+            let Some(project) = item.project.as_deref() else {
+                continue;
+            };
+            let i = match projects.iter().position(|(name, _, _)| *name == project) {
+                Some(i) => i,
+                None => {
+                    projects.push((project, 0, 0));
+                    projects.len() - 1
+                }
+            };
+            projects[i].1 += usize::from(item.complete);
+            projects[i].2 += 1;
+        }
+        projects
+    }
+
+    /// Whether an item depends on an item that is still incomplete. Dependencies on items that
+    /// were removed don't count.
+    pub fn is_blocked(&self, item: &Todo) -> bool {
+        item.depends_on.iter().any(|&id| {
+            self.items
+                .iter()
+                .any(|other| other.id == id && !other.complete)
+        })
+    }
+
+    /// The IDs of the items that are blocked.
+    pub fn blocked(&self) -> HashSet<u64> {
+        self.items
+            .iter()
+            .filter(|item| self.is_blocked(item))
+            .map(|item| item.id)
+            .collect()
+    }
+
+    /// Whether the item with ID `id` depends on the item with ID `other`, directly or through
+    /// other items.
+    pub fn depends_on(&self, id: u64, other: u64) -> bool {
+        let mut visited: HashSet<u64> = HashSet::new();
+        let mut stack = vec![id];
+        while let Some(id) = stack.pop() {
+            if !visited.insert(id) {
+                continue;
+            }
+            let Some(item) = self.items.iter().find(|item| item.id == id) else {
+                continue;
+            };
+            if item.depends_on.contains(&other) {
+                return true;
+            }
+            stack.extend(&item.depends_on);
+        }
+        false
+    }
+
+    /// Start a timer on the item at a (zero-based) index, stopping the running timer first.
+    /// Returns the index of the item whose timer was stopped and how long it ran, if any.
+    pub fn start_timer(&mut self, i: usize, now: DateTime<Utc>) -> Option<(usize, TimeDelta)> {
+        let stopped = self.stop_timer(now);
+        if let Some(item) = self.items.get_mut(i) {
+            item.time_log.push(WorkInterval {
+                start: now,
+                end: None,
+            });
+        }
+        stopped
+    }
+
+    /// Stop the running timer, returning the index of its item and how long it ran.
+    pub fn stop_timer(&mut self, now: DateTime<Utc>) -> Option<(usize, TimeDelta)> {
+        let i = self.items.iter().position(|item| item.is_timing())?;
+        let interval = self.items[i].time_log.last_mut()?;
+        interval.end = Some(now);
+        Some((i, now - interval.start))
+    }
+
+    /// Move the item at one (zero-based) index to another, shifting the items in between.
+    /// Indices past the end of the todo list move the item to the end.
+    pub fn move_item(&mut self, from: usize, to: usize) {
+        if from >= self.items.len() {
+            return;
+        }
+        let item = self.items.remove(from);
+        self.items.insert(to.min(self.items.len()), item);
+    }
+
+    /// Replace the item at a (zero-based) index with copies of it (see `Todo::duplicate`) that
+    /// have the given labels, in its place. Items that waited for it wait for every copy instead.
+    /// Returns the original item and the IDs of the copies.
+    pub fn split(&mut self, index: usize, labels: &[String]) -> (Todo, Vec<u64>) {
+        // The copies are given IDs while the original is still there, so its ID isn't reused
+        let original = self.items[index].clone();
+        let ids: Vec<u64> = labels
+            .iter()
+            .map(|label| {
+                self.push(Todo {
+                    label: label.clone(),
+                    ..original.duplicate()
+                })
+            })
+            .collect();
+        let copies = self.items.split_off(self.items.len() - ids.len());
+        self.items.splice(index..=index, copies);
+
+        for item in &mut self.items {
+            if item.depends_on.contains(&original.id) {
+                item.depends_on.retain(|&id| id != original.id);
+                item.depends_on.extend(&ids);
+            }
+        }
+        (original, ids)
+    }
+
+    /// Swap the items at two (zero-based) indices.
+    pub fn swap(&mut self, a: usize, b: usize) {
+        self.items.swap(a, b);
+    }
+
+    /// Sort the todo list, optionally in reverse. Sorting is stable, so items that compare equal
+    /// keep their order.
+    pub fn sort(&mut self, mode: SortMode, reverse: bool) {
+        self.items.sort_by(|a, b| mode.compare(a, b, reverse));
+    }
+}
+
+impl SortMode {
+    /// Compare two items in this order, or its reverse. Items without a priority or due date are
+    /// last either way.
+    pub fn compare(self, a: &Todo, b: &Todo, reverse: bool) -> Ordering {
+        let order = |ordering: Ordering| {
+            if reverse {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        };
+        match self {
+            SortMode::Completed => order(a.complete.cmp(&b.complete)),
+            SortMode::Priority => (a.priority.is_none().cmp(&b.priority.is_none()))
+                .then_with(|| order(a.priority.cmp(&b.priority))),
+            SortMode::Alpha => order(a.label.to_lowercase().cmp(&b.label.to_lowercase())),
+            SortMode::Created => order(a.created_at.cmp(&b.created_at)),
+            SortMode::Due => {
+                (a.due.is_none().cmp(&b.due.is_none())).then_with(|| order(a.due.cmp(&b.due)))
+            }
+        }
+    }
+}
+
+impl GroupBy {
+    /// Split items into sections, given with their headers, keeping the order of the items within
+    /// each section. Items that don't belong to any section (i.e., without tags) come last.
+    pub fn groups<'a>(self, items: &[(usize, &'a Todo)]) -> Vec<(String, Vec<(usize, &'a Todo)>)> {
+        type Section<'a> = (String, Vec<(usize, &'a Todo)>);
+        // Sections are ordered by whether they are the last one and then by a key
+        let mut sections: BTreeMap<(bool, String), Section> = BTreeMap::new();
+
+        for &(i, item) in items {
+            let keys: Vec<(String, String)> = match self {
+                GroupBy::Tag => item
+                    .tags
+                    .iter()
+                    .map(|tag| (tag.to_lowercase(), format!("+{tag}")))
+                    .collect(),
+                GroupBy::Priority => item
+                    .priority
+                    .map(|priority| ((priority as u8).to_string(), format!("Priority {priority}")))
+                    .into_iter()
+                    .collect(),
+                GroupBy::Due => item
+                    .due
+                    .map(|due| {
+                        let start = dates::start_of_week(due);
+                        let header = format!("Due the week of {}", dates::format_date(start));
+                        (start.to_string(), header)
+                    })
+                    .into_iter()
+                    .collect(),
+                GroupBy::Project => item
+                    .project
+                    .iter()
+                    .map(|project| (project.to_lowercase(), format!("Project {project}")))
+                    .collect(),
+            };
+
+            if keys.is_empty() {
+                let header = match self {
+                    GroupBy::Tag => "No tags",
+                    GroupBy::Priority => "No priority",
+                    GroupBy::Due => "No due date",
+                    GroupBy::Project => "No project",
+                };
+                let section = sections
+                    .entry((true, String::new()))
+                    .or_insert_with(|| (header.to_string(), Vec::new()));
+                section.1.push((i, item));
+            }
+            for (key, header) in keys {
+                let section = sections
+                    .entry((false, key))
+                    .or_insert_with(|| (header, Vec::new()));
+                section.1.push((i, item));
+            }
+        }
+
+        sections.into_values().collect()
+    }
+}
+
+impl Filter {
+    /// Whether no conditions are given, so every item that isn't snoozed is listed.
+    pub fn is_empty(&self) -> bool {
+        !self.pending
+            && !self.done
+            && self.status.is_none()
+            && self.tags.is_empty()
+            && self.project.is_none()
+            && self.context.is_none()
+            && self.assignee.is_none()
+            && !self.mine
+            && self.due.is_none()
+            && !self.overdue
+            && !self.unblocked
+            && !self.planned
+            && self.search.is_none()
+    }
+
+    /// Whether an item meets every condition, except for --unblocked, which depends on the other
+    /// items (see `TodoList::is_blocked`), and --mine, which has to be turned into --assignee
+    /// first.
+    pub fn matches(&self, item: &Todo, today: NaiveDate) -> bool {
+        let search = self.search.as_ref().map(|search| search.to_lowercase());
+
+        (self.all || !item.is_hidden(today))
+            && (!self.pending || !item.complete)
+            && (!self.done || item.complete)
+            && self.status.is_none_or(|status| item.state() == status)
+            && self
+                .tags
+                .iter()
+                .all(|tag| item.has_tag(tag.trim_start_matches('+')))
+            && self
+                .project
+                .as_ref()
+                .is_none_or(|p| item.project.as_ref() == Some(p))
+            && self
+                .context
+                .as_ref()
+                .is_none_or(|c| item.context.as_deref() == Some(c.trim_start_matches('@')))
+            && self
+                .assignee
+                .as_ref()
+                .is_none_or(|a| item.assignee.as_ref() == Some(a))
+            && self
+                .due
+                .is_none_or(|date| item.due.is_some_and(|due| due <= date))
+            && (!self.overdue || item.is_overdue(today))
+            && (!self.planned || item.is_planned(today))
+            && search.is_none_or(|search| item.label.to_lowercase().contains(&search))
+    }
+}
+
+/// The text labels are compared by to find duplicates.
+fn duplicate_key(label: &str, ignore_case: bool) -> String {
+    let label = label.trim();
+    if ignore_case {
+        label.to_lowercase()
+    } else {
+        label.to_string()
+    }
+}
+
+fn parse_date(s: &str) -> Result<NaiveDate, String> {
+    dates::parse_date(s, dates::today())
+}
+
+impl fmt::Display for ItemRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ItemRef::Position(pos) => write!(f, "{pos}"),
+            ItemRef::Id(id) => write!(f, "id:{id}"),
+        }
+    }
+}
+
+impl FromStr for ItemRef {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(id) = s.strip_prefix("id:") {
+            return id.parse::<u64>().map(ItemRef::Id).map_err(|err| {
+                format!("cannot convert ID string \"{id}\" into a valid ID value: {err}")
+            });
+        }
+
+        match s.parse::<usize>() {
+            Ok(0) => Err("positions start at 1".to_string()),
+            Ok(pos) => Ok(ItemRef::Position(pos)),
+            Err(err) => Err(format!(
+                "cannot convert position string \"{s}\" into a valid position value: {err}"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Selection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Selection::All => write!(f, "all"),
+            Selection::Checked => write!(f, "checked"),
+            Selection::Range(start, end) => write!(f, "{start}-{end}"),
+            Selection::Item(item) => write!(f, "{item}"),
+            Selection::Label(text) => write!(f, "\"{text}\""),
+        }
+    }
+}
+
+impl FromStr for Selection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "all" => Ok(Selection::All),
+            "checked" | "completed" => Ok(Selection::Checked),
+            _ if !s.starts_with(|c: char| c.is_ascii_digit()) && !s.starts_with("id:") => {
+                Ok(Selection::Label(s.to_string()))
+            }
+            _ => {
+                let Some((start, end)) = s.split_once('-') else {
+                    return s.parse().map(Selection::Item);
+                };
+
+                let parse = |pos: &str| match pos.parse::<ItemRef>()? {
+                    ItemRef::Position(pos) => Ok(pos),
+                    ItemRef::Id(_) => Err(format!("ranges can't use IDs, found \"{s}\"")),
+                };
+                let (start, end) = (parse(start)?, parse(end)?);
+                if start > end {
+                    return Err(format!("the range \"{s}\" ends before it starts"));
+                }
+                Ok(Selection::Range(start, end))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ids_of_removed_items_are_not_given_out_again() {
+        let mut list = TodoList::new();
+        list.add("a");
+        list.add("b");
+        let id = list.add("c");
+        list.remove(&[Selection::Item(ItemRef::Id(id))]);
+        assert_eq!(list.add("d"), id + 1);
+
+        // Restoring an item with a higher ID moves the next ID past it
+        list.restore(Todo::new(10, "e"));
+        list.remove(&[Selection::Item(ItemRef::Id(10))]);
+        assert_eq!(list.add("f"), 11);
+    }
+
+    #[test]
+    fn grouping_by_project() {
+        let mut list = TodoList::new();
+        for label in ["a", "b", "c"] {
+            list.add(label);
+        }
+        list.set_project(&[Selection::Item(ItemRef::Position(1))], Some("website"));
+        list.set_project(&[Selection::Item(ItemRef::Position(3))], Some("blog"));
+
+        let items = list.find(|_| true);
+        let groups: Vec<(String, Vec<&str>)> = GroupBy::Project
+            .groups(&items)
+            .into_iter()
+            .map(|(header, items)| {
+                let labels = items.iter().map(|(_, item)| item.label.as_str()).collect();
+                (header, labels)
+            })
+            .collect();
+        assert_eq!(
+            groups,
+            [
+                ("Project blog".to_string(), vec!["c"]),
+                ("Project website".to_string(), vec!["a"]),
+                ("No project".to_string(), vec!["b"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn the_next_occurrence_of_a_checked_item_is_not_a_duplicate() {
+        let mut list = TodoList::new();
+        let id = list.add("water the plants");
+        let item = list.get_mut(ItemRef::Id(id)).unwrap();
+        item.due = NaiveDate::from_ymd_opt(2026, 1, 5);
+        item.recurrence = Some("FREQ=WEEKLY".parse().unwrap());
+        list.set_complete(&[Selection::Item(ItemRef::Id(id))], true);
+        assert_eq!(list.len(), 2);
+
+        assert!(list.dedupe(false).is_empty());
+        let next = &list.items()[1];
+        assert!(!next.complete && next.recurrence.is_some());
+    }
+}
@@ -0,0 +1,46 @@
+//! The messages printed with `-v` and `-vv`, which show what a command reads, parses, and
+//! writes, i.e., to see which data file is used or why syncing fails.
+//!
+//! The rest of the crate logs through the `log` macros, and nothing is printed until `init`
+//! installs the logger, so programs using the library can install their own.
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+/// Prints the messages of this crate to stderr, each after its level, like warnings and errors.
+struct StderrLogger;
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.target().starts_with("todo")
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            let level = match record.level() {
+                Level::Error => "ERROR",
+                Level::Warn => "WARNING",
+                Level::Info => "INFO",
+                Level::Debug => "DEBUG",
+                Level::Trace => "TRACE",
+            };
+            eprintln!("{level}: {}", record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Print log messages to stderr, with more of them the more verbose it is: 1 for what is read
+/// and written, and 2 for what was parsed and run too. `--quiet` turns them off.
+pub fn init(verbose: u8, quiet: bool) {
+    if verbose == 0 || quiet {
+        return;
+    }
+    let level = match verbose {
+        1 => LevelFilter::Info,
+        _ => LevelFilter::Debug,
+    };
+    if log::set_logger(&StderrLogger).is_ok() {
+        log::set_max_level(level);
+    }
+}
@@ -1,24 +1,48 @@
+use clap::Parser;
 use std::env;
+use std::ffi::OsString;
+use std::process;
+use todo::{ErrorFormat, TodoError};
 
 fn main() {
-    let mut args = env::args();
-    let action: String;
-    let mut params: Vec<String> = Vec::new();
-
-    // Skip binary name argument 
-    args.next();
-
-    match args.next() {
-        Some(a) => { action = a; },
-        None => {
-            // Make listing the todos the default action
-            action = "list".to_string();
+    let cli = match todo::Cli::try_parse() {
+        Ok(cli) => cli,
+        // The command line couldn't be parsed, so --error-format is looked for by hand
+        Err(err) if err.use_stderr() && json_errors() => {
+            let message = err.to_string();
+            let message = message.lines().next().unwrap_or_default();
+            let err = TodoError::Usage(message.trim_start_matches("error: ").to_string());
+            eprintln!("{}", err.to_json());
+            process::exit(err.exit_code());
+        }
+        Err(err) => {
+            // Help and the version are printed to stdout, and aren't errors
+            let _ = err.print();
+            process::exit(if err.use_stderr() { 1 } else { 0 });
+        }
+    };
+    todo::logging::init(cli.verbose, cli.quiet);
+    let error_format = cli.error_format;
+    match todo::run(cli) {
+        Ok(()) => {}
+        // An alias asked for help or the version
+        Err(TodoError::Help(help)) => {
+            let _ = help.print();
+        }
+        Err(err) => {
+            match error_format {
+                ErrorFormat::Text => eprintln!("ERROR: {err}"),
+                ErrorFormat::Json => eprintln!("{}", err.to_json()),
+            }
+            process::exit(err.exit_code());
         }
     }
+}
 
-    for param in args {
-        params.push(param);
-    }
-
-    todo::run(&action, params);
+/// Whether the arguments ask for errors as JSON, i.e., "--error-format json".
+fn json_errors() -> bool {
+    let args: Vec<OsString> = env::args_os().collect();
+    args.windows(2)
+        .any(|pair| pair[0] == "--error-format" && pair[1] == "json")
+        || args.iter().any(|arg| arg == "--error-format=json")
 }
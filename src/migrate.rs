@@ -0,0 +1,112 @@
+//! Versioning of the data file format, and the migrations that upgrade older data files.
+//!
+//! A data file starts with a header line holding the version of its format and the ID of the next
+//! new item, i.e., `{"version":3,"next_id":7}`, followed by one item per line. Files without a header are version 1, from
+//! before the header was added. The items of an older data file are passed through the migration
+//! of every version up to the current one when the file is read, and the file is then written
+//! again in the current format. To change the format, bump `CURRENT_VERSION` and add a migration
+//! from the previous version to `MIGRATIONS`.
+
+use crate::error::TodoError;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::path::Path;
+
+/// The version of the format data files are written in.
+pub const CURRENT_VERSION: u32 = 3;
+
+/// The header line of a data file.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Header {
+    pub version: u32,
+    /// The ID the next new item gets, so that the IDs of removed items aren't given out again.
+    /// Zero for data files from before version 3, whose next ID has to be worked out from the IDs
+    /// in use.
+    #[serde(default)]
+    pub next_id: u64,
+}
+
+/// Changes an item of one version into an item of the next version.
+type Migration = fn(&mut Map<String, Value>);
+
+/// The migrations from each version to the next, starting with version 1 to 2.
+const MIGRATIONS: &[Migration] = &[v1_to_v2, v2_to_v3];
+
+/// The header line for data files written in the current format.
+pub fn header(next_id: u64) -> String {
+    let header = Header {
+        version: CURRENT_VERSION,
+        next_id,
+    };
+    serde_json::to_string(&header).expect("the header is always serializable")
+}
+
+/// Split the contents of a data file into its header and its item lines.
+/// Data files of a version newer than this program supports can't be read. When a data file was
+/// merged by `todo sync`, it may have the header of each side, so the other headers are left out
+/// of the item lines and the highest next ID of them is used.
+pub fn split_header<'a>(
+    path: &Path,
+    contents: &'a str,
+) -> Result<(Header, Vec<&'a str>), TodoError> {
+    let mut lines: Vec<&str> = contents.lines().collect();
+    let mut header = match lines
+        .first()
+        .and_then(|line| serde_json::from_str::<Header>(line).ok())
+    {
+        Some(header) => {
+            lines.remove(0);
+            header
+        }
+        None => Header {
+            version: 1,
+            next_id: 0,
+        },
+    };
+    if header.version == 0 || header.version > CURRENT_VERSION {
+        return Err(TodoError::UnsupportedVersion {
+            path: path.to_path_buf(),
+            version: header.version,
+        });
+    }
+
+    lines.retain(|line| match serde_json::from_str::<Header>(line) {
+        Ok(other) => {
+            header.next_id = header.next_id.max(other.next_id);
+            false
+        }
+        Err(_) => true,
+    });
+    Ok((header, lines))
+}
+
+/// Upgrade an item line of a data file of the given version to the current format. Lines that
+/// aren't JSON objects are left as they are, for the caller to deal with.
+pub fn upgrade(line: &str, version: u32) -> String {
+    if version >= CURRENT_VERSION {
+        return line.to_string();
+    }
+    let Ok(Value::Object(mut item)) = serde_json::from_str(line) else {
+        return line.to_string();
+    };
+
+    for migration in &MIGRATIONS[version as usize - 1..] {
+        migration(&mut item);
+    }
+    Value::Object(item).to_string()
+}
+
+/// Version 1 items may lack the creation and completion times, which were added later. They are
+/// set to the time of the migration.
+fn v1_to_v2(item: &mut Map<String, Value>) {
+    let now = Value::String(Utc::now().to_rfc3339());
+    item.entry("created_at").or_insert_with(|| now.clone());
+    if item.get("complete") == Some(&Value::Bool(true)) {
+        item.entry("completed_at").or_insert(now);
+    }
+}
+
+/// Version 3 added the next ID to the header. The items are the same as in version 2.
+fn v2_to_v3(_item: &mut Map<String, Value>) {}
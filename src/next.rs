@@ -0,0 +1,62 @@
+//! Choosing the most urgent item for `todo next`.
+//!
+//! Every pending item gets a score, which adds up its priority, how soon it is due, how long ago
+//! it was added, and whether it is pinned, each multiplied by a weight from the settings.
+
+use crate::list::{Filter, TodoList};
+use crate::settings::NextSettings;
+use crate::todo::{Priority, Todo};
+use chrono::{NaiveDate, Utc};
+
+/// The item with the highest score among the pending items that match the filter and aren't
+/// blocked. Ties go to the item listed first.
+pub fn next<'a>(
+    list: &'a TodoList,
+    filter: &Filter,
+    weights: &NextSettings,
+    today: NaiveDate,
+) -> Option<(usize, &'a Todo)> {
+    ranked(list, filter, weights, today).into_iter().next()
+}
+
+/// The pending items that match the filter and aren't blocked, from the highest score to the
+/// lowest, i.e., for `todo plan` to fill the day with. Ties keep the order of the list.
+pub fn ranked<'a>(
+    list: &'a TodoList,
+    filter: &Filter,
+    weights: &NextSettings,
+    today: NaiveDate,
+) -> Vec<(usize, &'a Todo)> {
+    let blocked = list.blocked();
+    let mut scored: Vec<(usize, &Todo, f64)> = list
+        .find(|item| !item.complete && !blocked.contains(&item.id) && filter.matches(item, today))
+        .into_iter()
+        .map(|(i, item)| (i, item, score(item, weights, today)))
+        .collect();
+    scored.sort_by(|(_, _, a), (_, _, b)| b.total_cmp(a));
+    scored.into_iter().map(|(i, item, _)| (i, item)).collect()
+}
+
+/// How urgent an item is. Each part is roughly between 0 and 1 before it is weighted, except
+/// that items that are long overdue count up to 4 times as much as items due today.
+fn score(item: &Todo, weights: &NextSettings, today: NaiveDate) -> f64 {
+    let priority = match item.priority {
+        Some(Priority::High) => 1.0,
+        Some(Priority::Medium) => 2.0 / 3.0,
+        Some(Priority::Low) => 1.0 / 3.0,
+        None => 0.0,
+    };
+    // Halves with every week left until the due date, and doubles with every week overdue
+    let due = item.due.map_or(0.0, |due| {
+        let days_left = (due - today).num_days() as f64;
+        (-days_left / 7.0).exp2().min(4.0)
+    });
+    // Reaches 1 after a month
+    let age = ((Utc::now() - item.created_at).num_days() as f64 / 30.0).clamp(0.0, 1.0);
+    let pinned = if item.pinned { 1.0 } else { 0.0 };
+
+    weights.priority_weight * priority
+        + weights.due_weight * due
+        + weights.age_weight * age
+        + weights.pinned_weight * pinned
+}
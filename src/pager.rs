@@ -0,0 +1,93 @@
+//! Paging output that doesn't fit in the terminal through `$PAGER` (or `less`), for long lists.
+//!
+//! While paging, stdout is sent through a pipe and collected in memory, so that nothing (i.e., the
+//! labels of an encrypted todo list) is written to a file. Once the output is complete, it is
+//! shown in the pager if it has more lines than the terminal, and printed as it is otherwise.
+
+use log::debug;
+use std::env;
+use std::io::{self, IsTerminal, Read, Write};
+use std::os::fd::{AsFd, AsRawFd, OwnedFd};
+use std::process::{Command, Stdio};
+use std::thread::{self, JoinHandle};
+
+/// Collects stdout until it is dropped, and then shows what was written.
+pub struct Pager {
+    /// Reads the output from the pipe until stdout is sent back to the terminal.
+    reader: Option<JoinHandle<Vec<u8>>>,
+    /// The terminal stdout was, to send it back there.
+    stdout: OwnedFd,
+}
+
+/// Start collecting stdout to page it, unless stdout isn't a terminal (i.e., it is piped to
+/// another program) or it can't be redirected.
+pub fn start() -> Option<Pager> {
+    if !io::stdout().is_terminal() {
+        return None;
+    }
+
+    let (mut pipe_reader, pipe_writer) = io::pipe().ok()?;
+    let stdout = io::stdout().as_fd().try_clone_to_owned().ok()?;
+    let _ = io::stdout().flush();
+    // SAFETY: both are open file descriptors, and stdout is only written through std
+    if unsafe { libc::dup2(pipe_writer.as_raw_fd(), libc::STDOUT_FILENO) } < 0 {
+        return None;
+    }
+    // Stdout is now the only writing end of the pipe, so the reader stops once it is sent back
+    drop(pipe_writer);
+    let reader = thread::spawn(move || {
+        let mut output = Vec::new();
+        let _ = pipe_reader.read_to_end(&mut output);
+        output
+    });
+    Some(Pager {
+        reader: Some(reader),
+        stdout,
+    })
+}
+
+impl Drop for Pager {
+    fn drop(&mut self) {
+        let _ = io::stdout().flush();
+        // SAFETY: as in `start`
+        unsafe { libc::dup2(self.stdout.as_raw_fd(), libc::STDOUT_FILENO) };
+        let output = self
+            .reader
+            .take()
+            .and_then(|reader| reader.join().ok())
+            .unwrap_or_default();
+        let output = String::from_utf8_lossy(&output);
+
+        let height = terminal_size::terminal_size().map_or(usize::MAX, |(_, h)| usize::from(h.0));
+        // One line is left for the shell's prompt
+        if output.lines().count() < height || !page(&output) {
+            let _ = io::stdout().write_all(output.as_bytes());
+        }
+    }
+}
+
+/// Show the output in $PAGER, or `less`, returning false if it couldn't be started. "cat" (or an
+/// empty $PAGER) turns paging off.
+fn page(output: &str) -> bool {
+    let pager = env::var("PAGER").unwrap_or_else(|_| "less".to_string());
+    if pager.trim().is_empty() || pager.trim() == "cat" {
+        return false;
+    }
+
+    let mut command = Command::new("sh");
+    command.args(["-c", &pager]).stdin(Stdio::piped());
+    // Keep the colors, unless the user has their own options for less
+    if env::var_os("LESS").is_none() {
+        command.env("LESS", "R");
+    }
+    debug!("Paging the output through {pager}");
+    let Ok(mut child) = command.spawn() else {
+        return false;
+    };
+    // The user can quit the pager before reading everything, which closes its stdin
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(output.as_bytes());
+    }
+    let _ = child.wait();
+    true
+}
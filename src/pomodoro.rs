@@ -0,0 +1,102 @@
+//! Pomodoro sessions on an item: a countdown of work followed by a break, with a progress bar in
+//! the terminal. Every finished work session is logged on the item, while one that is stopped
+//! with Ctrl-C isn't.
+
+use crate::error::TodoError;
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+use notify_rust::Notification;
+use std::io::{self, IsTerminal, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How many characters wide the progress bar is.
+const BAR_WIDTH: u64 = 30;
+
+/// The lengths of a pomodoro session, in minutes.
+pub struct Pomodoro {
+    pub work: u64,
+    pub rest: u64,
+    /// How many rounds of work and a break to run.
+    pub rounds: u32,
+    /// Show a desktop notification when work or a break is over.
+    pub notify: bool,
+}
+
+impl Pomodoro {
+    /// Run the rounds for the item with the given label, calling `log` with the start and end of
+    /// every finished work session.
+    pub fn run(
+        &self,
+        label: &str,
+        mut log: impl FnMut(DateTime<Utc>, DateTime<Utc>) -> Result<(), TodoError>,
+    ) -> Result<(), TodoError> {
+        for round in 1..=self.rounds {
+            println!("{} {label} ({round}/{})", "Work on".bold(), self.rounds);
+            let start = Utc::now();
+            countdown(self.work);
+            log(start, Utc::now())?;
+            self.announce(
+                "Time for a break!",
+                &format!("Finished a pomodoro on \"{label}\"."),
+            )?;
+
+            println!("{}", "Break".bold());
+            countdown(self.rest);
+            if round < self.rounds {
+                self.announce("Break is over!", &format!("Back to \"{label}\"."))?;
+            }
+        }
+        self.announce(
+            "All pomodoros are done!",
+            &format!("Finished {} pomodoro(s) on \"{label}\".", self.rounds),
+        )
+    }
+
+    /// Print a message, and show it in a desktop notification if asked to.
+    fn announce(&self, summary: &str, body: &str) -> Result<(), TodoError> {
+        println!("{summary} {body}");
+        if self.notify {
+            Notification::new()
+                .summary(&format!("todo: {summary}"))
+                .body(body)
+                .appname("todo")
+                .show()
+                .map_err(|err| TodoError::Notification(err.to_string()))?;
+        }
+        Ok(())
+    }
+}
+
+/// Wait for some minutes, showing a progress bar and the time left in a terminal.
+fn countdown(minutes: u64) {
+    let total = Duration::from_secs(minutes * 60);
+    let start = Instant::now();
+    let terminal = io::stdout().is_terminal();
+
+    loop {
+        let elapsed = start.elapsed();
+        if elapsed >= total {
+            break;
+        }
+        if terminal {
+            let left = (total - elapsed).as_secs();
+            let filled = (elapsed.as_secs() * BAR_WIDTH / total.as_secs()) as usize;
+            print!(
+                "\r{}{} {:02}:{:02} left",
+                "█".repeat(filled).green(),
+                "░".repeat(BAR_WIDTH as usize - filled).dimmed(),
+                left / 60,
+                left % 60
+            );
+            let _ = io::stdout().flush();
+        }
+        thread::sleep((total - elapsed).min(Duration::from_secs(1)));
+    }
+
+    // Clear the progress bar
+    if terminal {
+        print!("\r\x1B[2K");
+        let _ = io::stdout().flush();
+    }
+}
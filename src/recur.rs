@@ -0,0 +1,708 @@
+//! Recurring items, which come back with a new due date when they are checked.
+//!
+//! A recurrence is an iCalendar RRULE (<https://www.rfc-editor.org/rfc/rfc5545#section-3.3.10>),
+//! i.e., `FREQ=MONTHLY;BYDAY=1MO` for the first Monday of every month. The simpler phrases
+//! accepted by `todo due --every` are turned into the same rules (case-insensitively):
+//! - "day", "week", "month", "year" (or "daily", "weekly", "monthly", "yearly")
+//! - "2 days", "3 weeks", "2d", "3w", "6m", "1y"
+//! - weekdays, i.e., "monday" or "mon,wed,fri", and "weekday" for Monday to Friday
+//!
+//! Of the RRULE parts, FREQ (DAILY, WEEKLY, MONTHLY, or YEARLY), INTERVAL, COUNT, UNTIL, BYDAY,
+//! BYMONTHDAY, BYMONTH, BYSETPOS, and WKST are understood. Items are due on a date, so rules that
+//! repeat within a day aren't.
+
+use crate::dates;
+use chrono::{Datelike, Days, Months, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// How many periods (days, weeks, months, or years) to look through for the next occurrence
+/// before giving up on a rule that never matches, like February 30th.
+const MAX_PERIODS: u32 = 10_000;
+
+/// When a recurring item comes back, stored as its RRULE.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(try_from = "String", into = "String")]
+pub struct Recurrence {
+    frequency: Frequency,
+    interval: u32,
+    /// How many occurrences are left, counting the current one.
+    count: Option<u32>,
+    /// The last date an occurrence can be due on.
+    until: Option<NaiveDate>,
+    /// Weekdays, optionally numbered within the month or year, i.e., `(Some(-1), Fri)` for the
+    /// last Friday.
+    by_day: Vec<(Option<i32>, Weekday)>,
+    /// Days of the month, counting from the end when negative.
+    by_month_day: Vec<i32>,
+    by_month: Vec<u32>,
+    /// Which of the dates in a period to keep, counting from the end when negative.
+    by_set_pos: Vec<i32>,
+    week_start: Weekday,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+impl Frequency {
+    fn name(self) -> &'static str {
+        match self {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+            Frequency::Yearly => "YEARLY",
+        }
+    }
+
+    fn unit(self) -> &'static str {
+        match self {
+            Frequency::Daily => "day",
+            Frequency::Weekly => "week",
+            Frequency::Monthly => "month",
+            Frequency::Yearly => "year",
+        }
+    }
+}
+
+impl Recurrence {
+    fn new(frequency: Frequency, interval: u32) -> Recurrence {
+        Recurrence {
+            frequency,
+            interval,
+            count: None,
+            until: None,
+            by_day: Vec::new(),
+            by_month_day: Vec::new(),
+            by_month: Vec::new(),
+            by_set_pos: Vec::new(),
+            week_start: Weekday::Mon,
+        }
+    }
+
+    /// The first occurrence after a date, in the series that starts on the given date (the due
+    /// date of the current occurrence), along with the rule for the item due then. `None` when the
+    /// series has ended.
+    pub fn following(&self, after: NaiveDate, start: NaiveDate) -> Option<(NaiveDate, Recurrence)> {
+        if self.count.is_some_and(|count| count <= 1) {
+            return None;
+        }
+        let date = (0..MAX_PERIODS)
+            .map_while(|i| self.period(start, i.checked_mul(self.interval)?))
+            .flat_map(|period| self.dates_in(period, start))
+            .find(|&date| date > after)
+            .filter(|&date| self.until.is_none_or(|until| date <= until))?;
+        let next = Recurrence {
+            count: self.count.map(|count| count - 1),
+            ..self.clone()
+        };
+        Some((date, next))
+    }
+
+    /// The first day of the period that is some periods after the one holding the start date.
+    fn period(&self, start: NaiveDate, periods: u32) -> Option<NaiveDate> {
+        match self.frequency {
+            Frequency::Daily => start.checked_add_days(Days::new(periods as u64)),
+            Frequency::Weekly => start
+                .week(self.week_start)
+                .first_day()
+                .checked_add_days(Days::new(periods as u64 * 7)),
+            Frequency::Monthly => start.with_day(1)?.checked_add_months(Months::new(periods)),
+            Frequency::Yearly => NaiveDate::from_ymd_opt(start.year(), 1, 1)?
+                .checked_add_months(Months::new(periods.checked_mul(12)?)),
+        }
+    }
+
+    /// The dates of the rule in a period, in order.
+    fn dates_in(&self, period: NaiveDate, start: NaiveDate) -> Vec<NaiveDate> {
+        let mut dates = match self.frequency {
+            Frequency::Daily => vec![period],
+            Frequency::Weekly => period
+                .iter_days()
+                .take(7)
+                .filter(|&date| {
+                    if self.by_day.is_empty() {
+                        date.weekday() == start.weekday()
+                    } else {
+                        self.on_weekday(date)
+                    }
+                })
+                .collect(),
+            Frequency::Monthly => self.dates_in_month(period, start),
+            Frequency::Yearly if self.by_month.is_empty() && self.by_month_day.is_empty() => {
+                if self.by_day.is_empty() {
+                    start.with_year(period.year()).into_iter().collect()
+                } else {
+                    // Numbered weekdays count within the year, i.e., the 20th Monday
+                    let days: Vec<NaiveDate> = period
+                        .iter_days()
+                        .take_while(|date| date.year() == period.year())
+                        .collect();
+                    self.weekdays_in(&days)
+                }
+            }
+            Frequency::Yearly => (0..12)
+                .filter_map(|months| period.checked_add_months(Months::new(months)))
+                .filter(|month| self.by_month.is_empty() || self.by_month.contains(&month.month()))
+                .flat_map(|month| self.dates_in_month(month, start))
+                .collect(),
+        };
+
+        // Monthly and yearly rules already picked their days
+        if self.frequency == Frequency::Daily && !self.by_day.is_empty() {
+            dates.retain(|&date| self.on_weekday(date));
+        }
+        if matches!(self.frequency, Frequency::Daily | Frequency::Weekly)
+            && !self.by_month_day.is_empty()
+        {
+            dates.retain(|&date| self.on_month_day(date));
+        }
+        if !self.by_month.is_empty() {
+            dates.retain(|date| self.by_month.contains(&date.month()));
+        }
+
+        if self.by_set_pos.is_empty() {
+            return dates;
+        }
+        let mut kept: Vec<NaiveDate> = self
+            .by_set_pos
+            .iter()
+            .filter_map(|&pos| nth(&dates, pos))
+            .collect();
+        kept.sort();
+        kept.dedup();
+        kept
+    }
+
+    /// The dates of the rule in the month starting on the given day, in order. Without days of
+    /// the month or weekdays, that's the day of the month of the start date, if the month has it.
+    fn dates_in_month(&self, month: NaiveDate, start: NaiveDate) -> Vec<NaiveDate> {
+        let days: Vec<NaiveDate> = month
+            .iter_days()
+            .take_while(|date| date.month() == month.month())
+            .collect();
+        if !self.by_month_day.is_empty() {
+            days.into_iter()
+                .filter(|&date| self.on_month_day(date))
+                .filter(|&date| self.by_day.is_empty() || self.on_weekday(date))
+                .collect()
+        } else if !self.by_day.is_empty() {
+            self.weekdays_in(&days)
+        } else {
+            days.into_iter()
+                .filter(|date| date.day() == start.day())
+                .collect()
+        }
+    }
+
+    /// Whether a date falls on one of the rule's weekdays, whatever their numbers.
+    fn on_weekday(&self, date: NaiveDate) -> bool {
+        self.by_day.iter().any(|&(_, day)| day == date.weekday())
+    }
+
+    /// Whether a date falls on one of the rule's days of the month.
+    fn on_month_day(&self, date: NaiveDate) -> bool {
+        let from_end = days_from_end(date);
+        self.by_month_day
+            .iter()
+            .any(|&day| day == date.day() as i32 || day == from_end)
+    }
+
+    /// The days that fall on the rule's weekdays, where a numbered weekday only matches the nth
+    /// one of them among the days (or the nth from the end, when negative).
+    fn weekdays_in(&self, days: &[NaiveDate]) -> Vec<NaiveDate> {
+        let mut dates: Vec<NaiveDate> = Vec::new();
+        for &(n, weekday) in &self.by_day {
+            let matching: Vec<NaiveDate> = days
+                .iter()
+                .copied()
+                .filter(|date| date.weekday() == weekday)
+                .collect();
+            match n {
+                Some(n) => dates.extend(nth(&matching, n)),
+                None => dates.extend(matching),
+            }
+        }
+        dates.sort();
+        dates.dedup();
+        dates
+    }
+
+    /// A description of the rule, i.e., "every 2 weeks" or "every Monday, Friday", or the RRULE
+    /// itself when it is more involved.
+    pub fn describe(&self) -> String {
+        let simple = self.count.is_none()
+            && self.until.is_none()
+            && self.by_month_day.is_empty()
+            && self.by_month.is_empty()
+            && self.by_set_pos.is_empty()
+            && self.week_start == Weekday::Mon;
+        if !simple {
+            return self.to_string();
+        }
+        if self.by_day.is_empty() {
+            return match self.interval {
+                1 => format!("every {}", self.frequency.unit()),
+                n => format!("every {n} {}s", self.frequency.unit()),
+            };
+        }
+        if self.frequency == Frequency::Weekly
+            && self.interval == 1
+            && self.by_day.iter().all(|(n, _)| n.is_none())
+        {
+            let days: Vec<Weekday> = self.by_day.iter().map(|&(_, day)| day).collect();
+            if days == WORKDAYS {
+                return "every weekday".to_string();
+            }
+            let names: Vec<&str> = days.iter().map(|&day| weekday_name(day)).collect();
+            return format!("every {}", names.join(", "));
+        }
+        self.to_string()
+    }
+}
+
+/// Monday to Friday.
+const WORKDAYS: [Weekday; 5] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+];
+
+/// The day of the month counted from the end, i.e., -1 for the last day.
+fn days_from_end(date: NaiveDate) -> i32 {
+    let next_month = date
+        .with_day(1)
+        .and_then(|first| first.checked_add_months(Months::new(1)));
+    match next_month {
+        Some(next_month) => -((next_month - date).num_days() as i32),
+        None => 0,
+    }
+}
+
+/// The nth of some dates (starting at 1), or the nth from the end when negative.
+fn nth(dates: &[NaiveDate], n: i32) -> Option<NaiveDate> {
+    let index = if n > 0 {
+        n as usize - 1
+    } else {
+        dates.len().checked_sub(n.unsigned_abs() as usize)?
+    };
+    dates.get(index).copied()
+}
+
+fn weekday_name(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "Monday",
+        Weekday::Tue => "Tuesday",
+        Weekday::Wed => "Wednesday",
+        Weekday::Thu => "Thursday",
+        Weekday::Fri => "Friday",
+        Weekday::Sat => "Saturday",
+        Weekday::Sun => "Sunday",
+    }
+}
+
+fn weekday_code(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+fn parse_weekday_code(code: &str) -> Option<Weekday> {
+    match code {
+        "MO" => Some(Weekday::Mon),
+        "TU" => Some(Weekday::Tue),
+        "WE" => Some(Weekday::Wed),
+        "TH" => Some(Weekday::Thu),
+        "FR" => Some(Weekday::Fri),
+        "SA" => Some(Weekday::Sat),
+        "SU" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+impl FromStr for Recurrence {
+    type Err = String;
+
+    /// Parse an RRULE (with or without the "RRULE:" prefix), or a phrase such as "2 weeks".
+    fn from_str(s: &str) -> Result<Recurrence, String> {
+        let s = s.trim();
+        if s.contains('=') {
+            let rule = s
+                .get(..6)
+                .filter(|prefix| prefix.eq_ignore_ascii_case("RRULE:"));
+            parse_rrule(&s[rule.map_or(0, str::len)..].to_uppercase())
+        } else {
+            parse_phrase(&s.to_lowercase()).ok_or_else(|| {
+                format!(
+                    "cannot understand the recurrence \"{s}\" (try \"week\", \"2 days\", \"monday\", \"weekday\", or an RRULE such as \"FREQ=MONTHLY;BYDAY=1MO\")"
+                )
+            })
+        }
+    }
+}
+
+/// Parse the phrases described in the module documentation.
+fn parse_phrase(s: &str) -> Option<Recurrence> {
+    let s = s.strip_prefix("every ").unwrap_or(s).trim();
+    let words: Vec<&str> = s.split_whitespace().collect();
+    let (amount, unit) = match words.as_slice() {
+        ["daily"] => (1, "day"),
+        ["weekly"] => (1, "week"),
+        ["monthly"] => (1, "month"),
+        ["yearly" | "annually"] => (1, "year"),
+        ["weekday" | "weekdays"] => {
+            let mut rule = Recurrence::new(Frequency::Weekly, 1);
+            rule.by_day = WORKDAYS.iter().map(|&day| (None, day)).collect();
+            return Some(rule);
+        }
+        [short] if dates::parse_short_duration(short).is_some() => {
+            dates::parse_short_duration(short)?
+        }
+        [unit]
+            if matches!(
+                unit.trim_end_matches('s'),
+                "day" | "week" | "month" | "year"
+            ) =>
+        {
+            (1, *unit)
+        }
+        [amount, unit] => (dates::parse_amount(amount)?, *unit),
+        _ => {
+            // A list of weekdays, i.e., "mon,wed,fri" or "tuesday and thursday"
+            let days: Option<Vec<Weekday>> = s
+                .split([',', ' '])
+                .filter(|word| !word.is_empty() && *word != "and")
+                .map(dates::parse_weekday)
+                .collect();
+            let mut rule = Recurrence::new(Frequency::Weekly, 1);
+            rule.by_day = days?.into_iter().map(|day| (None, day)).collect();
+            rule.by_day.dedup();
+            return Some(rule);
+        }
+    };
+
+    let frequency = match unit.trim_end_matches('s') {
+        "day" => Frequency::Daily,
+        "week" => Frequency::Weekly,
+        "month" => Frequency::Monthly,
+        "year" => Frequency::Yearly,
+        _ => return None,
+    };
+    (amount > 0).then(|| Recurrence::new(frequency, amount))
+}
+
+/// Parse the parts of an RRULE, i.e., "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH".
+fn parse_rrule(s: &str) -> Result<Recurrence, String> {
+    let mut frequency = None;
+    let mut rule = Recurrence::new(Frequency::Daily, 1);
+
+    for part in s.split(';').filter(|part| !part.is_empty()) {
+        let Some((name, value)) = part.split_once('=') else {
+            return Err(format!(
+                "invalid RRULE part \"{part}\" (expected NAME=VALUE)"
+            ));
+        };
+        let invalid = || format!("invalid {name} \"{value}\" in the RRULE");
+        let list = |range: std::ops::RangeInclusive<i32>| {
+            value
+                .split(',')
+                .map(|n| n.parse().ok().filter(|n| *n != 0 && range.contains(n)))
+                .collect::<Option<Vec<i32>>>()
+                .ok_or_else(invalid)
+        };
+
+        match name {
+            "FREQ" => {
+                frequency = Some(match value {
+                    "DAILY" => Frequency::Daily,
+                    "WEEKLY" => Frequency::Weekly,
+                    "MONTHLY" => Frequency::Monthly,
+                    "YEARLY" => Frequency::Yearly,
+                    "HOURLY" | "MINUTELY" | "SECONDLY" => {
+                        return Err(format!(
+                            "FREQ={value} is not supported, since items are due on a date"
+                        ));
+                    }
+                    _ => return Err(invalid()),
+                })
+            }
+            "INTERVAL" => {
+                rule.interval = value.parse().ok().filter(|&n| n > 0).ok_or_else(invalid)?
+            }
+            "COUNT" => {
+                rule.count = Some(value.parse().ok().filter(|&n| n > 0).ok_or_else(invalid)?)
+            }
+            "UNTIL" => {
+                let date = value
+                    .get(..8)
+                    .and_then(|date| NaiveDate::parse_from_str(date, "%Y%m%d").ok());
+                rule.until = Some(date.ok_or_else(invalid)?);
+            }
+            "BYDAY" => {
+                rule.by_day = value
+                    .split(',')
+                    .map(|day| {
+                        let (n, code) = day.split_at(day.len().saturating_sub(2));
+                        let weekday = parse_weekday_code(code)?;
+                        if n.is_empty() {
+                            return Some((None, weekday));
+                        }
+                        let n: i32 = n
+                            .parse()
+                            .ok()
+                            .filter(|n| *n != 0 && (-53..=53).contains(n))?;
+                        Some((Some(n), weekday))
+                    })
+                    .collect::<Option<_>>()
+                    .ok_or_else(invalid)?
+            }
+            "BYMONTHDAY" => rule.by_month_day = list(-31..=31)?,
+            "BYMONTH" => rule.by_month = list(1..=12)?.into_iter().map(|n| n as u32).collect(),
+            "BYSETPOS" => rule.by_set_pos = list(-366..=366)?,
+            "WKST" => rule.week_start = parse_weekday_code(value).ok_or_else(invalid)?,
+            "BYHOUR" | "BYMINUTE" | "BYSECOND" | "BYWEEKNO" | "BYYEARDAY" => {
+                return Err(format!("{name} is not supported in recurrences"));
+            }
+            _ => return Err(format!("unknown RRULE part \"{name}\"")),
+        }
+    }
+
+    rule.frequency = frequency.ok_or("the RRULE has no FREQ")?;
+    if rule.count.is_some() && rule.until.is_some() {
+        return Err("an RRULE can't have both COUNT and UNTIL".to_string());
+    }
+    let numbered = rule.by_day.iter().any(|(n, _)| n.is_some());
+    if numbered && !matches!(rule.frequency, Frequency::Monthly | Frequency::Yearly) {
+        return Err("numbered weekdays in BYDAY need FREQ=MONTHLY or FREQ=YEARLY".to_string());
+    }
+    Ok(rule)
+}
+
+impl fmt::Display for Recurrence {
+    /// Format the rule as an RRULE, without the "RRULE:" prefix.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let join = |numbers: &[i32]| {
+            let numbers: Vec<String> = numbers.iter().map(i32::to_string).collect();
+            numbers.join(",")
+        };
+
+        write!(f, "FREQ={}", self.frequency.name())?;
+        if self.interval > 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        if !self.by_day.is_empty() {
+            let days: Vec<String> = self
+                .by_day
+                .iter()
+                .map(|&(n, day)| match n {
+                    Some(n) => format!("{n}{}", weekday_code(day)),
+                    None => weekday_code(day).to_string(),
+                })
+                .collect();
+            write!(f, ";BYDAY={}", days.join(","))?;
+        }
+        if !self.by_month_day.is_empty() {
+            write!(f, ";BYMONTHDAY={}", join(&self.by_month_day))?;
+        }
+        if !self.by_month.is_empty() {
+            let months: Vec<i32> = self.by_month.iter().map(|&month| month as i32).collect();
+            write!(f, ";BYMONTH={}", join(&months))?;
+        }
+        if !self.by_set_pos.is_empty() {
+            write!(f, ";BYSETPOS={}", join(&self.by_set_pos))?;
+        }
+        if self.week_start != Weekday::Mon {
+            write!(f, ";WKST={}", weekday_code(self.week_start))?;
+        }
+        if let Some(count) = self.count {
+            write!(f, ";COUNT={count}")?;
+        }
+        if let Some(until) = self.until {
+            write!(f, ";UNTIL={}", until.format("%Y%m%d"))?;
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<String> for Recurrence {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Recurrence, String> {
+        s.parse()
+    }
+}
+
+impl From<Recurrence> for String {
+    fn from(rule: Recurrence) -> String {
+        rule.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn rule(s: &str) -> Recurrence {
+        s.parse().unwrap()
+    }
+
+    /// The dates of the next occurrences of a rule, starting from its first due date.
+    fn occurrences(rule: &Recurrence, start: NaiveDate, n: usize) -> Vec<NaiveDate> {
+        let mut dates = Vec::new();
+        let (mut rule, mut due) = (rule.clone(), start);
+        while dates.len() < n {
+            let Some((next, next_rule)) = rule.following(due, due) else {
+                break;
+            };
+            dates.push(next);
+            (rule, due) = (next_rule, next);
+        }
+        dates
+    }
+
+    #[test]
+    fn last_friday_of_the_month() {
+        let rule = rule("FREQ=MONTHLY;BYDAY=-1FR");
+        assert_eq!(
+            occurrences(&rule, date(2026, 1, 30), 3),
+            [date(2026, 2, 27), date(2026, 3, 27), date(2026, 4, 24)]
+        );
+        // Across the end of the year
+        assert_eq!(
+            occurrences(&rule, date(2026, 12, 25), 1),
+            [date(2027, 1, 29)]
+        );
+    }
+
+    #[test]
+    fn yearly_on_february_29th_skips_to_leap_years() {
+        let rule = rule("FREQ=YEARLY");
+        assert_eq!(
+            occurrences(&rule, date(2024, 2, 29), 2),
+            [date(2028, 2, 29), date(2032, 2, 29)]
+        );
+    }
+
+    #[test]
+    fn monthly_on_the_31st_skips_shorter_months() {
+        let rule = rule("FREQ=MONTHLY");
+        assert_eq!(
+            occurrences(&rule, date(2026, 1, 31), 2),
+            [date(2026, 3, 31), date(2026, 5, 31)]
+        );
+    }
+
+    #[test]
+    fn last_day_of_the_month() {
+        let rule = rule("FREQ=MONTHLY;BYMONTHDAY=-1");
+        assert_eq!(
+            occurrences(&rule, date(2027, 12, 31), 3),
+            [date(2028, 1, 31), date(2028, 2, 29), date(2028, 3, 31)]
+        );
+    }
+
+    #[test]
+    fn last_workday_of_the_month() {
+        let rule = rule("FREQ=MONTHLY;BYDAY=MO,TU,WE,TH,FR;BYSETPOS=-1");
+        // February 28th and May 31st 2026 are on weekends
+        assert_eq!(
+            occurrences(&rule, date(2026, 1, 30), 4),
+            [
+                date(2026, 2, 27),
+                date(2026, 3, 31),
+                date(2026, 4, 30),
+                date(2026, 5, 29)
+            ]
+        );
+    }
+
+    #[test]
+    fn every_other_week_on_several_days() {
+        let rule = rule("FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH");
+        assert_eq!(
+            occurrences(&rule, date(2026, 1, 5), 3),
+            [date(2026, 1, 8), date(2026, 1, 19), date(2026, 1, 22)]
+        );
+    }
+
+    #[test]
+    fn count_runs_out() {
+        let rule = rule("FREQ=DAILY;COUNT=3");
+        let (due, rule) = rule.following(date(2026, 1, 1), date(2026, 1, 1)).unwrap();
+        assert_eq!(due, date(2026, 1, 2));
+        assert_eq!(rule.to_string(), "FREQ=DAILY;COUNT=2");
+        let (due, rule) = rule.following(due, due).unwrap();
+        assert_eq!(due, date(2026, 1, 3));
+        assert_eq!(rule.following(due, due), None);
+    }
+
+    #[test]
+    fn until_ends_the_series() {
+        let rule = rule("FREQ=WEEKLY;UNTIL=20260115");
+        assert_eq!(
+            occurrences(&rule, date(2026, 1, 1), 3),
+            [date(2026, 1, 8), date(2026, 1, 15)]
+        );
+    }
+
+    #[test]
+    fn overdue_items_come_back_after_today() {
+        let rule = rule("FREQ=WEEKLY");
+        let (due, _) = rule.following(date(2026, 1, 20), date(2026, 1, 5)).unwrap();
+        assert_eq!(due, date(2026, 1, 26));
+    }
+
+    #[test]
+    fn phrases_are_rrules() {
+        assert_eq!(rule("2 weeks").to_string(), "FREQ=WEEKLY;INTERVAL=2");
+        assert_eq!(rule("week").to_string(), "FREQ=WEEKLY");
+        assert_eq!(rule("every month").to_string(), "FREQ=MONTHLY");
+        assert_eq!(rule("yearly").to_string(), "FREQ=YEARLY");
+        assert_eq!(rule("3d").to_string(), "FREQ=DAILY;INTERVAL=3");
+        assert_eq!(
+            rule("weekday").to_string(),
+            "FREQ=WEEKLY;BYDAY=MO,TU,WE,TH,FR"
+        );
+        assert_eq!(
+            rule("mon, wed and fri").to_string(),
+            "FREQ=WEEKLY;BYDAY=MO,WE,FR"
+        );
+        assert_eq!(
+            rule("RRULE:freq=monthly;byday=1mo").to_string(),
+            "FREQ=MONTHLY;BYDAY=1MO"
+        );
+    }
+
+    #[test]
+    fn invalid_rules_are_rejected() {
+        for s in [
+            "FREQ=HOURLY",
+            "INTERVAL=2",
+            "FREQ=WEEKLY;BYDAY=1MO",
+            "FREQ=DAILY;COUNT=2;UNTIL=20260101",
+            "FREQ=MONTHLY;BYMONTHDAY=0",
+            "FREQ=MONTHLY;BYMONTHDAY=32",
+            "0 days",
+            "fortnightly",
+        ] {
+            assert!(s.parse::<Recurrence>().is_err(), "{s}");
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use crate::dates;
+use crate::error::TodoError;
+use crate::list::TodoList;
+use crate::todo::Todo;
+use chrono::{Days, NaiveDate};
+use notify_rust::Notification;
+
+/// The incomplete items that are overdue or due within `lead_days` days, soonest first.
+pub fn due_soon(list: &TodoList, today: NaiveDate, lead_days: u64) -> Vec<(usize, &Todo)> {
+    let horizon = today + Days::new(lead_days);
+    let mut items = list.find(|item| !item.complete && item.due.is_some_and(|due| due <= horizon));
+    items.sort_by_key(|(_, item)| item.due);
+    items
+}
+
+/// Print the items that are overdue or due soon, and optionally show them in a desktop
+/// notification. Nothing is shown when no items are due.
+pub fn remind(
+    list: &TodoList,
+    today: NaiveDate,
+    lead_days: u64,
+    notify: bool,
+) -> Result<(), TodoError> {
+    let items = due_soon(list, today, lead_days);
+    if items.is_empty() {
+        println!("Nothing is due soon.");
+        return Ok(());
+    }
+
+    let overdue = items
+        .iter()
+        .filter(|(_, item)| item.is_overdue(today))
+        .count();
+    let summary = match (overdue, items.len() - overdue) {
+        (0, soon) => format!("{soon} item(s) due soon"),
+        (overdue, 0) => format!("{overdue} item(s) overdue"),
+        (overdue, soon) => format!("{overdue} item(s) overdue, {soon} due soon"),
+    };
+
+    let mut body = String::new();
+    for (i, item) in &items {
+        let due = item.due.unwrap();
+        let when = if due < today {
+            format!("overdue since {}", dates::format_date(due))
+        } else if due == today {
+            String::from("due today")
+        } else {
+            format!("due {}", dates::format_date(due))
+        };
+        body.push_str(&format!("{}: {} ({when})\n", i + 1, item.label));
+    }
+
+    println!("{summary}");
+    print!("{body}");
+
+    if notify {
+        Notification::new()
+            .summary(&format!("todo: {summary}"))
+            .body(body.trim_end())
+            .appname("todo")
+            .show()
+            .map_err(|err| TodoError::Notification(err.to_string()))?;
+    }
+
+    Ok(())
+}
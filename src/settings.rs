@@ -0,0 +1,817 @@
+use crate::dates::DateFormat;
+use crate::display::{Checkbox, Theme, parse_color};
+use crate::error::TodoError;
+use crate::estimate::Estimate;
+use crate::list::SortMode;
+use crate::store::{DEFAULT_LIST, check_writable, read_only, validate_list_name, write_atomic};
+use crate::template::Template;
+use chrono::Weekday;
+use chrono_tz::Tz;
+use clap::ValueEnum;
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const SETTINGS_FILE_NAME: &str = "settings.toml";
+/// The settings file of older versions, which is migrated to the TOML file when found.
+const LEGACY_SETTINGS_FILE_NAME: &str = "settings.json";
+
+/// The available settings, their options, and a description of each.
+/// Settings without options accept any value.
+pub const SETTING_CHOICES: &[(&str, &[&str], &str)] = &[
+    (
+        "silent",
+        &["on", "off"],
+        "Don't print the todo list after each mutation command (Default = off)",
+    ),
+    (
+        "list",
+        &[],
+        "The list used when no --list is given (Default = default)",
+    ),
+    (
+        "capacity",
+        &[],
+        "How much work fits in a day, i.e., \"6h\" or \"10pt\", to warn when more is due today, or \"none\" (Default = none)",
+    ),
+    (
+        "project",
+        &[],
+        "The project new items are added to and `todo list` shows, or \"none\" (Default = none)",
+    ),
+    (
+        "context",
+        &[],
+        "The context new items are added to and `todo list` shows, i.e., \"@home\", or \"none\" (Default = none)",
+    ),
+    (
+        "format",
+        &["text", "json", "table"],
+        "How the todo list is printed: as text, as a JSON array for scripts, or as a table (Default = text)",
+    ),
+    (
+        "template",
+        &[],
+        "A format string to print each item with as text, i.e., \"{index}. [{check}] {label} {due:%m-%d}\", or \"none\" (Default = none)",
+    ),
+    (
+        "remind-days",
+        &[],
+        "How many days ahead `todo remind` warns about due items (Default = 1)",
+    ),
+    (
+        "trash-days",
+        &[],
+        "How many days removed items are kept in the trash, or 0 to keep them forever (Default = 30)",
+    ),
+    (
+        "backups",
+        &[],
+        "How many backups of a todo list are kept, made before each change, or 0 to keep none (Default = 5)",
+    ),
+    (
+        "encryption",
+        &["off", "on"],
+        "Encrypt the todo lists with a passphrase; use `todo encrypt` and `todo decrypt` to change it (Default = off)",
+    ),
+    (
+        "read-only",
+        &["off", "on"],
+        "Never write to the todo lists or the settings, i.e., for a data directory mounted read-only; `todo set read-only off` still works (Default = off)",
+    ),
+    (
+        "sort",
+        &["none", "completed", "priority", "alpha", "created", "due"],
+        "The order items are listed in, without changing their positions (Default = none)",
+    ),
+    (
+        "sort-reverse",
+        &["on", "off"],
+        "List items in the reverse of the \"sort\" order (Default = off)",
+    ),
+    (
+        "date-format",
+        &["iso", "dmy", "mdy"],
+        "How dates are written and read: YYYY-MM-DD, DD/MM/YYYY, or MM/DD/YYYY (Default = iso)",
+    ),
+    (
+        "week-start",
+        &[
+            "monday",
+            "tuesday",
+            "wednesday",
+            "thursday",
+            "friday",
+            "saturday",
+            "sunday",
+        ],
+        "The first day of the week, for `todo week` and `todo list --group-by due` (Default = monday)",
+    ),
+    (
+        "timezone",
+        &[],
+        "The timezone times are shown in and today's date is taken from, i.e., \"Europe/Berlin\", or \"none\" for the system's timezone (Default = none)",
+    ),
+    (
+        "exact-dates",
+        &["on", "off"],
+        "Show due dates as YYYY-MM-DD instead of relative to today, i.e., \"in 2 days\" (Default = off)",
+    ),
+    (
+        "pager",
+        &["on", "off"],
+        "Show `todo list` through $PAGER (or less) when it's longer than the terminal (Default = on)",
+    ),
+    (
+        "progress",
+        &["on", "off"],
+        "Show how many of the listed items are done, with a bar, under `todo list` (Default = off)",
+    ),
+    (
+        "numbering",
+        &["plain", "padded", "aligned", "hidden", "ids"],
+        "How items are numbered in `todo list`: by position, zero-padded (\"01\"), right-aligned, not at all, or by their stable IDs (Default = plain)",
+    ),
+    (
+        "long-labels",
+        &["wrap", "truncate", "off"],
+        "What to do with labels too long for the terminal: wrap them under themselves, cut them off with \"…\", or leave them to the terminal (Default = wrap)",
+    ),
+    (
+        "color",
+        &["auto", "always", "never"],
+        "When output is colored; \"auto\" colors it only in a terminal, unless NO_COLOR is set (Default = auto)",
+    ),
+    (
+        "checkbox",
+        &[],
+        "The checkboxes of items: \"unicode\" (☑/☐), \"ascii\" ([x]/[ ]), \"emoji\" (✅/⬜), or a custom pair like \"✔/✘\" (Default = unicode)",
+    ),
+    (
+        "completed-color",
+        &[],
+        "The color of completed items, as a name (i.e., \"bright green\") or a hex code (i.e., \"#88cc88\") (Default = green)",
+    ),
+    (
+        "pending-color",
+        &[],
+        "The color of incomplete items, as a name or a hex code (Default = white)",
+    ),
+    (
+        "in-progress-color",
+        &[],
+        "The color of items in progress, as a name or a hex code (Default = cyan)",
+    ),
+    (
+        "waiting-color",
+        &[],
+        "The color of items waiting on something, as a name or a hex code (Default = magenta)",
+    ),
+    (
+        "overdue-color",
+        &[],
+        "The color of overdue items, as a name or a hex code (Default = red)",
+    ),
+    (
+        "pinned-color",
+        &[],
+        "The color of the marker of pinned items, as a name or a hex code (Default = yellow)",
+    ),
+    (
+        "priority-weight",
+        &[],
+        "How much an item's priority counts towards it being picked by `todo next` (Default = 1)",
+    ),
+    (
+        "due-weight",
+        &[],
+        "How much an item's due date counts towards it being picked by `todo next` (Default = 2)",
+    ),
+    (
+        "age-weight",
+        &[],
+        "How much an item's age counts towards it being picked by `todo next` (Default = 0.5)",
+    ),
+    (
+        "pinned-weight",
+        &[],
+        "How much an item being pinned counts towards it being picked by `todo next` (Default = 3)",
+    ),
+    (
+        "warn-duplicates",
+        &["on", "off"],
+        "Warn when adding an item with the same label as an existing item (Default = on)",
+    ),
+    (
+        "confirm",
+        &["on", "off"],
+        "Ask before removing every item or emptying the trash (Default = on)",
+    ),
+    (
+        "strict",
+        &["on", "off"],
+        "Fail without changing anything when a command is given items that don't exist, instead of warning and skipping them (Default = off)",
+    ),
+    (
+        "edit-mode",
+        &["prompt", "editor"],
+        "How `todo edit` asks for new labels: with a prompt, or in $EDITOR along with the note (Default = prompt)",
+    ),
+    (
+        "git-commit",
+        &["on", "off"],
+        "Commit the data directory to a git repository after each command (Default = off)",
+    ),
+    (
+        "git-remote",
+        &[],
+        "The git repository `todo sync` pulls from and pushes to, or \"none\" (Default = none)",
+    ),
+    (
+        "caldav-url",
+        &[],
+        "The CalDAV calendar `todo sync` uploads items to, or \"none\" (Default = none)",
+    ),
+    (
+        "caldav-user",
+        &[],
+        "The user name to log in to the CalDAV server with, or \"none\" (Default = none)",
+    ),
+    (
+        "user",
+        &[],
+        "Your name in shared lists, for `todo list --mine`, or \"none\" (Default = none)",
+    ),
+    (
+        "on-add",
+        &[],
+        "A shell command to run for every item that is added, with the item in JSON on stdin, or \"none\" (Default = none)",
+    ),
+    (
+        "on-check",
+        &[],
+        "A shell command to run for every item that is checked, with the item in JSON on stdin, or \"none\" (Default = none)",
+    ),
+    (
+        "on-uncheck",
+        &[],
+        "A shell command to run for every item that is unchecked, with the item in JSON on stdin, or \"none\" (Default = none)",
+    ),
+    (
+        "on-edit",
+        &[],
+        "A shell command to run for every item that is otherwise changed, with the item in JSON on stdin, or \"none\" (Default = none)",
+    ),
+    (
+        "on-remove",
+        &[],
+        "A shell command to run for every item that is removed, with the item in JSON on stdin, or \"none\" (Default = none)",
+    ),
+    (
+        "on-write",
+        &[],
+        "A shell command to run after every change, with the changed items in JSON on stdin, or \"none\" (Default = none)",
+    ),
+];
+
+/// The settings, as stored in the TOML settings file. Missing settings have their default value.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Settings {
+    pub display: DisplaySettings,
+    pub theme: ThemeSettings,
+    pub storage: StorageSettings,
+    pub behavior: BehaviorSettings,
+    pub sync: SyncSettings,
+    pub hooks: HookSettings,
+    pub next: NextSettings,
+    /// Shortcuts for commands, i.e., "work" for "list --tag work".
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub aliases: BTreeMap<String, String>,
+}
+
+/// How the todo list is printed.
+#[derive(Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct DisplaySettings {
+    pub format: OutputFormat,
+    pub color: ColorMode,
+    /// The order items are listed in. The items keep their positions in the data file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort: Option<SortMode>,
+    pub sort_reverse: bool,
+    /// A format string to print each item with, instead of the default text format.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    /// The style of the checkboxes, or a custom pair of glyphs, i.e., "✔/✘".
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checkbox: Option<String>,
+    /// Show due dates as dates, instead of relative to today (i.e., "in 2 days").
+    pub exact_dates: bool,
+    /// Page `todo list` when it doesn't fit in the terminal.
+    pub pager: bool,
+    /// Show a progress bar of the items done under `todo list`.
+    pub progress: bool,
+    pub numbering: Numbering,
+    pub long_labels: LongLabels,
+    pub date_format: DateFormat,
+    pub week_start: Weekday,
+    /// An IANA timezone name, i.e., "Europe/Berlin", to use instead of the system's timezone.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+}
+
+/// How `todo next` weighs the parts of an item's urgency against each other.
+#[derive(Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct NextSettings {
+    pub priority_weight: f64,
+    pub due_weight: f64,
+    pub age_weight: f64,
+    pub pinned_weight: f64,
+}
+
+/// Shell commands run when items change, with the affected items in JSON on stdin.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct HookSettings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_add: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_check: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_uncheck: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_edit: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_remove: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_write: Option<String>,
+}
+
+/// The colors items are printed in, as color names or hex codes.
+#[derive(Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct ThemeSettings {
+    pub completed: String,
+    pub pending: String,
+    pub in_progress: String,
+    pub waiting: String,
+    pub overdue: String,
+    pub pinned: String,
+}
+
+/// Where and how the todo lists are stored.
+#[derive(Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct StorageSettings {
+    /// The list used when no --list is given.
+    pub list: String,
+    /// How many days removed items are kept in the trash, or 0 to keep them forever.
+    pub trash_days: u64,
+    /// How many backups of each data file are kept, or 0 to keep none.
+    pub backups: usize,
+    pub encryption: bool,
+    /// Never write anything, as with --read-only.
+    pub read_only: bool,
+}
+
+/// How commands behave.
+#[derive(Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct BehaviorSettings {
+    /// Don't print the todo list after each mutation command.
+    pub silent: bool,
+    /// How many days ahead `todo remind` warns about due items.
+    pub remind_days: u64,
+    /// Ask before removing every item or emptying the trash.
+    pub confirm: bool,
+    /// Warn when adding an item with the same label as an existing item.
+    pub warn_duplicates: bool,
+    /// Fail when given items that don't exist, instead of skipping them.
+    pub strict: bool,
+    pub edit_mode: EditMode,
+    /// How much work fits in a day, to warn when the estimates of the items due today add up to
+    /// more.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capacity: Option<Estimate>,
+    /// The project commands are scoped to, chosen with `todo project`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    /// The context commands are scoped to, without the "@", chosen with `todo context`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+}
+
+/// How the todo lists are synced with other machines and calendar apps.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct SyncSettings {
+    /// Commit the data directory to a git repository after each command.
+    pub git_commit: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub git_remote: Option<String>,
+    /// The URL of the CalDAV calendar collection.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub caldav_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub caldav_user: Option<String>,
+    /// Who you are in a shared list, to find the items assigned to you.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+}
+
+/// How the todo list is printed.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+    /// A column for each field
+    Table,
+}
+
+/// When output is colored.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorMode {
+    /// Only in a terminal, unless NO_COLOR is set
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// What items are numbered with in `todo list`.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Numbering {
+    /// Their positions, i.e., "1:"
+    #[default]
+    Plain,
+    /// Their positions, padded with zeros to the same width, i.e., "01:"
+    Padded,
+    /// Their positions, right-aligned, i.e., " 1:"
+    Aligned,
+    /// Nothing, only the checkbox
+    Hidden,
+    /// Their IDs, which don't change when other items are removed or moved, i.e., "id:7"
+    Ids,
+}
+
+/// How labels too long for the terminal are listed.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum LongLabels {
+    /// Continue on the next lines, indented under the start of the label
+    #[default]
+    Wrap,
+    /// Cut off with "…"
+    Truncate,
+    /// Printed as they are, for the terminal to wrap
+    Off,
+}
+
+/// How `todo edit` asks for new labels.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum EditMode {
+    #[default]
+    Prompt,
+    /// In $EDITOR, along with the note
+    Editor,
+}
+
+impl Default for ThemeSettings {
+    fn default() -> ThemeSettings {
+        ThemeSettings {
+            completed: "green".to_string(),
+            pending: "white".to_string(),
+            in_progress: "cyan".to_string(),
+            waiting: "magenta".to_string(),
+            overdue: "red".to_string(),
+            pinned: "yellow".to_string(),
+        }
+    }
+}
+
+impl DisplaySettings {
+    /// The glyphs to mark items with. Fails if the checkbox setting was edited into the settings
+    /// file by hand and isn't valid.
+    pub fn checkbox(&self) -> Result<Checkbox, TodoError> {
+        match &self.checkbox {
+            Some(style) => Checkbox::parse(style).ok_or_else(|| TodoError::InvalidSetting {
+                name: "checkbox".to_string(),
+                value: style.to_string(),
+            }),
+            None => Ok(Checkbox::default()),
+        }
+    }
+}
+
+impl HookSettings {
+    /// The command to run for an event (i.e., "on-add"), if any.
+    pub fn get(&self, event: &str) -> Option<&str> {
+        let hook = match event {
+            "on-add" => &self.on_add,
+            "on-check" => &self.on_check,
+            "on-uncheck" => &self.on_uncheck,
+            "on-edit" => &self.on_edit,
+            "on-remove" => &self.on_remove,
+            "on-write" => &self.on_write,
+            _ => &None,
+        };
+        hook.as_deref()
+    }
+}
+
+impl ThemeSettings {
+    /// The colors to print the items in. Fails if a color was edited into the settings file by
+    /// hand and isn't valid.
+    pub fn theme(&self) -> Result<Theme, TodoError> {
+        let color = |name: &str, value: &str| {
+            parse_color(value).ok_or_else(|| TodoError::InvalidSetting {
+                name: name.to_string(),
+                value: value.to_string(),
+            })
+        };
+        Ok(Theme {
+            completed: color("completed-color", &self.completed)?,
+            pending: color("pending-color", &self.pending)?,
+            in_progress: color("in-progress-color", &self.in_progress)?,
+            waiting: color("waiting-color", &self.waiting)?,
+            overdue: color("overdue-color", &self.overdue)?,
+            pinned: color("pinned-color", &self.pinned)?,
+        })
+    }
+}
+
+impl Default for DisplaySettings {
+    fn default() -> DisplaySettings {
+        DisplaySettings {
+            format: OutputFormat::Text,
+            color: ColorMode::Auto,
+            sort: None,
+            sort_reverse: false,
+            template: None,
+            checkbox: None,
+            exact_dates: false,
+            pager: true,
+            progress: false,
+            numbering: Numbering::Plain,
+            long_labels: LongLabels::Wrap,
+            date_format: DateFormat::Iso,
+            week_start: Weekday::Mon,
+            timezone: None,
+        }
+    }
+}
+
+impl Default for StorageSettings {
+    fn default() -> StorageSettings {
+        StorageSettings {
+            list: DEFAULT_LIST.to_string(),
+            trash_days: 30,
+            backups: 5,
+            encryption: false,
+            read_only: false,
+        }
+    }
+}
+
+impl Default for NextSettings {
+    fn default() -> NextSettings {
+        NextSettings {
+            priority_weight: 1.0,
+            due_weight: 2.0,
+            age_weight: 0.5,
+            pinned_weight: 3.0,
+        }
+    }
+}
+
+impl Default for BehaviorSettings {
+    fn default() -> BehaviorSettings {
+        BehaviorSettings {
+            silent: false,
+            remind_days: 1,
+            confirm: true,
+            warn_duplicates: true,
+            strict: false,
+            edit_mode: EditMode::Prompt,
+            capacity: None,
+            project: None,
+            context: None,
+        }
+    }
+}
+
+impl Settings {
+    /// Extract settings from config file.
+    /// If a config doesn't exist, make one (except in read-only mode), carrying over the settings
+    /// of an old JSON config.
+    pub fn load() -> Result<Settings, TodoError> {
+        let config_path = settings_path()?;
+        info!("Using the settings file {}", config_path.display());
+        let settings = Settings::read()?;
+        if read_only() || config_path.exists() {
+            return Ok(settings);
+        }
+
+        settings.save()?;
+        // The old config is only removed once the new one is written
+        let _ = fs::remove_file(config_path.with_file_name(LEGACY_SETTINGS_FILE_NAME));
+        Ok(settings)
+    }
+
+    /// Read the settings from the config file, or an old JSON config, without writing anything.
+    /// Without either, the settings are the defaults.
+    pub(crate) fn read() -> Result<Settings, TodoError> {
+        let config_path = settings_path()?;
+        if config_path.exists() {
+            debug!("Reading the settings from {}", config_path.display());
+            let settings_str = fs::read_to_string(&config_path)
+                .map_err(|err| TodoError::io("Could not read the settings file", err))?;
+            return toml::from_str(&settings_str).map_err(TodoError::ParseSettings);
+        }
+
+        let legacy_path = config_path.with_file_name(LEGACY_SETTINGS_FILE_NAME);
+        if legacy_path.exists() {
+            info!("Reading the old settings file {}", legacy_path.display());
+            Settings::migrate(&legacy_path)
+        } else {
+            debug!("There is no settings file, so the defaults are used");
+            Ok(Settings::default())
+        }
+    }
+
+    /// Read the settings of an old JSON config, in which every setting was a string.
+    /// Options that are no longer valid are left at their default.
+    fn migrate(path: &Path) -> Result<Settings, TodoError> {
+        let settings_str = fs::read_to_string(path)
+            .map_err(|err| TodoError::io("Could not read the old settings file", err))?;
+        let old: HashMap<String, String> =
+            serde_json::from_str(&settings_str).map_err(TodoError::ParseLegacySettings)?;
+
+        let mut settings = Settings::default();
+        for (name, value) in old {
+            let _ = settings.set(&name.replace('_', "-"), &value);
+        }
+        Ok(settings)
+    }
+
+    /// Write settings to disk.
+    pub fn save(&self) -> Result<(), TodoError> {
+        check_writable()?;
+        let settings_str = toml::to_string_pretty(self).map_err(TodoError::SerializeSettings)?;
+        let config_path = settings_path()?;
+        if let Some(dir) = config_path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|err| TodoError::io("Could not create config file", err))?;
+        }
+        write_atomic(&config_path, &settings_str)
+            .map_err(|err| TodoError::io("Could not create the config file", err))?;
+        info!("Wrote the settings to {}", config_path.display());
+        Ok(())
+    }
+
+    /// Change a setting to one of its options, given as on the command line (i.e., "on" for
+    /// true). The settings aren't written to disk.
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), TodoError> {
+        let invalid = || TodoError::InvalidSetting {
+            name: name.to_string(),
+            value: value.to_string(),
+        };
+        let color = || {
+            parse_color(value)
+                .map(|_| value.to_string())
+                .ok_or_else(invalid)
+        };
+
+        match name {
+            "silent" => self.behavior.silent = parse_switch(value).ok_or_else(invalid)?,
+            "list" => {
+                validate_list_name(value)?;
+                self.storage.list = value.to_string();
+            }
+            "project" => self.behavior.project = (value != "none").then(|| value.to_string()),
+            "capacity" if value == "none" => self.behavior.capacity = None,
+            "capacity" => self.behavior.capacity = Some(value.parse().map_err(|_| invalid())?),
+            "context" => {
+                let context = value.trim_start_matches('@');
+                if context.is_empty() {
+                    return Err(invalid());
+                }
+                self.behavior.context = (value != "none").then(|| context.to_string());
+            }
+            "format" => {
+                self.display.format = OutputFormat::from_str(value, false).map_err(|_| invalid())?
+            }
+            "template" if value == "none" => self.display.template = None,
+            "template" => {
+                Template::parse(value)?;
+                self.display.template = Some(value.to_string());
+            }
+            "remind-days" => self.behavior.remind_days = value.parse().map_err(|_| invalid())?,
+            "trash-days" => self.storage.trash_days = value.parse().map_err(|_| invalid())?,
+            "backups" => self.storage.backups = value.parse().map_err(|_| invalid())?,
+            "encryption" => self.storage.encryption = parse_switch(value).ok_or_else(invalid)?,
+            "read-only" => self.storage.read_only = parse_switch(value).ok_or_else(invalid)?,
+            "sort" if value == "none" => self.display.sort = None,
+            "sort" => {
+                self.display.sort = Some(SortMode::from_str(value, false).map_err(|_| invalid())?)
+            }
+            "sort-reverse" => {
+                self.display.sort_reverse = parse_switch(value).ok_or_else(invalid)?
+            }
+            "exact-dates" => self.display.exact_dates = parse_switch(value).ok_or_else(invalid)?,
+            "pager" => self.display.pager = parse_switch(value).ok_or_else(invalid)?,
+            "date-format" => {
+                self.display.date_format =
+                    DateFormat::from_str(value, false).map_err(|_| invalid())?
+            }
+            "week-start" => self.display.week_start = value.parse().map_err(|_| invalid())?,
+            "timezone" if value == "none" => self.display.timezone = None,
+            "timezone" => {
+                value.parse::<Tz>().map_err(|_| invalid())?;
+                self.display.timezone = Some(value.to_string());
+            }
+            "progress" => self.display.progress = parse_switch(value).ok_or_else(invalid)?,
+            "numbering" => {
+                self.display.numbering = Numbering::from_str(value, false).map_err(|_| invalid())?
+            }
+            "long-labels" => {
+                self.display.long_labels =
+                    LongLabels::from_str(value, false).map_err(|_| invalid())?
+            }
+            "color" => {
+                self.display.color = ColorMode::from_str(value, false).map_err(|_| invalid())?
+            }
+            "checkbox" => {
+                Checkbox::parse(value).ok_or_else(invalid)?;
+                self.display.checkbox = Some(value.to_string());
+            }
+            "on-add" => self.hooks.on_add = (value != "none").then(|| value.to_string()),
+            "on-check" => self.hooks.on_check = (value != "none").then(|| value.to_string()),
+            "on-uncheck" => self.hooks.on_uncheck = (value != "none").then(|| value.to_string()),
+            "on-edit" => self.hooks.on_edit = (value != "none").then(|| value.to_string()),
+            "on-remove" => self.hooks.on_remove = (value != "none").then(|| value.to_string()),
+            "on-write" => self.hooks.on_write = (value != "none").then(|| value.to_string()),
+            "completed-color" => self.theme.completed = color()?,
+            "in-progress-color" => self.theme.in_progress = color()?,
+            "waiting-color" => self.theme.waiting = color()?,
+            "pending-color" => self.theme.pending = color()?,
+            "overdue-color" => self.theme.overdue = color()?,
+            "pinned-color" => self.theme.pinned = color()?,
+            "priority-weight" => {
+                self.next.priority_weight = parse_weight(value).ok_or_else(invalid)?
+            }
+            "due-weight" => self.next.due_weight = parse_weight(value).ok_or_else(invalid)?,
+            "age-weight" => self.next.age_weight = parse_weight(value).ok_or_else(invalid)?,
+            "pinned-weight" => self.next.pinned_weight = parse_weight(value).ok_or_else(invalid)?,
+            "warn-duplicates" => {
+                self.behavior.warn_duplicates = parse_switch(value).ok_or_else(invalid)?
+            }
+            "confirm" => self.behavior.confirm = parse_switch(value).ok_or_else(invalid)?,
+            "strict" => self.behavior.strict = parse_switch(value).ok_or_else(invalid)?,
+            "edit-mode" => {
+                self.behavior.edit_mode = EditMode::from_str(value, false).map_err(|_| invalid())?
+            }
+            "git-commit" => self.sync.git_commit = parse_switch(value).ok_or_else(invalid)?,
+            "git-remote" => self.sync.git_remote = (value != "none").then(|| value.to_string()),
+            "caldav-url" => self.sync.caldav_url = (value != "none").then(|| value.to_string()),
+            "caldav-user" => self.sync.caldav_user = (value != "none").then(|| value.to_string()),
+            "user" => self.sync.user = (value != "none").then(|| value.to_string()),
+            _ => return Err(invalid()),
+        }
+        Ok(())
+    }
+}
+
+/// Parse the "on" or "off" option of a setting that is either enabled or disabled.
+fn parse_switch(value: &str) -> Option<bool> {
+    match value {
+        "on" => Some(true),
+        "off" => Some(false),
+        _ => None,
+    }
+}
+
+/// Parse a weight, which can't be negative.
+fn parse_weight(value: &str) -> Option<f64> {
+    value
+        .parse()
+        .ok()
+        .filter(|weight: &f64| weight.is_finite() && *weight >= 0.0)
+}
+
+/// The path of the settings file.
+fn settings_path() -> Result<PathBuf, TodoError> {
+    let mut config_path = dirs::config_dir().ok_or(TodoError::NoConfigDir)?;
+    config_path.push("todo-app");
+    config_path.push(SETTINGS_FILE_NAME);
+    Ok(config_path)
+}
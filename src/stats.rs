@@ -0,0 +1,191 @@
+use crate::archive::ArchivedTodo;
+use crate::dates;
+use crate::history::HistoryEntry;
+use crate::list::TodoList;
+use crate::todo::Todo;
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use serde::Serialize;
+
+/// A summary of a todo list and its archive.
+#[derive(Serialize)]
+pub struct Stats {
+    pub pending: usize,
+    /// Completed items that are still in the todo list.
+    pub completed: usize,
+    pub archived: usize,
+    /// The share of all items, including archived ones, that are completed (from 0 to 1).
+    pub completion_rate: f64,
+    pub completed_today: usize,
+    /// Items completed in the last 7 days, including today.
+    pub completed_week: usize,
+    /// Items completed in the last 28 days, including today.
+    pub completed_month: usize,
+    /// The incomplete item that was added first.
+    pub oldest_open: Option<OpenItem>,
+    /// The last days, oldest first and ending with today.
+    pub days: Vec<DayStats>,
+}
+
+/// An incomplete item, as listed in the statistics.
+#[derive(Serialize)]
+pub struct OpenItem {
+    /// The one-based position, as shown in `todo list`.
+    pub position: usize,
+    pub label: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// How a single day went.
+#[derive(Serialize)]
+pub struct DayStats {
+    pub date: NaiveDate,
+    /// How many items were open at the end of the day.
+    pub open: usize,
+    /// How many of the items in the todo list or its archive were completed that day.
+    pub completed: usize,
+}
+
+/// How many items of a todo list there are in each state, for scripts and status bars.
+#[derive(Serialize)]
+pub struct Counts {
+    pub total: usize,
+    pub pending: usize,
+    pub done: usize,
+    /// Pending items whose due date has passed.
+    pub overdue: usize,
+}
+
+impl Counts {
+    pub fn collect(list: &TodoList, today: NaiveDate) -> Counts {
+        let items = list.items();
+        let done = items.iter().filter(|item| item.complete).count();
+        Counts {
+            total: items.len(),
+            pending: items.len() - done,
+            done,
+            overdue: items.iter().filter(|item| item.is_overdue(today)).count(),
+        }
+    }
+
+    /// A single line such as "3/7 done, 1 overdue", leaving out the overdue items if there are
+    /// none.
+    pub fn summary(&self) -> String {
+        let mut summary = format!("{}/{} done", self.done, self.total);
+        if self.overdue > 0 {
+            summary.push_str(&format!(", {} overdue", self.overdue));
+        }
+        summary
+    }
+}
+
+/// How many items were open at the end of each of the last days, oldest first and ending with
+/// today, given how many are open now. Earlier counts are worked out by undoing the changes in
+/// the history, so changes made without `todo` (i.e., editing the data file) aren't counted.
+fn open_per_day(
+    open_now: usize,
+    history: &[HistoryEntry],
+    today: NaiveDate,
+    days: u64,
+) -> Vec<(NaiveDate, usize)> {
+    // How many more items were open after each entry than before it
+    let changes: Vec<(NaiveDate, i64)> = history
+        .iter()
+        .map(|entry| {
+            let open = |item: &Option<Todo>| item.as_ref().is_some_and(|item| !item.complete);
+            let change: i64 = entry
+                .changes
+                .iter()
+                .map(|change| open(&change.after) as i64 - open(&change.before) as i64)
+                .sum();
+            (dates::local(entry.time).date_naive(), change)
+        })
+        .collect();
+
+    let mut open = open_now as i64;
+    let mut series: Vec<(NaiveDate, usize)> = Vec::new();
+    for date in (0..days.max(1)).filter_map(|n| today.checked_sub_days(Days::new(n))) {
+        series.push((date, open.max(0) as usize));
+        let undone: i64 = changes
+            .iter()
+            .filter(|(changed_on, _)| *changed_on == date)
+            .map(|(_, change)| change)
+            .sum();
+        open -= undone;
+    }
+    series.reverse();
+    series
+}
+
+impl Stats {
+    /// Summarize the todo list and its archived items, counting completions by local date, over
+    /// a number of days up to today. The history is used to work out how many items were open on
+    /// each day.
+    pub fn collect(
+        list: &TodoList,
+        archived: &[ArchivedTodo],
+        history: &[HistoryEntry],
+        today: NaiveDate,
+        days: u64,
+    ) -> Stats {
+        let completed_on: Vec<NaiveDate> = list
+            .items()
+            .iter()
+            .chain(archived.iter().map(|item| &item.todo))
+            .filter_map(|item| item.completed_at)
+            .map(|completed_at| dates::local(completed_at).date_naive())
+            .collect();
+        let since = |days: u64| today.checked_sub_days(Days::new(days)).unwrap_or(today);
+        let (week, month) = (since(6), since(27));
+
+        let mut stats = Stats {
+            pending: 0,
+            completed: 0,
+            archived: archived.len(),
+            completion_rate: 0.0,
+            completed_today: 0,
+            completed_week: 0,
+            completed_month: 0,
+            oldest_open: None,
+            days: Vec::new(),
+        };
+        for &date in &completed_on {
+            stats.completed_today += (date == today) as usize;
+            stats.completed_week += (date >= week) as usize;
+            stats.completed_month += (date >= month) as usize;
+        }
+
+        for (i, item) in list.items().iter().enumerate() {
+            if item.complete {
+                stats.completed += 1;
+                continue;
+            }
+            stats.pending += 1;
+            if stats
+                .oldest_open
+                .as_ref()
+                .is_none_or(|oldest| item.created_at < oldest.created_at)
+            {
+                stats.oldest_open = Some(OpenItem {
+                    position: i + 1,
+                    label: item.label.clone(),
+                    created_at: item.created_at,
+                });
+            }
+        }
+
+        let done = stats.completed + stats.archived;
+        if done > 0 {
+            stats.completion_rate = done as f64 / (stats.pending + done) as f64;
+        }
+        stats.days = open_per_day(stats.pending, history, today, days)
+            .into_iter()
+            .map(|(date, open)| DayStats {
+                date,
+                open,
+                completed: completed_on.iter().filter(|&&on| on == date).count(),
+            })
+            .collect();
+
+        stats
+    }
+}
@@ -0,0 +1,775 @@
+use crate::archive::Archive;
+use crate::crypto;
+#[cfg(unix)]
+use crate::daemon::{self, Request, Response};
+use crate::error::TodoError;
+use crate::history::History;
+use crate::journal::DoneLog;
+use crate::list::TodoList;
+use crate::migrate;
+use crate::todo::Todo;
+use crate::trash::Trash;
+use log::{debug, info};
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+const DATA_FILE_NAME: &str = "todo.dat";
+const LISTS_DIR_NAME: &str = "lists";
+
+/// The name of a project's own data file, which `todo` finds by walking up from the current
+/// directory.
+pub const LOCAL_FILE_NAME: &str = ".todo";
+
+/// The name of the list stored in the original data file.
+pub const DEFAULT_LIST: &str = "default";
+
+/// Whether nothing may be written, set by --read-only or the "read-only" setting, and by
+/// --dry-run.
+static READ_ONLY: AtomicBool = AtomicBool::new(false);
+/// Whether the command is only showing what it would change, set by --dry-run.
+static DRY_RUN: AtomicBool = AtomicBool::new(false);
+/// Whether the commands of a batch are running, which only change the todo list in memory.
+static BATCH: AtomicBool = AtomicBool::new(false);
+
+/// The data file a todo list is read from and written to.
+/// After a header line with the version of the format, each line of the data file is a single
+/// todo item in JSON format.
+#[derive(Clone)]
+pub struct TodoStore {
+    path: PathBuf,
+    encrypt: bool,
+    backups: usize,
+}
+
+/// An exclusive lock on a data file, which is released when dropped.
+pub struct StoreLock {
+    _file: Option<File>,
+}
+
+impl TodoStore {
+    /// Use the data file at the given path.
+    pub fn new(path: impl Into<PathBuf>) -> TodoStore {
+        TodoStore {
+            path: path.into(),
+            encrypt: false,
+            backups: 0,
+        }
+    }
+
+    /// Encrypt the data file (and its archive and trash) when writing it. Encrypted files are
+    /// always decrypted when read, whether or not this is set.
+    pub fn encrypted(mut self, encrypt: bool) -> TodoStore {
+        self.encrypt = encrypt;
+        self
+    }
+
+    /// Keep the given number of backups of the data file ("<data file>.bak.1" being the newest),
+    /// which are made before each write. Zero keeps no backups.
+    pub fn backups(mut self, count: usize) -> TodoStore {
+        self.backups = count;
+        self
+    }
+
+    /// Use the data file of the default list in the data directory (see `open_list`), creating
+    /// the folder if it doesn't exist.
+    pub fn open_default() -> Result<TodoStore, TodoError> {
+        TodoStore::open_list(DEFAULT_LIST)
+    }
+
+    /// Use the nearest project data file (".todo"), looking in the given directory and then each
+    /// of its parents.
+    pub fn find_local(dir: &Path) -> Option<TodoStore> {
+        dir.ancestors()
+            .map(|dir| dir.join(LOCAL_FILE_NAME))
+            .find(|path| path.is_file())
+            .map(TodoStore::new)
+    }
+
+    /// Use the data file of a named list. The default list is stored in "todo.dat", and every other
+    /// list in "lists/<name>.dat" inside the data directory. The data directory is the "todo-app"
+    /// folder of the user's data directory, unless the TODO_DATA_DIR environment variable is set.
+    pub fn open_list(name: &str) -> Result<TodoStore, TodoError> {
+        validate_list_name(name)?;
+
+        let mut path = data_dir()?;
+        if name == DEFAULT_LIST {
+            path.push(DATA_FILE_NAME);
+        } else {
+            path.push(LISTS_DIR_NAME);
+            create_dir(&path)?;
+            path.push(format!("{name}.dat"));
+        }
+
+        Ok(TodoStore::new(path))
+    }
+
+    /// The names of all lists that have a data file, starting with the default list
+    /// (which is always included).
+    pub fn list_names() -> Result<Vec<String>, TodoError> {
+        let mut lists_dir = data_dir()?;
+        lists_dir.push(LISTS_DIR_NAME);
+
+        let mut names: Vec<String> = Vec::new();
+        if let Ok(entries) = fs::read_dir(&lists_dir) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.extension().is_some_and(|ext| ext == "dat")
+                    && let Some(name) = path.file_stem().and_then(|stem| stem.to_str())
+                    && validate_list_name(name).is_ok()
+                    && name != DEFAULT_LIST
+                {
+                    names.push(name.to_string());
+                }
+            }
+        }
+        names.sort();
+        names.insert(0, DEFAULT_LIST.to_string());
+
+        Ok(names)
+    }
+
+    /// The path of the data file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The archive of the todo list, which is kept next to the data file.
+    /// The default list is archived in "archive.dat", and other lists in "<name>.archive.dat".
+    pub fn archive(&self) -> Archive {
+        Archive::new(self.companion_path("archive", "dat")).encrypted(self.encrypt)
+    }
+
+    /// The trash of the todo list, which holds removed items and is kept next to the data file.
+    /// The default list's trash is "trash.dat", and other lists' is "<name>.trash.dat".
+    pub fn trash(&self) -> Trash {
+        Trash::new(self.companion_path("trash", "dat")).encrypted(self.encrypt)
+    }
+
+    /// The log of the commands that changed the todo list, which is kept next to the data file.
+    /// The default list's history is "history.jsonl", and other lists' is "<name>.history.jsonl".
+    pub fn history(&self) -> History {
+        History::new(self.companion_path("history", "jsonl")).encrypted(self.encrypt)
+    }
+
+    /// The log of the items checked in the todo list, which is kept next to the data file.
+    /// The default list's done log is "done.log", and other lists' is "<name>.done.log".
+    pub fn done_log(&self) -> DoneLog {
+        DoneLog::new(self.companion_path("done", "log")).encrypted(self.encrypt)
+    }
+
+    /// The path of a file kept next to the data file, named "<kind>.<extension>" for the default
+    /// list and "<name>.<kind>.<extension>" for other lists.
+    fn companion_path(&self, kind: &str, extension: &str) -> PathBuf {
+        let stem = self
+            .path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let file_name = if self.path.ends_with(DATA_FILE_NAME) {
+            format!("{kind}.{extension}")
+        } else {
+            format!("{stem}.{kind}.{extension}")
+        };
+        self.path.with_file_name(file_name)
+    }
+
+    /// Wait for other `todo` processes to finish with the data file, and then lock it until the
+    /// returned guard is dropped. Holding the lock across a load and save keeps concurrent
+    /// invocations from overwriting each other's changes.
+    /// In read-only mode, nothing is locked, since the lock file can't be created.
+    pub fn lock(&self) -> Result<StoreLock, TodoError> {
+        if read_only() {
+            return Ok(StoreLock { _file: None });
+        }
+        let mut lock_path = self.path.clone().into_os_string();
+        lock_path.push(".lock");
+        debug!("Locking {}", Path::new(&lock_path).display());
+
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&lock_path)
+            .map_err(|err| TodoError::io("Could not open the lock file", err))?;
+        file.lock()
+            .map_err(|err| TodoError::io("Could not lock the data file", err))?;
+
+        Ok(StoreLock { _file: Some(file) })
+    }
+
+    /// Read the todo list, from the daemon serving it if one is running, or else from the data
+    /// file. A missing data file is an empty todo list.
+    pub fn load(&self) -> Result<TodoList, TodoError> {
+        #[cfg(unix)]
+        if let Some(Response::Items { items, next_id }) =
+            daemon::request(&self.socket_path(), &Request::Load)?
+        {
+            info!(
+                "Read {} item(s) from the daemon at {}",
+                items.len(),
+                self.socket_path().display()
+            );
+            return Ok(TodoList::from_items(items).with_next_id(next_id));
+        }
+        self.load_file()
+    }
+
+    /// Read the todo list like `load`, but without the lock, for commands that have to be quick
+    /// and only read it, i.e., `todo status`. The data file is left as it is, since another
+    /// command may be writing it.
+    pub fn peek(&self) -> Result<TodoList, TodoError> {
+        #[cfg(unix)]
+        if let Some(Response::Items { items, next_id }) =
+            daemon::request(&self.socket_path(), &Request::Load)?
+        {
+            return Ok(TodoList::from_items(items).with_next_id(next_id));
+        }
+        self.read_file(false)
+    }
+
+    /// Read the todo list from the data file, even if a daemon is serving it.
+    /// A data file of an older format is upgraded (see the `migrate` module), and lines that
+    /// aren't valid items are moved out of the data file (see `quarantine`), so that the rest of
+    /// the todo list can still be used. In read-only mode, the data file is left as it is.
+    pub(crate) fn load_file(&self) -> Result<TodoList, TodoError> {
+        self.read_file(!read_only())
+    }
+
+    /// Read the todo list from the data file, upgrading the data file and moving unreadable lines
+    /// out of it only if `write` is given.
+    fn read_file(&self, write: bool) -> Result<TodoList, TodoError> {
+        let mut items: Vec<Todo> = Vec::new();
+        let mut corrupt: Vec<&str> = Vec::new();
+
+        let contents = read_data(&self.path)?;
+        let (header, lines) = migrate::split_header(&self.path, contents.as_deref().unwrap_or(""))?;
+        let version = header.version;
+        for line in lines {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(&migrate::upgrade(line, version)) {
+                Ok(todo) => items.push(todo),
+                Err(err) => {
+                    debug!(
+                        "Couldn't read a line of {} as an item: {err}",
+                        self.path.display()
+                    );
+                    corrupt.push(line);
+                }
+            }
+        }
+        info!(
+            "Read {} item(s) from {} (format version {version})",
+            items.len(),
+            self.path.display()
+        );
+
+        // Older data files don't have the next ID, so it is kept past the IDs of the removed and
+        // archived items instead
+        let next_id = match header.next_id {
+            0 => self.removed_ids()?.max().map_or(0, |id| id + 1),
+            next_id => next_id,
+        };
+        let list = TodoList::from_items(items).with_next_id(next_id);
+        if !write {
+            if !corrupt.is_empty() {
+                eprintln!(
+                    "WARNING: {} line(s) of {} couldn't be read as items, and were skipped.",
+                    corrupt.len(),
+                    self.path.display()
+                );
+            }
+        } else if !corrupt.is_empty() {
+            self.quarantine(&corrupt, &list)?;
+        } else if version < migrate::CURRENT_VERSION && contents.is_some() {
+            info!(
+                "Upgrading {} to format version {}",
+                self.path.display(),
+                migrate::CURRENT_VERSION
+            );
+            self.write_items(&list)?;
+        }
+        Ok(list)
+    }
+
+    /// The IDs of the items in the trash and archive.
+    fn removed_ids(&self) -> Result<impl Iterator<Item = u64>, TodoError> {
+        let trashed = self.trash().load()?.into_iter().map(|item| item.todo.id);
+        let archived = self.archive().load()?.into_iter().map(|item| item.todo.id);
+        Ok(trashed.chain(archived))
+    }
+
+    /// Append lines of the data file that aren't valid items to the ".corrupt" file next to it,
+    /// where they can be fixed by hand, and write the data file again without them.
+    fn quarantine(&self, lines: &[&str], list: &TodoList) -> Result<(), TodoError> {
+        let corrupt_path = self.corrupt_path();
+        let mut contents = read_data(&corrupt_path)?.unwrap_or_default();
+        for line in lines {
+            contents.push_str(line);
+            contents.push('\n');
+        }
+        write_data(
+            &corrupt_path,
+            &contents,
+            self.encrypt,
+            "Could not write the file for unreadable items",
+        )?;
+        self.write_items(list)?;
+
+        eprintln!(
+            "WARNING: {} line(s) of {} couldn't be read as items, and were moved to {}.",
+            lines.len(),
+            self.path.display(),
+            corrupt_path.display()
+        );
+        Ok(())
+    }
+
+    /// Write the todo list to the data file, through the daemon serving it if one is running.
+    /// The previous contents of the data file are kept so the write can be undone.
+    pub fn save(&self, list: &TodoList) -> Result<(), TodoError> {
+        check_writable()?;
+        #[cfg(unix)]
+        {
+            let request = Request::Save {
+                items: list.items().to_vec(),
+                next_id: list.next_id(),
+            };
+            if daemon::request(&self.socket_path(), &request)?.is_some() {
+                info!(
+                    "Sent {} item(s) to the daemon at {}",
+                    list.len(),
+                    self.socket_path().display()
+                );
+                return Ok(());
+            }
+        }
+        self.save_file(list)
+    }
+
+    /// Write the todo list to the data file, even if a daemon is serving it.
+    /// The previous contents of the data file, its trash, and its archive are kept so the write
+    /// can be undone. The trash and archive have to be written after the data file, so that the
+    /// contents kept are from before the command that changed them.
+    pub(crate) fn save_file(&self, list: &TodoList) -> Result<(), TodoError> {
+        check_writable()?;
+        for path in self.undoable_paths() {
+            let previous = fs::read_to_string(&path).unwrap_or_default();
+            write_atomic(&undo_path(&path), &previous)
+                .map_err(|err| TodoError::io("Could not write the undo file", err))?;
+        }
+        self.backup()?;
+        self.write_items(list)
+    }
+
+    /// Copy the data file to the newest backup, shifting the older backups along and deleting the
+    /// oldest one.
+    fn backup(&self) -> Result<(), TodoError> {
+        if self.backups == 0 || !self.path.exists() {
+            return Ok(());
+        }
+
+        let io_err = |err| TodoError::io("Could not back up the data file", err);
+        let _ = fs::remove_file(self.backup_path(self.backups));
+        for n in (1..self.backups).rev() {
+            let path = self.backup_path(n);
+            if path.exists() {
+                fs::rename(&path, self.backup_path(n + 1)).map_err(io_err)?;
+            }
+        }
+        fs::copy(&self.path, self.backup_path(1)).map_err(io_err)?;
+        debug!(
+            "Backed up {} to {}",
+            self.path.display(),
+            self.backup_path(1).display()
+        );
+        Ok(())
+    }
+
+    /// The paths of the backups of the data file, newest first.
+    pub fn backup_paths(&self) -> Vec<PathBuf> {
+        (1..)
+            .map(|n| self.backup_path(n))
+            .take_while(|path| path.exists())
+            .collect()
+    }
+
+    /// Replace the data file with a backup (1 being the newest), backing up the current data file
+    /// first so that this can be reverted too. Returns false if there is no such backup.
+    pub fn restore_backup(&self, n: usize) -> Result<bool, TodoError> {
+        let Ok(contents) = fs::read_to_string(self.backup_path(n)) else {
+            return Ok(false);
+        };
+
+        check_writable()?;
+        self.backup()?;
+        write_atomic(&self.path, &contents)
+            .map_err(|err| TodoError::io("Could not restore the data file", err))?;
+        info!(
+            "Restored {} from {}",
+            self.path.display(),
+            self.backup_path(n).display()
+        );
+        Ok(true)
+    }
+
+    /// Write the items of the todo list to the data file, in the current format.
+    fn write_items(&self, list: &TodoList) -> Result<(), TodoError> {
+        let mut buf = migrate::header(list.next_id());
+        buf.push('\n');
+        for item in list.items() {
+            let item_serialized = serde_json::to_string(item).map_err(TodoError::Serialize)?;
+            buf.push_str(&item_serialized);
+            buf.push('\n');
+        }
+
+        debug!("Writing {} item(s) to {}", list.len(), self.path.display());
+        write_data(
+            &self.path,
+            &buf,
+            self.encrypt,
+            "Could not write to the data file",
+        )
+    }
+
+    /// Write the todo list, its archive, its trash, its history, its done log, and its backups
+    /// again, i.e., to encrypt or decrypt them.
+    /// The undo file is removed, since it may hold the old contents.
+    pub fn rewrite(&self) -> Result<(), TodoError> {
+        self.save_file(&self.load_file()?)?;
+        for path in self.undoable_paths() {
+            let _ = fs::remove_file(undo_path(&path));
+        }
+
+        let archive = self.archive();
+        if archive.path().exists() {
+            archive.save(&archive.load()?)?;
+        }
+        let trash = self.trash();
+        if trash.path().exists() {
+            trash.save(&trash.load()?)?;
+        }
+        let history = self.history();
+        if history.path().exists() {
+            history.save(&history.load()?)?;
+        }
+        let done_log = self.done_log();
+        if done_log.path().exists() {
+            done_log.save(&done_log.load()?)?;
+        }
+        for path in self.backup_paths() {
+            let contents = read_data(&path)?.unwrap_or_default();
+            write_data(&path, &contents, self.encrypt, "Could not write a backup")?;
+        }
+        Ok(())
+    }
+
+    /// Restore the data file, its trash, and its archive to how they were before the last write.
+    /// Returns false if there is nothing to undo. Only the last write can be undone.
+    pub fn undo(&self) -> Result<bool, TodoError> {
+        if !undo_path(&self.path).exists() {
+            return Ok(false);
+        }
+        check_writable()?;
+
+        // The data file goes last, since its undo file is what tells there is something to undo
+        for path in self.undoable_paths().into_iter().rev() {
+            let undo = undo_path(&path);
+            if !undo.exists() {
+                continue;
+            }
+            fs::rename(&undo, &path)
+                .map_err(|err| TodoError::io("Could not restore the data file", err))?;
+            info!("Restored {} from {}", path.display(), undo.display());
+        }
+        Ok(true)
+    }
+
+    /// The files a write can be undone for: the data file, its trash, and its archive.
+    fn undoable_paths(&self) -> [PathBuf; 3] {
+        [
+            self.path.clone(),
+            self.trash().path().to_path_buf(),
+            self.archive().path().to_path_buf(),
+        ]
+    }
+
+    /// The path of the socket a daemon serving the todo list listens on.
+    pub fn socket_path(&self) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(".sock");
+        PathBuf::from(path)
+    }
+
+    /// The path of a backup of the data file, 1 being the newest.
+    fn backup_path(&self, n: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".bak.{n}"));
+        PathBuf::from(path)
+    }
+
+    /// The path of the file lines of the data file that couldn't be read are moved to.
+    fn corrupt_path(&self) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(".corrupt");
+        PathBuf::from(path)
+    }
+}
+
+/// The path of the file holding a file's contents before the last write of the data file.
+fn undo_path(path: &Path) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(".undo");
+    PathBuf::from(path)
+}
+
+/// Read a data file, decrypting it if it is encrypted. A missing data file has no contents.
+pub(crate) fn read_data(path: &Path) -> Result<Option<String>, TodoError> {
+    let Ok(contents) = fs::read_to_string(path) else {
+        debug!("{} doesn't exist yet", path.display());
+        return Ok(None);
+    };
+    if !crypto::is_encrypted(&contents) {
+        debug!("Read {}", path.display());
+        return Ok(Some(contents));
+    }
+
+    debug!("Decrypting {}", path.display());
+    match crypto::decrypt(&contents)? {
+        Some(contents) => Ok(Some(contents)),
+        None => Err(TodoError::Decrypt(path.to_path_buf())),
+    }
+}
+
+/// Write a data file atomically, encrypting it first if asked to.
+/// The context describes the file in the error if writing fails.
+pub(crate) fn write_data(
+    path: &Path,
+    contents: &str,
+    encrypt: bool,
+    context: &str,
+) -> Result<(), TodoError> {
+    check_writable()?;
+    let encrypted;
+    let contents = if encrypt {
+        encrypted = crypto::encrypt(contents)?;
+        &encrypted
+    } else {
+        contents
+    };
+
+    write_atomic(path, contents).map_err(|err| TodoError::io(context, err))?;
+    info!(
+        "Wrote {}{}",
+        path.display(),
+        if encrypt { " (encrypted)" } else { "" }
+    );
+    Ok(())
+}
+
+/// Write a file such that it either keeps its old contents or has the new contents, even if the
+/// program is interrupted. The contents are written to a temporary file in the same directory,
+/// flushed to disk, and then renamed over the original file.
+pub(crate) fn write_atomic(path: &Path, contents: &str) -> io::Result<()> {
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_path);
+
+    let result = (|| {
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(contents.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, path)
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+        return result;
+    }
+
+    // Make sure the rename itself is on disk. Not every platform can open a directory for this.
+    if let Some(dir) = path.parent()
+        && let Ok(dir) = File::open(dir)
+    {
+        let _ = dir.sync_all();
+    }
+
+    Ok(())
+}
+
+/// Check that a list name can be used as a file name.
+pub fn validate_list_name(name: &str) -> Result<(), TodoError> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+
+    if valid {
+        Ok(())
+    } else {
+        Err(TodoError::InvalidListName(name.to_string()))
+    }
+}
+
+/// Don't write anything from now on: writing a file fails with `TodoError::ReadOnly`, and
+/// directories and lock files aren't created.
+pub(crate) fn set_read_only() {
+    READ_ONLY.store(true, Ordering::Relaxed);
+}
+
+/// Don't write anything, like `set_read_only`, but fail with `TodoError::DryRun` instead, since
+/// the command is only showing what it would change.
+pub(crate) fn set_dry_run() {
+    DRY_RUN.store(true, Ordering::Relaxed);
+    set_read_only();
+}
+
+/// Don't write anything while the commands of a batch run, failing with `TodoError::Batch`, since
+/// the todo list is only written after the last of them.
+pub(crate) fn set_batch(batch: bool) {
+    BATCH.store(batch, Ordering::Relaxed);
+}
+
+/// Whether read-only mode is on (see `set_read_only`).
+pub(crate) fn read_only() -> bool {
+    READ_ONLY.load(Ordering::Relaxed)
+}
+
+/// Fail with `TodoError::ReadOnly` in read-only mode (or `TodoError::DryRun` for a dry run, and
+/// `TodoError::Batch` in a batch), before writing anything.
+pub(crate) fn check_writable() -> Result<(), TodoError> {
+    if BATCH.load(Ordering::Relaxed) {
+        Err(TodoError::Batch)
+    } else if DRY_RUN.load(Ordering::Relaxed) {
+        Err(TodoError::DryRun)
+    } else if read_only() {
+        Err(TodoError::ReadOnly)
+    } else {
+        Ok(())
+    }
+}
+
+/// The directory given by TODO_DATA_DIR, or else the "todo-app" folder of the user's data
+/// directory, creating it if it doesn't exist (except in read-only mode).
+pub(crate) fn data_dir() -> Result<PathBuf, TodoError> {
+    if let Some(path) = env::var_os("TODO_DATA_DIR").filter(|path| !path.is_empty()) {
+        let path = PathBuf::from(path);
+        create_dir(&path)?;
+        return Ok(path);
+    }
+
+    let mut path = dirs::data_dir().ok_or(TodoError::NoDataDir)?;
+    path.push("todo-app");
+    create_dir(&path)?;
+    Ok(path)
+}
+
+fn create_dir(path: &Path) -> Result<(), TodoError> {
+    if read_only() {
+        return Ok(());
+    }
+    fs::create_dir_all(path).map_err(|err| {
+        TodoError::io(
+            format!("Could not create the data directory at {}", path.display()),
+            err,
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::list::{ItemRef, Selection};
+
+    /// A new, empty directory for the files of a test.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("todo-test-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn labels(list: &TodoList) -> Vec<&str> {
+        list.items()
+            .iter()
+            .map(|item| item.label.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn undoing_a_restore_puts_the_item_back_in_the_trash() {
+        let store = TodoStore::new(temp_dir("undo-restore").join(DATA_FILE_NAME));
+        let trash = store.trash();
+        let mut list = TodoList::new();
+        list.add("a");
+        list.add("b");
+        store.save_file(&list).unwrap();
+
+        // todo remove 1
+        let removed = list.remove(&[Selection::Item(ItemRef::Position(1))]);
+        store.save_file(&list).unwrap();
+        trash.append(removed).unwrap();
+
+        // todo restore 1
+        let mut trashed = trash.load().unwrap();
+        list.restore(trashed.remove(0).todo);
+        store.save_file(&list).unwrap();
+        trash.save(&trashed).unwrap();
+
+        assert!(store.undo().unwrap());
+        assert_eq!(labels(&store.load_file().unwrap()), ["b"]);
+        let trashed = trash.load().unwrap();
+        assert_eq!(trashed.len(), 1);
+        assert_eq!(trashed[0].todo.label, "a");
+    }
+
+    #[test]
+    fn undoing_an_archive_takes_the_items_out_of_the_archive() {
+        let store = TodoStore::new(temp_dir("undo-archive").join(DATA_FILE_NAME));
+        let archive = store.archive();
+        let mut list = TodoList::new();
+        list.add("a");
+        list.add("b");
+        list.set_complete(&[Selection::Item(ItemRef::Position(1))], true);
+        store.save_file(&list).unwrap();
+
+        // todo archive
+        let completed = list.remove(&[Selection::Checked]);
+        store.save_file(&list).unwrap();
+        archive.append(completed).unwrap();
+
+        assert!(store.undo().unwrap());
+        assert_eq!(labels(&store.load_file().unwrap()), ["a", "b"]);
+        assert!(archive.load().unwrap().is_empty());
+        // Only the last write can be undone
+        assert!(!store.undo().unwrap());
+    }
+
+    #[test]
+    fn ids_are_not_reused_after_the_data_file_is_read_again() {
+        let store = TodoStore::new(temp_dir("next-id").join(DATA_FILE_NAME));
+        let mut list = TodoList::new();
+        list.add("a");
+        let id = list.add("b");
+        list.remove(&[Selection::Item(ItemRef::Id(id))]);
+        store.save_file(&list).unwrap();
+
+        let mut list = store.load_file().unwrap();
+        assert_eq!(list.add("c"), id + 1);
+    }
+
+    #[test]
+    fn older_data_files_keep_ids_past_the_trash() {
+        let store = TodoStore::new(temp_dir("next-id-v2").join(DATA_FILE_NAME));
+        store.trash().append(vec![Todo::new(2, "b")]).unwrap();
+        let item = serde_json::to_string(&Todo::new(1, "a")).unwrap();
+        fs::write(store.path(), format!("{{\"version\":2}}\n{item}\n")).unwrap();
+
+        let mut list = store.load_file().unwrap();
+        assert_eq!(list.add("c"), 3);
+    }
+}
@@ -0,0 +1,156 @@
+//! Format strings for printing items, set with the "template" setting, i.e.,
+//! `{index}. [{status}] {label} {due:%m-%d}`.
+//!
+//! Fields are written in braces, and dates take an optional strftime format after a colon. Fields
+//! the item doesn't have (i.e., a due date) are left empty. Write `{{` and `}}` for literal braces.
+
+use crate::dates;
+use crate::error::TodoError;
+use crate::todo::Todo;
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, NaiveDate, Utc};
+
+/// The fields a template can use, and a description of each.
+pub const FIELDS: &[(&str, &str)] = &[
+    ("index", "The position in the todo list"),
+    ("id", "The stable ID"),
+    (
+        "status",
+        "\"done\", \"overdue\", \"snoozed\", or \"pending\"",
+    ),
+    ("check", "\"x\" if completed, otherwise a space"),
+    ("label", "The label"),
+    ("priority", "\"high\", \"medium\", or \"low\""),
+    (
+        "due",
+        "The due date (Default format = the \"date-format\" setting)",
+    ),
+    ("tags", "The tags, i.e., \"+work +urgent\""),
+    ("note", "The note"),
+    (
+        "created",
+        "When it was created (Default format = the \"date-format\" setting and %H:%M)",
+    ),
+    (
+        "completed",
+        "When it was completed (Default format = the \"date-format\" setting and %H:%M)",
+    ),
+];
+
+/// A parsed format string.
+#[derive(Clone)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+#[derive(Clone)]
+enum Part {
+    Text(String),
+    /// A field name, and the date format given for it.
+    Field(String, Option<String>),
+}
+
+impl Template {
+    /// Parse a format string, checking that its fields exist and that its date formats are valid.
+    pub fn parse(template: &str) -> Result<Template, TodoError> {
+        let invalid = |reason: String| TodoError::InvalidTemplate {
+            template: template.to_string(),
+            reason,
+        };
+
+        let mut parts: Vec<Part> = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.as_str().starts_with('{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.as_str().starts_with('}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '}' => return Err(invalid("a \"}\" has no matching \"{\"".to_string())),
+                '{' => {
+                    let rest = chars.as_str();
+                    let end = rest
+                        .find('}')
+                        .ok_or_else(|| invalid("a \"{\" has no matching \"}\"".to_string()))?;
+                    let (name, format) = match rest[..end].split_once(':') {
+                        Some((name, format)) => (name.trim(), Some(format.to_string())),
+                        None => (rest[..end].trim(), None),
+                    };
+
+                    if !FIELDS.iter().any(|(field, _)| *field == name) {
+                        return Err(invalid(format!("there is no field \"{name}\"")));
+                    }
+                    if let Some(format) = &format {
+                        if !matches!(name, "due" | "created" | "completed") {
+                            return Err(invalid(format!("\"{name}\" isn't a date")));
+                        }
+                        if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+                            return Err(invalid(format!("\"{format}\" isn't a valid date format")));
+                        }
+                    }
+
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(Part::Field(name.to_string(), format));
+                    chars = rest[end + 1..].chars();
+                }
+                c => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+
+        Ok(Template { parts })
+    }
+
+    /// Fill in the fields of an item at a (zero-based) position in the todo list.
+    pub fn render(&self, i: usize, item: &Todo, today: NaiveDate) -> String {
+        let timestamp_format = format!("{} %H:%M", dates::date_pattern());
+        let date = |date: Option<NaiveDate>, format: &Option<String>| {
+            date.map_or(String::new(), |date| {
+                date.format(format.as_deref().unwrap_or(dates::date_pattern()))
+                    .to_string()
+            })
+        };
+        let timestamp = |timestamp: Option<DateTime<Utc>>, format: &Option<String>| {
+            timestamp.map_or(String::new(), |timestamp| {
+                dates::local(timestamp)
+                    .format(format.as_deref().unwrap_or(&timestamp_format))
+                    .to_string()
+            })
+        };
+
+        let mut line = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Text(text) => line.push_str(text),
+                Part::Field(name, format) => line.push_str(&match name.as_str() {
+                    "index" => (i + 1).to_string(),
+                    "id" => item.id.to_string(),
+                    "status" => item.status(today).to_string(),
+                    "check" => if item.complete { "x" } else { " " }.to_string(),
+                    "label" => item.label.clone(),
+                    "priority" => item.priority.map_or(String::new(), |p| p.to_string()),
+                    "due" => date(item.due, format),
+                    "tags" => {
+                        let tags: Vec<String> =
+                            item.tags.iter().map(|tag| format!("+{tag}")).collect();
+                        tags.join(" ")
+                    }
+                    "note" => item.note.clone().unwrap_or_default(),
+                    "created" => timestamp(Some(item.created_at), format),
+                    "completed" => timestamp(item.completed_at, format),
+                    _ => unreachable!("fields are checked when parsing"),
+                }),
+            }
+        }
+        line
+    }
+}
@@ -0,0 +1,370 @@
+use crate::estimate::Estimate;
+use crate::recur::Recurrence;
+use chrono::{DateTime, NaiveDate, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// A single item in the todo list.
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct Todo {
+    /// A stable identifier that doesn't change when items are added, removed, or sorted.
+    #[serde(default)]
+    pub id: u64,
+    pub label: String,
+    pub complete: bool,
+    /// Whether an incomplete item is being worked on or waiting on something. Completed items
+    /// are done whatever this is, see `Todo::state`.
+    #[serde(default, skip_serializing_if = "Status::is_pending")]
+    pub progress: Status,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due: Option<NaiveDate>,
+    /// The day the item can be started on. It is left out of `todo list` until then.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<NaiveDate>,
+    /// When the item comes back after it is checked, i.e., every week.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recurrence: Option<Recurrence>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<Priority>,
+    /// How much work the item is expected to take, i.e., "2h" or "3pt".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimate: Option<Estimate>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// The project the item belongs to, i.e., "website".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    /// Where or with what the item can be done, without the "@", i.e., "home" for "@home".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    /// Who is responsible for the item in a shared list, i.e., "alice".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assignee: Option<String>,
+    /// A longer, possibly multi-line description of the item.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// A web page the item is about, opened with `todo open`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Remarks added over time with `todo comment`, oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub comments: Vec<Comment>,
+    /// When the item was added. Items from older data files, which didn't record this, are given
+    /// the time they were migrated.
+    #[serde(default = "Utc::now")]
+    pub created_at: DateTime<Utc>,
+    /// When the item was last checked, if it is completed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<DateTime<Utc>>,
+    /// Pinned items are listed before the other items, whatever the order.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
+    /// Focused items are the only ones `todo list` shows, until `todo unfocus`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub focused: bool,
+    /// The day the item was planned for with `todo plan`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub planned: Option<NaiveDate>,
+    /// The item is left out of `todo list` before this date.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hidden_until: Option<NaiveDate>,
+    /// The IDs of the items that have to be completed before this one.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<u64>,
+    /// The spans of time spent working on the item, oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub time_log: Vec<WorkInterval>,
+    /// How many pomodoro work sessions were finished on the item.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub pomodoros: u32,
+}
+
+fn is_zero(n: &u32) -> bool {
+    *n == 0
+}
+
+/// A span of time spent working on an item, recorded with `todo start` and `todo stop`.
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct WorkInterval {
+    pub start: DateTime<Utc>,
+    /// Missing while the timer is running.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<DateTime<Utc>>,
+}
+
+/// A timestamped remark on an item, i.e., "waiting on vendor".
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct Comment {
+    pub at: DateTime<Utc>,
+    /// The "user" setting of whoever added it, if it was set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    pub text: String,
+}
+
+impl Todo {
+    /// Create a new, incomplete item.
+    pub fn new(id: u64, label: impl Into<String>) -> Todo {
+        Todo {
+            id,
+            label: label.into(),
+            complete: false,
+            progress: Status::Pending,
+            due: None,
+            start: None,
+            recurrence: None,
+            priority: None,
+            estimate: None,
+            tags: Vec::new(),
+            project: None,
+            context: None,
+            assignee: None,
+            note: None,
+            url: None,
+            comments: Vec::new(),
+            created_at: Utc::now(),
+            completed_at: None,
+            pinned: false,
+            focused: false,
+            planned: None,
+            hidden_until: None,
+            depends_on: Vec::new(),
+            time_log: Vec::new(),
+            pomodoros: 0,
+        }
+    }
+
+    /// A new, incomplete item with the same details (label, due date, tags, note, ...), but
+    /// without the progress made on this one: its time log, pomodoros, and comments.
+    /// The copy doesn't have an ID yet, so it should be added to a list with `TodoList::push`.
+    pub fn duplicate(&self) -> Todo {
+        Todo {
+            id: 0,
+            complete: false,
+            progress: Status::Pending,
+            created_at: Utc::now(),
+            completed_at: None,
+            comments: Vec::new(),
+            time_log: Vec::new(),
+            pomodoros: 0,
+            ..self.clone()
+        }
+    }
+
+    /// The next occurrence of a recurring item, due on the first date of its recurrence after its
+    /// due date, or after today if it is overdue. `None` if the item doesn't recur, or its
+    /// recurrence has ended. The occurrence doesn't have an ID yet, like a duplicate.
+    pub fn next_occurrence(&self, today: NaiveDate) -> Option<Todo> {
+        let recurrence = self.recurrence.as_ref()?;
+        let start = self.due.unwrap_or(today);
+        let (due, recurrence) = recurrence.following(start.max(today), start)?;
+        Some(Todo {
+            due: Some(due),
+            recurrence: Some(recurrence),
+            hidden_until: None,
+            ..self.duplicate()
+        })
+    }
+
+    /// Mark the item as completed (or incomplete), recording when it was completed.
+    /// Checking an item that is already completed keeps its completion time, and an item that is
+    /// unchecked is pending again.
+    pub fn set_complete(&mut self, complete: bool) {
+        if complete && !self.complete {
+            self.completed_at = Some(Utc::now());
+        } else if !complete {
+            self.completed_at = None;
+        }
+        self.complete = complete;
+        self.progress = Status::Pending;
+    }
+
+    /// Where the item is in its workflow: done if it is completed, and otherwise pending, in
+    /// progress, or waiting.
+    pub fn state(&self) -> Status {
+        if self.complete {
+            Status::Done
+        } else {
+            self.progress
+        }
+    }
+
+    /// Move the item to a stage of its workflow, checking or unchecking it as needed.
+    pub fn set_state(&mut self, status: Status) {
+        self.set_complete(status == Status::Done);
+        if status != Status::Done {
+            self.progress = status;
+        }
+    }
+
+    /// Whether the item was snoozed until a date after today.
+    pub fn is_snoozed(&self, today: NaiveDate) -> bool {
+        self.hidden_until.is_some_and(|date| date > today)
+    }
+
+    /// Whether the item was planned for today with `todo plan`.
+    pub fn is_planned(&self, today: NaiveDate) -> bool {
+        self.planned == Some(today)
+    }
+
+    /// Whether the item can't be started until a date after today.
+    pub fn is_deferred(&self, today: NaiveDate) -> bool {
+        self.start.is_some_and(|date| date > today)
+    }
+
+    /// Whether the item is left out of `todo list` today, i.e., it is snoozed or can't be started
+    /// yet.
+    pub fn is_hidden(&self, today: NaiveDate) -> bool {
+        self.is_snoozed(today) || self.is_deferred(today)
+    }
+
+    /// Whether the item is incomplete and its due date has passed.
+    pub fn is_overdue(&self, today: NaiveDate) -> bool {
+        !self.complete && self.due.is_some_and(|date| date < today)
+    }
+
+    /// A word for the state of the item: "done", "overdue", "snoozed", "in-progress", "waiting",
+    /// or "pending".
+    pub fn status(&self, today: NaiveDate) -> &'static str {
+        if self.complete {
+            "done"
+        } else if self.is_overdue(today) {
+            "overdue"
+        } else if self.is_snoozed(today) {
+            "snoozed"
+        } else {
+            self.progress.name()
+        }
+    }
+
+    /// Whether a timer is running on the item.
+    pub fn is_timing(&self) -> bool {
+        self.time_log
+            .last()
+            .is_some_and(|interval| interval.end.is_none())
+    }
+
+    /// The total time spent working on the item, counting a running timer up to now.
+    pub fn time_spent(&self, now: DateTime<Utc>) -> TimeDelta {
+        self.time_log
+            .iter()
+            .map(|interval| interval.end.unwrap_or(now) - interval.start)
+            .sum()
+    }
+
+    /// Whether the item has the given tag.
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    /// Add a tag to the item. A leading "+" is ignored, and tags the item already has are skipped.
+    pub fn add_tag(&mut self, tag: &str) {
+        let tag = tag.trim_start_matches('+');
+        if !tag.is_empty() && !self.has_tag(tag) {
+            self.tags.push(tag.to_string());
+        }
+    }
+
+    /// Remove a tag from the item. A leading "+" is ignored.
+    pub fn remove_tag(&mut self, tag: &str) {
+        let tag = tag.trim_start_matches('+');
+        self.tags.retain(|t| t != tag);
+    }
+}
+
+/// The stages of an item's workflow, from not started to completed.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum Status {
+    #[default]
+    Pending,
+    InProgress,
+    /// Waiting on someone or something else before it can go on.
+    Waiting,
+    Done,
+}
+
+impl Status {
+    /// The name of the stage, i.e., "in-progress".
+    pub fn name(&self) -> &'static str {
+        match self {
+            Status::Pending => "pending",
+            Status::InProgress => "in-progress",
+            Status::Waiting => "waiting",
+            Status::Done => "done",
+        }
+    }
+
+    fn is_pending(&self) -> bool {
+        *self == Status::Pending
+    }
+}
+
+impl FromStr for Status {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" | "todo" => Ok(Status::Pending),
+            "in-progress" | "started" | "doing" => Ok(Status::InProgress),
+            "waiting" | "wait" => Ok(Status::Waiting),
+            "done" => Ok(Status::Done),
+            _ => Err(format!(
+                "invalid status \"{s}\" (expected pending, in-progress, waiting, or done)"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name().replace('-', " "))
+    }
+}
+
+/// Priority levels, ordered from most to least important.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    High,
+    Medium,
+    Low,
+}
+
+impl FromStr for Priority {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "high" | "h" => Ok(Priority::High),
+            "medium" | "med" | "m" => Ok(Priority::Medium),
+            "low" | "l" => Ok(Priority::Low),
+            _ => Err(format!(
+                "invalid priority \"{s}\" (expected high, medium, or low)"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Priority::High => "high",
+            Priority::Medium => "medium",
+            Priority::Low => "low",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl Priority {
+    /// The prefix shown before the label in the todo list.
+    pub fn marker(&self) -> &'static str {
+        match self {
+            Priority::High => "!!! ",
+            Priority::Medium => "!! ",
+            Priority::Low => "! ",
+        }
+    }
+}
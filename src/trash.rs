@@ -0,0 +1,111 @@
+use crate::error::TodoError;
+use crate::store::{read_data, write_data};
+use crate::todo::Todo;
+use chrono::{DateTime, Days, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// An item that was removed from the todo list.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TrashedTodo {
+    #[serde(flatten)]
+    pub todo: Todo,
+    /// When the item was removed.
+    pub deleted_at: DateTime<Utc>,
+}
+
+/// The file removed items of a todo list are kept in until they are restored or purged.
+/// Like the data file, each line is a single item in JSON format.
+pub struct Trash {
+    path: PathBuf,
+    encrypt: bool,
+}
+
+impl Trash {
+    /// Use the trash file at the given path.
+    pub fn new(path: impl Into<PathBuf>) -> Trash {
+        Trash {
+            path: path.into(),
+            encrypt: false,
+        }
+    }
+
+    /// Encrypt the trash file when writing it.
+    pub fn encrypted(mut self, encrypt: bool) -> Trash {
+        self.encrypt = encrypt;
+        self
+    }
+
+    /// The path of the trash file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Read the removed items, oldest first. A missing trash file has no items.
+    pub fn load(&self) -> Result<Vec<TrashedTodo>, TodoError> {
+        let mut items: Vec<TrashedTodo> = Vec::new();
+
+        if let Some(str) = read_data(&self.path)? {
+            for line in str.lines() {
+                let item = serde_json::from_str(line).map_err(|source| TodoError::ParseLine {
+                    line: line.to_string(),
+                    source,
+                })?;
+                items.push(item);
+            }
+        }
+
+        Ok(items)
+    }
+
+    /// Write the removed items to the trash file.
+    pub fn save(&self, items: &[TrashedTodo]) -> Result<(), TodoError> {
+        let mut buf = String::new();
+        for item in items {
+            let item_serialized = serde_json::to_string(item).map_err(TodoError::Serialize)?;
+            buf.push_str(&item_serialized);
+            buf.push('\n');
+        }
+
+        write_data(
+            &self.path,
+            &buf,
+            self.encrypt,
+            "Could not write to the trash file",
+        )
+    }
+
+    /// Add items to the trash, marking them as removed now.
+    pub fn append(&self, items: Vec<Todo>) -> Result<(), TodoError> {
+        if items.is_empty() {
+            return Ok(());
+        }
+
+        let mut trashed = self.load()?;
+        let now = Utc::now();
+        trashed.extend(items.into_iter().map(|todo| TrashedTodo {
+            todo,
+            deleted_at: now,
+        }));
+        self.save(&trashed)
+    }
+
+    /// Permanently delete the items that were removed more than the given number of days ago.
+    /// Zero days keeps every item.
+    pub fn purge(&self, days: u64) -> Result<(), TodoError> {
+        if days == 0 || !self.path.exists() {
+            return Ok(());
+        }
+        let Some(cutoff) = Utc::now().checked_sub_days(Days::new(days)) else {
+            return Ok(());
+        };
+
+        let mut trashed = self.load()?;
+        let count = trashed.len();
+        trashed.retain(|item| item.deleted_at >= cutoff);
+        if trashed.len() == count {
+            return Ok(());
+        }
+        self.save(&trashed)
+    }
+}
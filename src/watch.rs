@@ -0,0 +1,50 @@
+//! Keeping the todo list on screen, printing it again whenever the data file changes, i.e., in a
+//! tmux pane while items are added from another terminal.
+
+use crate::display::{ListOptions, list_items};
+use crate::error::TodoError;
+use crate::store::TodoStore;
+use notify::{RecursiveMode, Watcher};
+use std::path::Path;
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// How long to wait for the rest of the events of a single write before printing the list.
+const SETTLE_TIME: Duration = Duration::from_millis(50);
+
+/// Print the todo list, and print it again every time the data file changes, until stopped.
+pub fn watch(store: &TodoStore, options: &ListOptions) -> Result<(), TodoError> {
+    let watch_err = |err: notify::Error| TodoError::Watch(err.to_string());
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).map_err(watch_err)?;
+
+    // Every write replaces the data file, so its folder is watched instead of the file itself
+    let dir = match store.path().parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    watcher
+        .watch(dir, RecursiveMode::NonRecursive)
+        .map_err(watch_err)?;
+
+    loop {
+        // JSON output is left as a stream of arrays for scripts, instead of clearing the screen
+        if !options.json {
+            print!("\x1B[2J\x1B[H");
+        }
+        list_items(&store.load()?, options)?;
+
+        loop {
+            let event = rx.recv().map_err(|err| TodoError::Watch(err.to_string()))?;
+            let changed = event
+                .map_err(watch_err)?
+                .paths
+                .iter()
+                .any(|path| path.file_name() == store.path().file_name());
+            if changed {
+                break;
+            }
+        }
+        while rx.recv_timeout(SETTLE_TIME).is_ok() {}
+    }
+}